@@ -0,0 +1,79 @@
+//
+// bitboards.rs
+// Copyright © 2019-2023, Todd L Smith.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+//    this list of conditions and the following disclaimer.
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+//    this list of conditions and the following disclaimer in the documentation
+//    and/or other materials provided with the distribution.
+// 3. Neither the name of the copyright holder nor the names of its contributors
+//    may be used to endorse or promote products derived from this software
+//    without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+//
+
+//!
+//! ## Bitboard benchmark
+//!
+//! Herein is a benchmark that solves the sample boards both with and without
+//! the bitboards derived from their grids, and reports the speedup. Run it
+//! with `cargo bench`.
+//!
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use tumblesolve::Board;
+
+/// The sample boards that are well-formed and solvable, by name.
+const SOLVABLE: [(&str, &str); 4] = [
+	("board005", include_str!("../examples/board005.tsb")),
+	("board006", include_str!("../examples/board006.tsb")),
+	("board329", include_str!("../examples/board329.tsb")),
+	("board415", include_str!("../examples/board415.tsb"))
+];
+
+/// The number of times to solve each board in each representation.
+const ROUNDS: u32 = 20;
+
+/// Answer the mean time taken to solve the specified board.
+fn time (board: &Board) -> Duration
+{
+	let start = Instant::now();
+	for _ in 0..ROUNDS
+	{
+		let mut board = board.clone();
+		black_box(board.solve()).expect("board is solvable");
+	}
+	start.elapsed() / ROUNDS
+}
+
+fn main ()
+{
+	for (name, tsb) in SOLVABLE
+	{
+		let board = Board::parse(tsb).unwrap();
+		let bitboards = time(&board);
+		let grid = time(&board.clone().without_bitboards());
+		println!(
+			"{}: {:?} with bitboards, {:?} without, {:.2}× speedup",
+			name,
+			bitboards,
+			grid,
+			grid.as_secs_f64() / bitboards.as_secs_f64());
+	}
+}
@@ -36,9 +36,17 @@
 
 use std::fmt::{Display, Formatter, Result};
 use std::collections::HashMap;
-use std::num::ParseIntError;
+#[cfg(feature = "json")]
+use std::collections::BTreeMap;
+use std::iter::{from_fn, once};
 use std::result;
-use std::str::ParseBoolError;
+use std::str::FromStr;
+#[cfg(feature = "rand")]
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "rand")]
+use rand::rngs::StdRng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use tokesies::*;
 
 /******************************************************************************
@@ -53,11 +61,20 @@ pub trait Stone
 
 	/// Answer `true` if the receiver is, by nature, directly removable.
 	fn is_removable (&self) -> bool;
+
+	/// Answer the points scored by removing the receiver. By default, every
+	/// removable stone is worth `1` point, and every other stone is worth
+	/// nothing.
+	fn points (&self) -> u32
+	{
+		self.is_removable() as u32
+	}
 }
 
 /// The absence of stoniness, i.e., the nothing that does not live inside an
 /// empty cell. Always represented by `'_'` in input, as `' '` in output.
-#[derive(Copy, Clone, Hash, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NoStone;
 
 impl Stone for NoStone
@@ -83,7 +100,8 @@ impl Display for NoStone
 }
 
 /// An ordinary stone.
-#[derive(Copy, Clone, Hash, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OrdinaryStone
 {
 	/// The character that represents this ordinary stone.
@@ -91,16 +109,32 @@ pub struct OrdinaryStone
 
 	/// A bit mask that uniquely represents the color of this ordinary stone.
 	/// Exactly 1 bit must be set. Bit masks support [wild stones]. The chosen
-	/// representation imposes a 32-color limit on any specific board.
+	/// representation imposes a 64-color limit on any specific board; see
+	/// [`MAX_COLORS`].
 	///
 	/// [wild stones]: WildStone
-	color: u32
+	color: u64
 }
 
 impl OrdinaryStone
 {
+	/// Answer an ordinary stone represented by the specified character and
+	/// colored by the specified mask, for use with a [`BoardBuilder`]. Exactly
+	/// 1 bit of the mask must be set, and every stone with the same character
+	/// must have the same color; [`build`](BoardBuilder::build) enforces this.
+	pub fn new (rep: char, color: u64) -> OrdinaryStone
+	{
+		OrdinaryStone { rep, color }
+	}
+
+	/// Answer the character that represents the receiver.
+	pub fn rep (&self) -> char
+	{
+		self.rep
+	}
+
 	/// Answer the color of the receiver.
-	pub fn color (&self) -> u32
+	pub fn color (&self) -> u64
 	{
 		self.color
 	}
@@ -128,9 +162,99 @@ impl Display for OrdinaryStone
 	}
 }
 
+/// An armored stone is an [ordinary stone] that must be matched several times
+/// before it is cleared. Each match strikes off one hit, and only the final
+/// match removes the stone; once a single hit remains, the stone is just an
+/// ordinary stone. Armor also shields the stone from the blast of a
+/// [bomb](BombStone). Represented by the character of the ordinary stone
+/// followed by the number of hits remaining, e.g., `r2` or `g3`, and rendered
+/// as the number of hits remaining, in the color of the ordinary stone.
+///
+/// [ordinary stone]: OrdinaryStone
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ArmoredStone
+{
+	/// The ordinary stone beneath the armor.
+	stone: OrdinaryStone,
+
+	/// The number of matches required to clear the stone, between `2` and
+	/// `9`.
+	hits_remaining: u8
+}
+
+impl ArmoredStone
+{
+	/// Answer the specified ordinary stone armored to require the specified
+	/// number of hits, for use with a [`BoardBuilder`].
+	///
+	/// # Panics
+	///
+	/// If `hits_remaining` is not between `2` and `9`.
+	pub fn new (stone: OrdinaryStone, hits_remaining: u8) -> ArmoredStone
+	{
+		assert!((2..=9).contains(&hits_remaining));
+		ArmoredStone { stone, hits_remaining }
+	}
+
+	/// Answer the [ordinary stone](OrdinaryStone) beneath the armor.
+	pub fn stone (&self) -> OrdinaryStone
+	{
+		self.stone
+	}
+
+	/// Answer the color of the receiver.
+	pub fn color (&self) -> u64
+	{
+		self.stone.color
+	}
+
+	/// Answer the number of matches required to clear the receiver.
+	pub fn hits_remaining (&self) -> u8
+	{
+		self.hits_remaining
+	}
+
+	/// Answer the stone that remains after the receiver is struck once.
+	fn struck (&self) -> AnyStone
+	{
+		match self.hits_remaining
+		{
+			2 => AnyStone::Ordinary(self.stone),
+			hits => AnyStone::Armored(ArmoredStone {
+				stone: self.stone,
+				hits_remaining: hits - 1
+			})
+		}
+	}
+}
+
+impl Stone for ArmoredStone
+{
+	/// Answer a copy of the receiver.
+	fn for_board (&self, _board: &Board) -> Self
+	{
+		*self
+	}
+
+	fn is_removable (&self) -> bool
+	{
+		true
+	}
+}
+
+impl Display for ArmoredStone
+{
+	fn fmt (&self, f: &mut Formatter) -> Result
+	{
+		write!(f, "{}", self.hits_remaining)
+	}
+}
+
 /// A survivor stone cannot be removed directly, but automatically disappears
 /// when the last stone in its row has been removed.
-#[derive(Copy, Clone, Hash, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SurvivorStone;
 
 impl Stone for SurvivorStone
@@ -158,11 +282,53 @@ impl Display for SurvivorStone
 /// A wild stone has potential to provide one or more colors; all other wild
 /// stones lose a color whenever a wild stone is committed to a particular
 /// color. A wild stone's color space is a property of the [board], not of the
-/// wild stone itself (flyweight pattern). Always represented by `*`.
+/// wild stone itself (flyweight pattern). A wild stone may further restrict
+/// itself to some of the board's wild colors, in which case it provides only
+/// the colors in both the board's color space and its own. Represented by
+/// `*`, followed by the characters of its own colors if it is restricted,
+/// e.g., `*rb`. A restricted wild stone that can no longer provide any color
+/// can never be removed, and is represented by `*-`.
 ///
 /// [board]: Board
-#[derive(Copy, Clone, Hash, Debug)]
-pub struct WildStone;
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WildStone
+{
+	/// The colors to which the wild stone is restricted, if any.
+	restriction: Option<u64>
+}
+
+impl WildStone
+{
+	/// Answer a wild stone that can provide any of the board's wild colors.
+	pub fn new () -> WildStone
+	{
+		WildStone { restriction: None }
+	}
+
+	/// Answer a wild stone that can provide only those of the board's wild
+	/// colors that are present in the specified mask. The mask must not be
+	/// empty.
+	pub fn restricted (mask: u64) -> WildStone
+	{
+		assert_ne!(mask, 0);
+		WildStone { restriction: Some(mask) }
+	}
+
+	/// Answer the colors to which the receiver is restricted, or `None` if it
+	/// is unrestricted.
+	pub fn restriction (&self) -> Option<u64>
+	{
+		self.restriction
+	}
+
+	/// Answer the colors that the receiver can currently provide on the
+	/// specified board.
+	pub fn colors (&self, board: &Board) -> u64
+	{
+		board.wild_colors & self.restriction.unwrap_or(!0)
+	}
+}
 
 impl Stone for WildStone
 {
@@ -176,6 +342,12 @@ impl Stone for WildStone
 	{
 		true
 	}
+
+	/// Answer `3`, because a wild stone is the most versatile of stones.
+	fn points (&self) -> u32
+	{
+		3
+	}
 }
 
 impl Display for WildStone
@@ -186,28 +358,140 @@ impl Display for WildStone
 	}
 }
 
+/// A bomb stone has no color of its own, so it may be played as any stone of
+/// a triplet without establishing the triplet's color. When removed, it also
+/// removes every adjacent [ordinary stone] and bomb stone, orthogonally or
+/// diagonally; bombs caught in the blast explode in turn. [Wild stones] are
+/// spared, because a blast cannot decide which of their colors to consume.
+/// Always represented by `o`.
+///
+/// [ordinary stone]: OrdinaryStone
+/// [Wild stones]: WildStone
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BombStone;
+
+impl Stone for BombStone
+{
+	/// Answer a copy of the receiver.
+	fn for_board (&self, _board: &Board) -> Self
+	{
+		*self
+	}
+
+	fn is_removable (&self) -> bool
+	{
+		true
+	}
+}
+
+impl Display for BombStone
+{
+	fn fmt (&self, f: &mut Formatter) -> Result
+	{
+		write!(f, "\u{1b}[38;5;208mo")
+	}
+}
+
+/// A rainbow stone has no color of its own, but adopts the color of the
+/// triplet in which it is played, so it may complete a triplet of any color.
+/// Because it has nothing to adopt until some stone has established the
+/// triplet's color, it may not begin a triplet. Unlike a [wild stone], it
+/// does not draw upon the board's wild colors, so playing it never reduces
+/// the wild color space. Always represented by `@`.
+///
+/// [wild stone]: WildStone
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RainbowStone;
+
+impl Stone for RainbowStone
+{
+	/// Answer a copy of the receiver.
+	fn for_board (&self, _board: &Board) -> Self
+	{
+		*self
+	}
+
+	fn is_removable (&self) -> bool
+	{
+		true
+	}
+}
+
+impl Display for RainbowStone
+{
+	fn fmt (&self, f: &mut Formatter) -> Result
+	{
+		write!(f, "\u{1b}[38;5;51m@")
+	}
+}
+
 /// A toggle stone cannot be matched directly. It alternately obstructs and
-/// permits access to stones above it. Initially open is represented by `'/'`,
-/// initially closed is represented by `'+'`.
-#[derive(Copy, Clone, Hash, Debug)]
+/// permits access to stones above it, changing state every [period] turns.
+/// Initially open is represented by `'/'`, initially closed is represented by
+/// `'+'`. Either may be followed by a digit between `2` and `9` that gives the
+/// period, e.g., `/2`; otherwise, the period is `1`, i.e., the toggle stone
+/// changes state every turn.
+///
+/// [period]: ToggleStone::period
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ToggleStone
 {
-	/// The phase of the toggle stone, either `0` or `1`. The [turn number] is
-	/// added to the phase to determine whether the toggle stone is currently an
-	/// obstruction — even indicates that the toggle stone is "open", odd that
-	/// it is "closed".
+	/// The phase of the toggle stone. The [turn number] is added to the phase
+	/// to determine whether the toggle stone is currently an obstruction: the
+	/// toggle stone is "open" when the sum divided by the [period] is even,
+	/// and "closed" when it is odd. The state recurs every two periods, so
+	/// the sum is taken modulo twice the period, which keeps it from
+	/// overflowing.
 	///
 	/// [turn number]: Board::turn
-	/// [board]: Board
-	phase: u32
+	/// [period]: ToggleStone::period
+	phase: u32,
+
+	/// The number of turns between changes of state, between `1` and `9`.
+	period: u32
 }
 
 impl ToggleStone
 {
+	/// Answer a toggle stone that is initially open, i.e., `/`.
+	pub fn open () -> ToggleStone
+	{
+		ToggleStone { phase: 0, period: 1 }
+	}
+
+	/// Answer a toggle stone that is initially closed, i.e., `+`.
+	pub fn closed () -> ToggleStone
+	{
+		ToggleStone { phase: 1, period: 1 }
+	}
+
+	/// Answer a copy of the receiver that changes state every `period` turns,
+	/// but is initially in the same state as the receiver, e.g.,
+	/// `ToggleStone::closed().with_period(2)`, i.e., `+2`.
+	///
+	/// # Panics
+	///
+	/// If `period` is not between `1` and `9`.
+	pub fn with_period (&self, period: u32) -> ToggleStone
+	{
+		assert!((1..=9).contains(&period));
+		let phase = if self.is_open() { 0 } else { period };
+		ToggleStone { phase, period }
+	}
+
 	/// Answer `true` if the receiver is open, `false` otherwise.
 	pub fn is_open (&self) -> bool
 	{
-		self.phase & 1 == 0
+		(self.phase / self.period) & 1 == 0
+	}
+
+	/// Answer the number of turns between changes of state.
+	pub fn period (&self) -> u32
+	{
+		self.period
 	}
 }
 
@@ -216,7 +500,11 @@ impl Stone for ToggleStone
 	/// Answer the state of the receiver given the specified board state.
 	fn for_board (&self, board: &Board) -> Self
 	{
-		ToggleStone { phase: self.phase + board.turn }
+		let cycle = 2 * self.period;
+		ToggleStone {
+			phase: (self.phase % cycle + board.turn % cycle) % cycle,
+			period: self.period
+		}
 	}
 
 	fn is_removable (&self) -> bool
@@ -241,14 +529,18 @@ impl Display for ToggleStone
 }
 
 /// An arbitrary stone.
-#[derive(Copy, Clone, Hash, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AnyStone
 {
 	None (NoStone),
 	Ordinary (OrdinaryStone),
 	Survivor (SurvivorStone),
 	Wild (WildStone),
-	Toggle (ToggleStone)
+	Toggle (ToggleStone),
+	Bomb (BombStone),
+	Armored (ArmoredStone),
+	Rainbow (RainbowStone)
 }
 
 //
@@ -269,7 +561,10 @@ impl Stone for AnyStone
 			Ordinary(s) => Ordinary(s.for_board(board)),
 			Survivor(s) => Survivor(s.for_board(board)),
 			Wild(s) => Wild(s.for_board(board)),
-			Toggle(s) => Toggle(s.for_board(board))
+			Toggle(s) => Toggle(s.for_board(board)),
+			Bomb(s) => Bomb(s.for_board(board)),
+			Armored(s) => Armored(s.for_board(board)),
+			Rainbow(s) => Rainbow(s.for_board(board))
 		}
 	}
 
@@ -281,7 +576,25 @@ impl Stone for AnyStone
 			AnyStone::Ordinary(s) => s.is_removable(),
 			AnyStone::Survivor(s) => s.is_removable(),
 			AnyStone::Wild(s) => s.is_removable(),
-			AnyStone::Toggle(s) => s.is_removable()
+			AnyStone::Toggle(s) => s.is_removable(),
+			AnyStone::Bomb(s) => s.is_removable(),
+			AnyStone::Armored(s) => s.is_removable(),
+			AnyStone::Rainbow(s) => s.is_removable()
+		}
+	}
+
+	fn points (&self) -> u32
+	{
+		match self
+		{
+			AnyStone::None(s) => s.points(),
+			AnyStone::Ordinary(s) => s.points(),
+			AnyStone::Survivor(s) => s.points(),
+			AnyStone::Wild(s) => s.points(),
+			AnyStone::Toggle(s) => s.points(),
+			AnyStone::Bomb(s) => s.points(),
+			AnyStone::Armored(s) => s.points(),
+			AnyStone::Rainbow(s) => s.points()
 		}
 	}
 }
@@ -296,7 +609,10 @@ impl Display for AnyStone
 			AnyStone::Ordinary(s) => s.fmt(f),
 			AnyStone::Survivor(s) => s.fmt(f),
 			AnyStone::Wild(s) => s.fmt(f),
-			AnyStone::Toggle(s) => s.fmt(f)
+			AnyStone::Toggle(s) => s.fmt(f),
+			AnyStone::Bomb(s) => s.fmt(f),
+			AnyStone::Armored(s) => s.fmt(f),
+			AnyStone::Rainbow(s) => s.fmt(f)
 		}
 	}
 }
@@ -305,7 +621,8 @@ impl Display for AnyStone
  *                                   Board.                                   *
  ******************************************************************************/
 
-/// The default board width.
+/// The board width to assume when neither the legend nor the grid establishes
+/// one, i.e., when the grid is empty.
 const DEFAULT_WIDTH: u32 = 5;
 
 /// An `(x,y`) point for locating a [stone] on a [board].
@@ -315,7 +632,73 @@ const DEFAULT_WIDTH: u32 = 5;
 pub type Point = (u32, u32);
 
 /// The state of the game board during a particular turn.
-#[derive(Debug)]
+///
+/// A clone is a deep copy, i.e., a snapshot that evolves independently of the
+/// original. This makes it easy to try a move and then discard it, without
+/// recourse to the undo closure answered by [`remove`], or to explore several
+/// lines of play at once, e.g., on different threads.
+///
+/// [`remove`]: Board::remove
+/// [coordinate origin]: Board::coordinate_origin
+/// [`reset`]: Board::reset
+///
+/// Two boards are equal if they represent the same state of play and look the
+/// same, i.e., if they agree on every stone, the turn, the wild colors, the
+/// color lock, the survivor and gravity rules, the access direction, the
+/// display properties, and the expected solution. Presentational and
+/// incidental details do not participate in equality: the highlighted stones,
+/// the [coordinate origin], the order of the legend, the properties that the
+/// solver ignores, the assignment of colors to characters that no stone uses,
+/// the undo and redo stacks, and the initial state that [`reset`] restores.
+///
+/// With the `serde` feature enabled, a board serializes as a structure that
+/// mirrors its fields, so a round trip preserves the complete state of play.
+/// Maps serialize as arrays of `[key, value]` pairs, sorted by key, because
+/// JSON permits only strings as keys. For example, this board:
+///
+/// ```text
+/// width = 3
+/// wild = r
+/// ---
+/// * r r
+/// ```
+///
+/// serializes to JSON like this:
+///
+/// ```json
+/// {
+///   "turn": 0,
+///   "wild_colors": 1,
+///   "lock_colors": 0,
+///   "locked_color": 0,
+///   "survivor_rule": "RowOnly",
+///   "gravity": "None",
+///   "access": "FromBottom",
+///   "highlight": [],
+///   "width": 3,
+///   "height": 1,
+///   "removable_stones": 3,
+///   "grid": [
+///     {"Wild": {"restriction": null}},
+///     {"Ordinary": {"rep": "r", "color": 1}},
+///     {"Ordinary": {"rep": "r", "color": 1}}
+///   ],
+///   "initial": [
+///     {"Wild": {"restriction": null}},
+///     {"Ordinary": {"rep": "r", "color": 1}},
+///     {"Ordinary": {"rep": "r", "color": 1}}
+///   ],
+///   "initial_wild_colors": 1,
+///   "properties": [["Width", {"U32": 3}], ["Wild", {"U64": 1}]],
+///   "property_order": ["Width", "Wild"],
+///   "expected_solution": null,
+///   "undo_stack": [],
+///   "redo_stack": [],
+///   "colors": [["r", 1]]
+/// }
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Board
 {
 	/// The current turn. This, combined with initial [phase], impacts the
@@ -330,14 +713,44 @@ pub struct Board
 	///
 	/// [colors]: OrdinaryStone::color
 	/// [wild stones]: WildStone
-	wild_colors: u32,
+	wild_colors: u64,
 
-	/// `true` if the board is color locked, i.e., it is not permitted to play
-	/// two triplets of the same color sequentially, `false` otherwise.
-	color_locked: bool,
+	/// The bitwise OR of the [colors] that are subject to the color lock,
+	/// i.e., that may not be played in two sequential triplets. `u64::MAX` if
+	/// every color is locked, `0` if the board is not color locked.
+	///
+	/// [colors]: OrdinaryStone::color
+	lock_colors: u64,
+
+	/// The [color] of the most recently completed triplet, if the board is
+	/// [color locked]; the next triplet may not be played in this color. `0`
+	/// if no color is locked.
+	///
+	/// [color]: OrdinaryStone::color
+	/// [color locked]: Board::color_locked
+	locked_color: u64,
+
+	/// The rule that determines when [survivors] disappear.
+	///
+	/// [survivors]: SurvivorStone
+	survivor_rule: SurvivorRule,
+
+	/// The rule that determines whether [stones] fall into vacated cells.
+	///
+	/// [stones]: AnyStone
+	gravity: GravityRule,
+
+	/// The side of the board from which [stones] are accessed.
+	///
+	/// [stones]: AnyStone
+	access: AccessDirection,
 
-	/// The point to display highlighted, if any.
-	highlight: Option<Point>,
+	/// The points to display highlighted, if any.
+	highlight: Vec<Point>,
+
+	/// The origin of the displayed coordinates.
+	#[cfg_attr(feature = "serde", serde(skip))]
+	origin: CoordinateOrigin,
 
 	/// The row stride of the physical board, i.e., the number of [stones] in
 	/// any given row.
@@ -359,8 +772,210 @@ pub struct Board
 	/// The physical board, as a single linear vector.
 	grid: Vec<AnyStone>,
 
+	/// The [grid](Board::grid) as it was when the board was constructed, which
+	/// [`reset`](Board::reset) restores.
+	initial: Vec<AnyStone>,
+
+	/// The [wild colors](Board::wild_colors) as they were when the board was
+	/// constructed, which [`reset`](Board::reset) restores.
+	initial_wild_colors: u64,
+
 	/// The property map.
-	properties: PropertyMap
+	#[cfg_attr(feature = "serde", serde(with = "sorted_pairs"))]
+	properties: PropertyMap,
+
+	/// The keys of the properties authored in the legend, in the order that
+	/// they were encountered.
+	property_order: Vec<PropertyKey>,
+
+	/// The expected solution given by the footer, if any.
+	expected_solution: Option<Vec<Point>>,
+
+	/// The moves [played undoably](Board::play_undoable), most recent last.
+	undo_stack: Vec<Removal>,
+
+	/// The points and colors of the moves [undone](Board::undo), most recent
+	/// last.
+	redo_stack: Vec<(Point, u64)>,
+
+	/// The color map, which associates the representative characters of
+	/// [ordinary stones] with their [colors], including the colors of [wild
+	/// stones].
+	///
+	/// [ordinary stones]: OrdinaryStone
+	/// [colors]: OrdinaryStone::color
+	/// [wild stones]: WildStone
+	#[cfg_attr(feature = "serde", serde(with = "sorted_pairs"))]
+	colors: ColorMap,
+
+	/// The number of empty cells at the bottom of each column, which every
+	/// removal maintains, so that the [frontier] need not scan them. Empty
+	/// until first [measured](Board::measure_clearances), e.g., when the
+	/// board has just been deserialized.
+	///
+	/// [frontier]: Board::frontier
+	#[cfg_attr(feature = "serde", serde(skip))]
+	clearances: Vec<u32>,
+
+	/// The [bitboards] derived from the grid, which every change to the grid
+	/// maintains, or `None` if the board has too many cells to represent or
+	/// has just been deserialized.
+	///
+	/// [bitboards]: Bitboards
+	#[cfg_attr(feature = "serde", serde(skip))]
+	bitboards: Option<Bitboards>
+}
+
+/// The rule that determines when [survivors] disappear.
+///
+/// [survivors]: SurvivorStone
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SurvivorRule
+{
+	/// A survivor disappears when its row contains no removable stones. This
+	/// is the usual rule.
+	#[default]
+	RowOnly,
+
+	/// A survivor disappears when either its row or its column contains no
+	/// removable stones.
+	RowOrColumn
+}
+
+impl FromStr for SurvivorRule
+{
+	type Err = ();
+
+	fn from_str (s: &str) -> result::Result<Self, Self::Err>
+	{
+		match s
+		{
+			"row" => Ok(SurvivorRule::RowOnly),
+			"roworcolumn" => Ok(SurvivorRule::RowOrColumn),
+			_ => Err(())
+		}
+	}
+}
+
+impl Display for SurvivorRule
+{
+	fn fmt (&self, f: &mut Formatter) -> Result
+	{
+		match self
+		{
+			SurvivorRule::RowOnly => write!(f, "row"),
+			SurvivorRule::RowOrColumn => write!(f, "roworcolumn")
+		}
+	}
+}
+
+/// The rule that determines whether [stones] fall into the cells vacated by a
+/// [removal](Board::remove).
+///
+/// [stones]: AnyStone
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GravityRule
+{
+	/// Stones stay where they are, leaving vacated cells empty. This is the
+	/// usual rule.
+	#[default]
+	None,
+
+	/// Stones fall down into vacated cells. After each removal, the stones of
+	/// every column that lost a stone settle toward the bottom of the board,
+	/// closing every gap beneath them. [Toggle stones](ToggleStone) are fixed
+	/// in place, so the stones above a toggle come to rest upon it. Survivors
+	/// fall like any other stone, and are swept away according to their rows
+	/// after the fall; the cells that they vacate are closed by the next fall
+	/// in their columns.
+	Down
+}
+
+impl FromStr for GravityRule
+{
+	type Err = ();
+
+	fn from_str (s: &str) -> result::Result<Self, Self::Err>
+	{
+		match s
+		{
+			"none" => Ok(GravityRule::None),
+			"down" => Ok(GravityRule::Down),
+			_ => Err(())
+		}
+	}
+}
+
+impl Display for GravityRule
+{
+	fn fmt (&self, f: &mut Formatter) -> Result
+	{
+		match self
+		{
+			GravityRule::None => write!(f, "none"),
+			GravityRule::Down => write!(f, "down")
+		}
+	}
+}
+
+/// The side of the board from which [stones] are accessed, i.e., the side
+/// toward which the [frontier](Board::frontier) faces. Each lane of the board
+/// — a column when stones are accessed from the bottom or the top, a row when
+/// they are accessed from the left or the right — is scanned from that side,
+/// and its first stone is exposed. A closed [toggle stone](ToggleStone)
+/// obstructs the stones beyond it in its lane.
+///
+/// [stones]: AnyStone
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AccessDirection
+{
+	/// Stones are accessed from the bottom of their columns. This is the
+	/// usual direction.
+	#[default]
+	FromBottom,
+
+	/// Stones are accessed from the top of their columns.
+	FromTop,
+
+	/// Stones are accessed from the left end of their rows.
+	FromLeft,
+
+	/// Stones are accessed from the right end of their rows.
+	FromRight
+}
+
+impl FromStr for AccessDirection
+{
+	type Err = ();
+
+	fn from_str (s: &str) -> result::Result<Self, Self::Err>
+	{
+		match s
+		{
+			"bottom" => Ok(AccessDirection::FromBottom),
+			"top" => Ok(AccessDirection::FromTop),
+			"left" => Ok(AccessDirection::FromLeft),
+			"right" => Ok(AccessDirection::FromRight),
+			_ => Err(())
+		}
+	}
+}
+
+impl Display for AccessDirection
+{
+	fn fmt (&self, f: &mut Formatter) -> Result
+	{
+		match self
+		{
+			AccessDirection::FromBottom => write!(f, "bottom"),
+			AccessDirection::FromTop => write!(f, "top"),
+			AccessDirection::FromLeft => write!(f, "left"),
+			AccessDirection::FromRight => write!(f, "right")
+		}
+	}
 }
 
 impl Board
@@ -373,17 +988,33 @@ impl Board
 	/// The string should begin with a legend, specified as a linefeed-separated
 	/// list of `key = value` options. Following the legend are three hyphens
 	/// (`---`), after which the board must occur. The grid terminates with the
-	/// first blank line.
+	/// first blank line. An optional footer may follow the grid, giving the
+	/// [expected solution] as lines of the form `solution = column,row …`.
+	/// Lines may end with either a linefeed or a carriage return and linefeed,
+	/// and trailing whitespace is ignored.
 	///
 	/// Column spacing defaults to `1`, but may be overridden by the
 	/// `columnspacing` property. Row spacing defaults to `1`, but may be
 	/// overridden by the `rowspacing` property.
+	///
+	/// [expected solution]: Board::expected_solution
 	pub fn parse (tsb: &str) -> BoardResult
 	{
+		// Normalize the line endings, so that the separator can be found, and
+		// trim trailing whitespace, so that it can't masquerade as empty
+		// cells. Line and column numbers are unaffected.
+		let mut normalized = String::with_capacity(tsb.len() + 1);
+		for line in tsb.lines()
+		{
+			normalized.push_str(line.trim_end());
+			normalized.push('\n');
+		}
+		let tsb = normalized.as_str();
 		let mut colors = ColorMap::new();
 		let mut next_color = 1;
 		let mut legend = PropertyMap::new();
-		legend.insert(PropertyKey::Width, PropertyValue::U32(DEFAULT_WIDTH));
+		let mut order = Vec::<PropertyKey>::new();
+		let mut lines = HashMap::<PropertyKey, usize>::new();
 		let index = match tsb.find("\n---\n")
 		{
 			Some(index) =>
@@ -391,36 +1022,367 @@ impl Board
 				Board::parse_legend(
 					&tsb[..=index],
 					&mut legend,
+					&mut order,
+					&mut lines,
 					&mut colors,
 					&mut next_color)?;
 				index + 5
 			},
 			None => 0
 		};
+		let (grid, footer) = Board::split_footer(&tsb[index..]);
+		let first_line = location(tsb, index).0;
+		let footer_line = first_line + grid.matches('\n').count();
 		let grid = Board::parse_grid(
-			&tsb[index..],
+			grid,
+			first_line,
+			lines.get(&PropertyKey::Width).copied().unwrap_or(0),
 			&mut legend,
 			&mut colors,
 			&mut next_color)?;
-		let wild_colors = match legend.get(&PropertyKey::Wild)
-		{
-			Some(PropertyValue::U32(mask)) => *mask,
-			_ => 0
-		};
-		let color_locked = match legend.get(&PropertyKey::ColorLock)
-		{
-			Some(PropertyValue::Bool(b)) => *b,
-			_ => false
-		};
-		let width = match legend.get(&PropertyKey::Width)
+		let mut board = Board::assemble(legend, order, colors, grid)?;
+		board.expected_solution = Board::parse_footer(
+			footer, footer_line, board.width, board.height)?;
+		Ok(board)
+	}
+
+	/// Parse a board from the specified JSON, which must be an object of this
+	/// shape:
+	///
+	/// ```json
+	/// {
+	///   "width": 5,
+	///   "height": 2,
+	///   "wild": "g",
+	///   "colorlock": true,
+	///   "survivorrule": "row",
+	///   "gravity": "none",
+	///   "access": "bottom",
+	///   "legend": {"r": 9, "g": 28},
+	///   "grid": [["r", "g", "_", "_", "_"], ["r", "r", "g", "*", "#"]]
+	/// }
+	/// ```
+	///
+	/// Only `grid` is required. Every property means just what it means in the
+	/// legend of the [text format], and `legend` gives the display properties.
+	/// Each row of `grid` comprises single-character stone codes, just as in
+	/// the text format. The same validations apply to both formats. Malformed
+	/// JSON is reported as [`ParseError::Json`]. Errors that would report a
+	/// line number instead report the 1-based index of the offending row, or
+	/// `0` for an offending property.
+	///
+	/// [text format]: Board::parse
+	#[cfg(feature = "json")]
+	pub fn from_json (json: &str) -> BoardResult
+	{
+		let spec = serde_json::from_str::<JsonBoard>(json)
+			.map_err(|e| ParseError::Json(e.to_string()))?;
+		let mut colors = ColorMap::new();
+		let mut next_color = 1;
+		let mut legend = PropertyMap::new();
+		let mut order = Vec::<PropertyKey>::new();
+		let mut properties = Vec::<(PropertyKey, String)>::new();
+		if let Some(width) = spec.width
+		{
+			properties.push((PropertyKey::Width, width.to_string()));
+		}
+		if let Some(height) = spec.height
+		{
+			properties.push((PropertyKey::Height, height.to_string()));
+		}
+		if let Some(wild) = spec.wild
+		{
+			properties.push((PropertyKey::Wild, wild));
+		}
+		if let Some(colorlock) = spec.colorlock
+		{
+			let term = match colorlock
+			{
+				serde_json::Value::String(term) => term,
+				value => value.to_string()
+			};
+			properties.push((PropertyKey::ColorLock, term));
+		}
+		if let Some(rule) = spec.survivorrule
+		{
+			properties.push((PropertyKey::SurvivorRule, rule));
+		}
+		if let Some(rule) = spec.gravity
+		{
+			properties.push((PropertyKey::Gravity, rule));
+		}
+		if let Some(direction) = spec.access
+		{
+			properties.push((PropertyKey::Access, direction));
+		}
+		for (key, value) in spec.legend
+		{
+			let mut chars = key.chars();
+			let c = match (chars.next(), chars.next())
+			{
+				(Some(c), None) => c,
+				_ => return Err(ParseError::Json(format!(
+					"legend key is not a single character: {:?}", key)))
+			};
+			let term = match value
+			{
+				serde_json::Value::String(term) => term,
+				value => value.to_string()
+			};
+			properties.push((PropertyKey::Display(c), term));
+		}
+		for (key, term) in properties
+		{
+			Board::parse_property(
+				key,
+				&term,
+				0,
+				&mut legend,
+				&mut order,
+				&mut colors,
+				&mut next_color)?;
+		}
+		let mut rows = Vec::<(usize, Vec<String>)>::new();
+		for (row, codes) in (1..).zip(spec.grid)
+		{
+			if let Some(code) = codes.iter()
+				.find(|code| code.chars().count() != 1
+					|| code.trim().is_empty())
+			{
+				return Err(ParseError::Json(format!(
+					"invalid stone code in row {}: {:?}", row, code)))
+			}
+			rows.push((row, codes));
+		}
+		let grid = Board::build_grid(
+			&rows, 0, &mut legend, &mut colors, &mut next_color)?;
+		Board::assemble(legend, order, colors, grid)
+	}
+
+	/// Parse a board from the specified [compact encoding], e.g.,
+	/// `wild=g;w3:rgr/*_`. Any properties of the form `key=value;` precede the
+	/// width, and are interpreted just as in the legend of the [text format].
+	/// The number of stone codes must be a multiple of the width. A digit
+	/// between `2` and `9` that follows the code of a toggle stone or, if the
+	/// `armor` property is `true`, of an ordinary stone is not a stone code,
+	/// but rather the period of a [toggle stone] or the hits remaining to an
+	/// [armored stone]. The colors of a
+	/// restricted [wild stone] are enclosed in parentheses after its `*`,
+	/// e.g., `*(rb)`. The same validations apply as for the text format;
+	/// errors that would report a line number report `1`.
+	///
+	/// [compact encoding]: Board::to_compact
+	/// [armored stone]: ArmoredStone
+	/// [toggle stone]: ToggleStone
+	/// [wild stone]: WildStone
+	/// [text format]: Board::parse
+	pub fn from_compact (compact: &str) -> BoardResult
+	{
+		let mut colors = ColorMap::new();
+		let mut next_color = 1;
+		let mut legend = PropertyMap::new();
+		let mut order = Vec::<PropertyKey>::new();
+		let mut rest = compact.trim();
+		let (width, codes) = loop
+		{
+			// The width prefix cannot be mistaken for a property, because no
+			// property key begins with `w` and a digit.
+			if let Some((width, codes)) = rest.strip_prefix('w')
+				.and_then(|rest| rest.split_once(':'))
+				.filter(|(width, _)| width.parse::<u32>().is_ok())
+			{
+				break (width, codes)
+			}
+			let (property, remainder) = rest.split_once(';')
+				.ok_or_else(|| ParseError::Compact(
+					"missing width prefix".to_string()))?;
+			let (key, term) = property.split_once('=')
+				.ok_or_else(|| ParseError::Compact(
+					format!("invalid property: {:?}", property)))?;
+			Board::parse_property(
+				Board::property_key(key),
+				term,
+				1,
+				&mut legend,
+				&mut order,
+				&mut colors,
+				&mut next_color)?;
+			rest = remainder;
+		};
+		Board::parse_property(
+			PropertyKey::Width,
+			width,
+			1,
+			&mut legend,
+			&mut order,
+			&mut colors,
+			&mut next_color)?;
+		let width = width.parse::<usize>().unwrap();
+		if width == 0
+		{
+			return Err(ParseError::Compact("zero width".to_string()))
+		}
+		if let Some(c) = codes.chars().find(|c| c.is_whitespace())
+		{
+			return Err(ParseError::Compact(
+				format!("invalid stone code: {:?}", c)))
+		}
+		let armor = matches!(
+			legend.get(&PropertyKey::Armor),
+			Some(PropertyValue::Bool(true)));
+		let codes = stone_codes(codes, true, armor);
+		if codes.len() % width != 0
+		{
+			return Err(ParseError::IncompleteBoard {
+				row: (codes.len() / width + 1) as u32,
+				stones: (codes.len() % width) as u32
+			})
+		}
+		let rows = codes.chunks(width)
+			.map(|codes| (1, codes.to_vec()))
+			.collect::<Vec<_>>();
+		let grid = Board::build_grid(
+			&rows, 0, &mut legend, &mut colors, &mut next_color)?;
+		Board::assemble(legend, order, colors, grid)
+	}
+
+	/// Construct a board from the specified rows of its grid, each written
+	/// just as in the [text format], e.g., `"r g *rb"`, with the specified
+	/// wild colors, e.g., `"rb"`, and color lock. This is a concise way to
+	/// write test fixtures: the answer equals the board [parsed] from a legend
+	/// that gives just the `wild` property, unless `wild` is empty, and the
+	/// `colorlock` property, unless `color_lock` is `false`. Blank rows are
+	/// ignored, and the longest row establishes the width of the board. The
+	/// same validations apply as for the text format. Errors that would
+	/// report a line number instead report the 1-based index of the offending
+	/// row, or `0` for an invalid `wild`.
+	///
+	/// [text format]: Board::parse
+	/// [parsed]: Board::parse
+	pub fn from_grid_chars (
+		rows: &[&str],
+		wild: &str,
+		color_lock: bool) -> BoardResult
+	{
+		let mut colors = ColorMap::new();
+		let mut next_color = 1;
+		let mut legend = PropertyMap::new();
+		let mut order = Vec::<PropertyKey>::new();
+		let mut properties = Vec::<(PropertyKey, &str)>::new();
+		if !wild.is_empty()
+		{
+			properties.push((PropertyKey::Wild, wild));
+		}
+		if color_lock
+		{
+			properties.push((PropertyKey::ColorLock, "true"));
+		}
+		for (key, term) in properties
+		{
+			Board::parse_property(
+				key,
+				term,
+				0,
+				&mut legend,
+				&mut order,
+				&mut colors,
+				&mut next_color)?;
+		}
+		let rows = (1..).zip(rows)
+			.map(|(row, text)| (row, stone_codes(text, false, false)))
+			.filter(|(_, codes)| !codes.is_empty())
+			.collect::<Vec<_>>();
+		let grid = Board::build_grid(
+			&rows, 0, &mut legend, &mut colors, &mut next_color)?;
+		Board::assemble(legend, order, colors, grid)
+	}
+
+	/// Assemble a board from its parsed constituents, validating that they are
+	/// mutually consistent. The legend must record the width of the board.
+	/// The board has no [expected solution].
+	///
+	/// [expected solution]: Board::expected_solution
+	fn assemble (
+		mut legend: PropertyMap,
+		mut order: Vec<PropertyKey>,
+		colors: ColorMap,
+		grid: Vec<AnyStone>) -> BoardResult
+	{
+		// Armored stones can only be written if the legend enables them, e.g.,
+		// for a board built from its stones.
+		if grid.iter().any(|s| matches!(s, AnyStone::Armored(_)))
+			&& !order.contains(&PropertyKey::Armor)
+		{
+			legend.insert(PropertyKey::Armor, PropertyValue::Bool(true));
+			order.push(PropertyKey::Armor);
+		}
+		let wild_colors = match legend.get(&PropertyKey::Wild)
+		{
+			Some(PropertyValue::U64(mask)) => *mask,
+			_ => 0
+		};
+		let lock_colors = match legend.get(&PropertyKey::ColorLock)
+		{
+			Some(PropertyValue::Bool(true)) => u64::MAX,
+			Some(PropertyValue::String(reps)) =>
+			{
+				let mut mask = 0;
+				for rep in reps.chars()
+				{
+					mask |= colors.get(&rep)
+						.ok_or(ParseError::UnknownLockColor { rep })?;
+				}
+				mask
+			},
+			_ => 0
+		};
+		let survivor_rule = match legend.get(&PropertyKey::SurvivorRule)
+		{
+			Some(PropertyValue::String(rule)) =>
+				rule.parse::<SurvivorRule>().unwrap_or_default(),
+			_ => SurvivorRule::default()
+		};
+		let gravity = match legend.get(&PropertyKey::Gravity)
+		{
+			Some(PropertyValue::String(rule)) =>
+				rule.parse::<GravityRule>().unwrap_or_default(),
+			_ => GravityRule::default()
+		};
+		let access = match legend.get(&PropertyKey::Access)
+		{
+			Some(PropertyValue::String(direction)) =>
+				direction.parse::<AccessDirection>().unwrap_or_default(),
+			_ => AccessDirection::default()
+		};
+		let width = match legend.get(&PropertyKey::Width)
 		{
 			Some(PropertyValue::U32(width)) => *width,
 			_ => unreachable!()
 		};
+		// An empty grid has no meaningful dimensions, and guarding here also
+		// keeps the arithmetic below from dividing by zero.
+		if grid.iter().all(|stone| matches!(stone, AnyStone::None(_)))
+		{
+			return Err(ParseError::EmptyBoard)
+		}
 		let height = (grid.len() as u32 + (width - 1)) / width;
 		if width * height != grid.len() as u32
 		{
-			return Err(ParseError::IncompleteBoard)
+			return Err(ParseError::IncompleteBoard {
+				row: height,
+				stones: grid.len() as u32 - (height - 1) * width
+			})
+		}
+		if let Some(PropertyValue::U32(declared)) =
+			legend.get(&PropertyKey::Height)
+		{
+			if *declared != height
+			{
+				return Err(ParseError::DimensionMismatch {
+					declared: *declared,
+					actual: height
+				})
+			}
 		}
 		let removable_stones =
 			grid.iter().filter(|s| s.is_removable()).count() as u32;
@@ -436,159 +1398,584 @@ impl Board
 		{
 			return Err(ParseError::WrongWildCount)
 		}
-		Ok(Board
+		// Every move belongs to a triplet, so absent bombs, which clear stones
+		// without playing them, the moves must come out even. An armored stone
+		// requires one move per hit.
+		let bombs = grid.iter().any(|s| matches!(s, AnyStone::Bomb(_)));
+		let moves = grid.iter()
+			.map(|s| match s
+			{
+				AnyStone::Armored(a) => a.hits_remaining as u32,
+				s => s.is_removable() as u32
+			})
+			.sum::<u32>();
+		if !bombs && moves % 3 != 0
+		{
+			return Err(ParseError::NotDivisibleByThree { moves })
+		}
+		let board = Board
 		{
 			turn: 0,
 			wild_colors,
-			color_locked,
-			highlight: None,
+			lock_colors,
+			locked_color: 0,
+			survivor_rule,
+			gravity,
+			access,
+			highlight: vec![],
+			origin: CoordinateOrigin::default(),
 			width,
 			height,
 			removable_stones,
+			initial: grid.clone(),
 			grid,
-			properties: legend
-		})
+			initial_wild_colors: wild_colors,
+			properties: legend,
+			property_order: order,
+			expected_solution: None,
+			undo_stack: vec![],
+			redo_stack: vec![],
+			colors,
+			clearances: vec![],
+			bitboards: None
+		};
+		// A checksum guards the grid against tampering or corruption in
+		// transit, so it covers the grid as written, before any survivors are
+		// released.
+		if let Some(PropertyValue::U64(expected)) =
+			board.properties.get(&PropertyKey::Checksum)
+		{
+			let actual = board.content_hash();
+			if actual != *expected
+			{
+				return Err(ParseError::ChecksumMismatch {
+					expected: *expected,
+					actual
+				})
+			}
+		}
+		// Survivors alone are released at once, which would leave nothing to
+		// write.
+		let board = board.with_released_survivors();
+		if board.grid.iter().all(|stone| matches!(stone, AnyStone::None(_)))
+		{
+			return Err(ParseError::EmptyBoard)
+		}
+		Ok(board.with_clearances())
+	}
+
+	/// Split the specified text, which begins with a grid, into the grid and
+	/// the footer that follows it. The grid terminates with the first blank
+	/// line that follows a row of stones; everything thereafter belongs to the
+	/// footer.
+	fn split_footer (text: &str) -> (&str, &str)
+	{
+		let mut seen_row = false;
+		let mut offset = 0;
+		for line in text.split_inclusive('\n')
+		{
+			if !line.trim().is_empty()
+			{
+				seen_row = true;
+			}
+			else if seen_row
+			{
+				return (&text[..offset], &text[offset..])
+			}
+			offset += line.len();
+		}
+		(text, "")
+	}
+
+	/// Parse a board footer from the specified string. A footer is specified as
+	/// a linefeed-separated list of `solution = moves` lines, where _moves_ is
+	/// a whitespace-separated list of `column,row` points. The moves of every
+	/// line are concatenated to form the expected solution, so a long solution
+	/// may be spread across several lines, e.g., one triplet per line. Every
+	/// point must lie within a board of the specified dimensions. Answer `None`
+	/// if the footer is empty. `first_line` is the 1-based line number of the
+	/// footer within the whole board file, for error reporting.
+	fn parse_footer (
+		footer: &str,
+		first_line: usize,
+		width: u32,
+		height: u32) -> FooterResult
+	{
+		let mut solution = None::<Vec<Point>>;
+		for (line, text) in footer.lines()
+			.enumerate()
+			.map(|(i, text)| (first_line + i, text))
+			.filter(|(_, text)| !text.trim().is_empty())
+		{
+			let moves = match text.split_once('=')
+			{
+				Some((key, moves)) if key.trim() == "solution" => moves,
+				_ => return Err(ParseError::InvalidPropertySyntax {
+					line,
+					column: 1
+				})
+			};
+			let solution = solution.get_or_insert_with(Vec::new);
+			for m in moves.split_whitespace()
+			{
+				let p = m.split_once(',')
+					.and_then(|(column, row)| Some((
+						column.parse::<u32>().ok()?,
+						row.parse::<u32>().ok()?)))
+					.filter(|p| p.0 < width && p.1 < height)
+					.ok_or(ParseError::InvalidPropertyValue { line })?;
+				solution.push(p);
+			}
+		}
+		Ok(solution)
 	}
 
 	/// Parse a board legend from the specified string. A legend is specified as
-	/// a linefeed-separated list of `key = value` options. A board legend is
+	/// a linefeed-separated list of `key = value` options. A line that begins
+	/// with `#` or `//` is a comment, and is ignored. If the `strict` property
+	/// is `true`, then every key must be known. A board legend is
 	/// terminated by a line containing only three hyphens (`---`). Populate the
-	/// supplied map. The color map and next color mask are provided to support
-	/// [wild stones].
+	/// supplied map, record the encounter order of its keys, and record the
+	/// 1-based line number of each key. The color map and next color mask are
+	/// provided to support [wild stones].
 	///
 	/// [wild stones]: WildStone
 	fn parse_legend (
 		legend: &str,
 		map: &mut PropertyMap,
+		order: &mut Vec<PropertyKey>,
+		lines: &mut HashMap<PropertyKey, usize>,
 		colors: &mut ColorMap,
-		next_color: &mut u32) -> LegendResult
+		next_color: &mut u64) -> LegendResult
 	{
 		// The local aliases make the match discriminants easier to understand.
 		use self::LegendParseState::*;
 		let mut key = None::<PropertyKey>;
+		let mut unknown = None::<(usize, String)>;
 		let mut state = ExpectKeyOrLinefeedOrEnd;
 		let tokens = FilteredTokenizer::new(
 			LegendFilter, legend).collect::<Vec<Token>>();
+		let mut line = 1;
 		for token in tokens
 		{
+			let (token_line, column) = location(legend, token.start_offset);
+			line = token_line;
+			let syntax_error =
+				ParseError::InvalidPropertySyntax { line, column };
 			match (state, token.term.as_ref())
 			{
-				(ExpectKeyOrLinefeedOrEnd, "=") =>
-					return Err(ParseError::InvalidPropertySyntax),
+				(ExpectKeyOrLinefeedOrEnd, "=") => return Err(syntax_error),
 				(ExpectKeyOrLinefeedOrEnd, "\n") =>
 					state = ExpectKeyOrLinefeedOrEnd,
+				(ExpectKeyOrLinefeedOrEnd, term)
+					if term.starts_with('#') || term.starts_with("//") =>
+						state = ExpectCommentOrLinefeed,
 				(ExpectKeyOrLinefeedOrEnd, term) =>
 				{
-					key = Some(match term
+					let property = Board::property_key(term);
+					if let PropertyKey::Unknown(name) = &property
 					{
-						"width" => PropertyKey::Width,
-						"wild" => PropertyKey::Wild,
-						"colorlock" => PropertyKey::ColorLock,
-						unknown =>
-						{
-							if unknown.len() == 1
-							{
-								PropertyKey::Display(
-									unknown.chars().next().unwrap())
-							}
-							else
-							{
-								PropertyKey::Unknown(unknown.to_string())
-							}
-						}
-					});
+						unknown.get_or_insert_with(|| (line, name.clone()));
+					}
+					key = Some(property);
 					state = ExpectEquals;
 				},
 				(ExpectEquals, "=") => state = ExpectValue,
-				(ExpectEquals, _) => return Err(
-					ParseError::InvalidPropertySyntax),
+				(ExpectEquals, _) => return Err(syntax_error),
 				(ExpectValue, term) =>
 				{
-					let unwrapped = key.unwrap();
-					match unwrapped
-					{
-						PropertyKey::Width => map.insert(
-							unwrapped,
-							PropertyValue::U32(term.parse::<u32>()?)),
-						PropertyKey::Wild =>
-						{
-							for c in term.chars()
-							{
-								if colors.get(&c) != None
-								{
-									return Err(ParseError::RepeatedWildColor);
-								}
-								colors.insert(c, *next_color);
-								*next_color = *next_color << 1;
-							}
-							map.insert(
-								unwrapped,
-								PropertyValue::U32(*next_color - 1))
-						},
-						PropertyKey::ColorLock => map.insert(
-							unwrapped,
-							PropertyValue::Bool(term.parse::<bool>()?)),
-						PropertyKey::Display(c) =>
-						{
-							let s = format!(
-								"\u{1b}[38;5;{}m{}",
-								term,
-								c);
-							map.insert(
-								unwrapped,
-								PropertyValue::String(s))
-						},
-						PropertyKey::Unknown(_) => map.insert(
-							unwrapped,
-							PropertyValue::String(term.to_string()))
-					};
-					key = None;
+					let key = key.take().unwrap();
+					lines.insert(key.clone(), line);
+					Board::parse_property(
+						key,
+						term,
+						line,
+						map,
+						order,
+						colors,
+						next_color)?;
 					state = ExpectLinefeed;
 				},
 				(ExpectLinefeed, "\n") => state = ExpectKeyOrLinefeedOrEnd,
-				(ExpectLinefeed, _) => return Err(
-					ParseError::InvalidPropertySyntax)
+				(ExpectLinefeed, _) => return Err(syntax_error),
+				(ExpectCommentOrLinefeed, "\n") =>
+					state = ExpectKeyOrLinefeedOrEnd,
+				(ExpectCommentOrLinefeed, _) => state = ExpectCommentOrLinefeed
+			}
+		}
+		if matches!(state, ExpectKeyOrLinefeedOrEnd | ExpectCommentOrLinefeed)
+		{
+			match unknown
+			{
+				Some((line, key)) if matches!(
+					map.get(&PropertyKey::Strict),
+					Some(PropertyValue::Bool(true))) =>
+						Err(ParseError::UnknownProperty { line, key }),
+				_ => Ok(())
+			}
+		}
+		else
+		{
+			// The legend ended prematurely, so blame the end of the last line.
+			let column = legend.lines().nth(line - 1)
+				.map_or(1, |text| text.chars().count() + 1);
+			Err(ParseError::InvalidPropertySyntax { line, column })
+		}
+	}
+
+	/// Answer the property key denoted by the specified legend term.
+	fn property_key (term: &str) -> PropertyKey
+	{
+		match term
+		{
+			"width" => PropertyKey::Width,
+			"height" => PropertyKey::Height,
+			"wild" => PropertyKey::Wild,
+			"colorlock" => PropertyKey::ColorLock,
+			"strictwidth" => PropertyKey::StrictWidth,
+			"armor" => PropertyKey::Armor,
+			"strict" => PropertyKey::Strict,
+			"survivorrule" => PropertyKey::SurvivorRule,
+			"gravity" => PropertyKey::Gravity,
+			"access" => PropertyKey::Access,
+			"version" => PropertyKey::Version,
+			"checksum" => PropertyKey::Checksum,
+			unknown =>
+			{
+				if unknown.chars().count() == 1
+				{
+					PropertyKey::Display(unknown.chars().next().unwrap())
+				}
+				else
+				{
+					warn!("unknown property: {}", unknown);
+					PropertyKey::Unknown(unknown.to_string())
+				}
 			}
 		}
-		if state == ExpectKeyOrLinefeedOrEnd { Ok(()) }
-		else { Err(ParseError::InvalidPropertySyntax) }
 	}
 
-	/// Parse a grid from the specified string.
+	/// Parse the value of the specified property from the specified term,
+	/// which occurs on the specified 1-based line. Populate the supplied map,
+	/// and record the encounter order of the key. The color map and next color
+	/// mask are provided to support [wild stones].
+	///
+	/// [wild stones]: WildStone
+	fn parse_property (
+		key: PropertyKey,
+		term: &str,
+		line: usize,
+		map: &mut PropertyMap,
+		order: &mut Vec<PropertyKey>,
+		colors: &mut ColorMap,
+		next_color: &mut u64) -> LegendResult
+	{
+		let value_error = ParseError::InvalidPropertyValue { line };
+		if !order.contains(&key)
+		{
+			order.push(key.clone());
+		}
+		let value = match key
+		{
+			PropertyKey::Width => PropertyValue::U32(
+				term.parse::<u32>()
+					.ok()
+					.filter(|&width| width > 0)
+					.ok_or(value_error)?),
+			PropertyKey::Height => PropertyValue::U32(
+				term.parse::<u32>().map_err(|_| value_error)?),
+			PropertyKey::Wild =>
+			{
+				let mut mask = 0;
+				for c in term.chars()
+				{
+					if colors.get(&c) != None
+					{
+						return Err(ParseError::RepeatedWildColor { line });
+					}
+					let color = allocate_color(next_color)
+						.ok_or(ParseError::TooManyColors { line })?;
+					colors.insert(c, color);
+					mask |= color;
+				}
+				PropertyValue::U64(mask)
+			},
+			PropertyKey::ColorLock => match term.parse::<bool>()
+			{
+				Ok(b) => PropertyValue::Bool(b),
+				// The grid has not been parsed yet, so keep the characters
+				// until the colors are known.
+				Err(_) => PropertyValue::String(term.to_string())
+			},
+			PropertyKey::StrictWidth
+				| PropertyKey::Armor
+				| PropertyKey::Strict =>
+				PropertyValue::Bool(
+					term.parse::<bool>().map_err(|_| value_error)?),
+			PropertyKey::SurvivorRule =>
+			{
+				term.parse::<SurvivorRule>().map_err(|_| value_error)?;
+				PropertyValue::String(term.to_string())
+			},
+			PropertyKey::Gravity =>
+			{
+				term.parse::<GravityRule>().map_err(|_| value_error)?;
+				PropertyValue::String(term.to_string())
+			},
+			PropertyKey::Access =>
+			{
+				term.parse::<AccessDirection>().map_err(|_| value_error)?;
+				PropertyValue::String(term.to_string())
+			},
+			PropertyKey::Version => PropertyValue::U32(
+				term.parse::<u32>()
+					.ok()
+					.filter(|&version| version == FORMAT_VERSION)
+					.ok_or(value_error)?),
+			PropertyKey::Checksum => PropertyValue::U64(
+				u64::from_str_radix(term, 16).map_err(|_| value_error)?),
+			PropertyKey::Display(_) => PropertyValue::String(
+				palette_index(term).ok_or(value_error)?.to_string()),
+			PropertyKey::Unknown(_) => PropertyValue::String(term.to_string())
+		};
+		map.insert(key, value);
+		Ok(())
+	}
+
+	/// Parse a grid from the specified string. Each nonblank line is a row.
+	/// If the legend specifies a `width`, then no row may contain more stones;
+	/// otherwise, the longest row establishes the width of the board. Shorter
+	/// rows are padded with [empty cells]. If the legend enables
+	/// `strictwidth`, then the first row establishes the width of the board,
+	/// overriding any `width` property, and every subsequent row must contain
+	/// exactly as many stones. Record the width in the legend. `first_line` is
+	/// the 1-based line number of the grid within the whole board file, and
+	/// `width_line` is the 1-based line number of the `width` property, for
+	/// error reporting.
+	///
+	/// [empty cells]: NoStone
 	fn parse_grid (
 		grid: &str,
-		_legend: &mut PropertyMap,
+		first_line: usize,
+		width_line: usize,
+		legend: &mut PropertyMap,
 		colors: &mut ColorMap,
-		next_color: &mut u32) -> GridResult
+		next_color: &mut u64) -> GridResult
 	{
-		let mut vec = Vec::<AnyStone>::new();
-		let tokens = FilteredTokenizer::new(
-			StoneFilter, grid).collect::<Vec<Token>>();
-		for token in tokens
+		let armor = matches!(
+			legend.get(&PropertyKey::Armor),
+			Some(PropertyValue::Bool(true)));
+		let rows = grid.lines()
+			.enumerate()
+			.map(|(i, text)|
+			{
+				(first_line + i, stone_codes(text, false, armor))
+			})
+			.filter(|(_, tokens)| !tokens.is_empty())
+			.collect::<Vec<_>>();
+		Board::build_grid(&rows, width_line, legend, colors, next_color)
+	}
+
+	/// Build a grid from the specified rows, each of which pairs its 1-based
+	/// line number with its stone tokens, as described by [`parse_grid`]. The
+	/// declared width, if any, may not exceed the total number of stones,
+	/// which guards against absurd allocations; `width_line` is the 1-based
+	/// line number of the `width` property, for error reporting.
+	///
+	/// [`parse_grid`]: Board::parse_grid
+	fn build_grid (
+		rows: &[(usize, Vec<String>)],
+		width_line: usize,
+		legend: &mut PropertyMap,
+		colors: &mut ColorMap,
+		next_color: &mut u64) -> GridResult
+	{
+		let strict = matches!(
+			legend.get(&PropertyKey::StrictWidth),
+			Some(PropertyValue::Bool(true)));
+		let declared = match legend.get(&PropertyKey::Width)
+		{
+			Some(PropertyValue::U32(width)) if !strict => Some(*width),
+			_ => None
+		};
+		let stones = rows.iter().map(|(_, tokens)| tokens.len()).sum::<usize>();
+		if stones > 0 && declared.is_some_and(|width| width as usize > stones)
+		{
+			return Err(ParseError::InvalidPropertyValue { line: width_line })
+		}
+		let wild_colors = match legend.get(&PropertyKey::Wild)
+		{
+			Some(PropertyValue::U64(mask)) => *mask,
+			_ => 0
+		};
+		let mut stone_rows = Vec::<Vec<AnyStone>>::new();
+		let mut width = None::<u32>;
+		for (row, (line, tokens)) in (1..).zip(rows)
 		{
-			vec.push(match token.term.as_ref()
+			let line = *line;
+			let actual = tokens.len() as u32;
+			if strict
 			{
-				"_" => AnyStone::None(NoStone),
-				"#" => AnyStone::Survivor(SurvivorStone),
-				"*" => AnyStone::Wild(WildStone),
-				"/" => AnyStone::Toggle(ToggleStone {phase: 0}),
-				"+" => AnyStone::Toggle(ToggleStone {phase: 1}),
-				s @ _ =>
+				match width
 				{
-					let c = s.chars().next().unwrap();
-					let color = *colors.entry(c).or_insert_with(|| {
-						let color = *next_color;
-						*next_color = *next_color << 1;
-						color
-					});
-					AnyStone::Ordinary(OrdinaryStone {rep: c, color})
+					None => width = Some(actual),
+					Some(expected) if expected != actual =>
+						return Err(ParseError::RowWidthMismatch {
+							line,
+							row,
+							expected,
+							actual
+						}),
+					_ => {}
 				}
-			});
+			}
+			else if let Some(width) = declared.filter(|&width| actual > width)
+			{
+				return Err(ParseError::RowExceedsWidth {
+					line,
+					row,
+					width,
+					actual
+				})
+			}
+			let mut stones = Vec::<AnyStone>::with_capacity(tokens.len());
+			for token in tokens
+			{
+				stones.push(Board::parse_stone(
+					token, line, wild_colors, colors, next_color)?);
+			}
+			stone_rows.push(stones);
+		}
+		let width = width.or(declared).unwrap_or_else(||
+			stone_rows.iter()
+				.map(|stones| stones.len() as u32)
+				.max()
+				.unwrap_or(DEFAULT_WIDTH));
+		legend.insert(PropertyKey::Width, PropertyValue::U32(width));
+		let mut vec =
+			Vec::<AnyStone>::with_capacity(stone_rows.len() * width as usize);
+		for mut stones in stone_rows
+		{
+			stones.resize(width as usize, AnyStone::None(NoStone));
+			vec.append(&mut stones);
 		}
 		Ok(vec)
 	}
 
-	/// Answer the current turn.
+	/// Parse a single stone from the specified token, which occurs on the
+	/// specified 1-based line. The color map and next color mask are provided
+	/// to support [ordinary stones]. A digit between `2` and `9` that follows
+	/// the first character of the token gives the hits of an [armored stone]
+	/// or the period of a [toggle stone]; any other suffix is invalid. The
+	/// characters that follow the `*` of a [wild stone] restrict it to their
+	/// colors, each of which must be one of the specified wild colors; `*-`
+	/// denotes a wild stone that can provide no color at all.
+	///
+	/// [ordinary stones]: OrdinaryStone
+	/// [armored stone]: ArmoredStone
+	/// [toggle stone]: ToggleStone
+	/// [wild stone]: WildStone
+	fn parse_stone (
+		token: &str,
+		line: usize,
+		wild_colors: u64,
+		colors: &mut ColorMap,
+		next_color: &mut u64) -> StoneResult
+	{
+		Ok(match token
+		{
+			"_" => AnyStone::None(NoStone),
+			"#" => AnyStone::Survivor(SurvivorStone),
+			"*" => AnyStone::Wild(WildStone::new()),
+			"/" => AnyStone::Toggle(ToggleStone::open()),
+			"+" => AnyStone::Toggle(ToggleStone::closed()),
+			s if s.starts_with(['/', '+']) =>
+			{
+				let toggle = match s.as_bytes()[0]
+				{
+					b'/' => ToggleStone::open(),
+					_ => ToggleStone::closed()
+				};
+				let period = suffix_digit(&s[1..])
+					.ok_or_else(|| ParseError::InvalidStoneCode {
+						line,
+						code: s.to_string()
+					})?;
+				AnyStone::Toggle(toggle.with_period(period))
+			},
+			"o" => AnyStone::Bomb(BombStone),
+			"@" => AnyStone::Rainbow(RainbowStone),
+			"*-" => AnyStone::Wild(WildStone { restriction: Some(0) }),
+			s if s.starts_with('*') =>
+			{
+				let mut mask = 0;
+				for rep in s.chars().skip(1)
+				{
+					mask |= colors.get(&rep)
+						.copied()
+						.filter(|color| color & wild_colors != 0)
+						.ok_or(ParseError::UnknownWildColor { line, rep })?;
+				}
+				AnyStone::Wild(WildStone::restricted(mask))
+			},
+			s @ _ =>
+			{
+				let mut chars = s.chars();
+				let c = chars.next().unwrap();
+				let color = match colors.get(&c)
+				{
+					Some(color) => *color,
+					None =>
+					{
+						let color = allocate_color(next_color)
+							.ok_or(ParseError::TooManyColors { line })?;
+						colors.insert(c, color);
+						color
+					}
+				};
+				let stone = OrdinaryStone {rep: c, color};
+				match chars.as_str()
+				{
+					"" => AnyStone::Ordinary(stone),
+					hits => AnyStone::Armored(ArmoredStone {
+						stone,
+						hits_remaining: suffix_digit(hits)
+							.ok_or_else(|| ParseError::InvalidStoneCode {
+								line,
+								code: s.to_string()
+							})? as u8
+					})
+				}
+			}
+		})
+	}
+
+	/// Answer the index into the [grid] of the specified point.
+	///
+	/// [grid]: Board::grid
+	fn index (&self, p: Point) -> usize
+	{
+		debug_assert!(p.0 < self.width && p.1 < self.height);
+		let index = (p.1 * self.width + p.0) as usize;
+		debug_assert_eq!(self.point(index), p);
+		index
+	}
+
+	/// Answer the point that corresponds to the specified index into the
+	/// [grid]. This is the inverse of [`index`].
+	///
+	/// [grid]: Board::grid
+	/// [`index`]: Board::index
+	fn point (&self, index: usize) -> Point
+	{
+		debug_assert!(index < self.grid.len());
+		(index as u32 % self.width, index as u32 / self.width)
+	}
+
+	/// Answer the current turn, i.e., the number of moves played. The turn
+	/// saturates at `u32::MAX` rather than wrapping around to `0`, e.g., for
+	/// a board deserialized in an absurd state; thereafter, the triplets and
+	/// the toggle stones no longer advance.
 	pub fn turn (&self) -> u32
 	{
 		self.turn
@@ -611,384 +1998,4312 @@ impl Board
 	///
 	/// [colors]: OrdinaryStone::color
 	/// [wild stones]: WildStone
-	pub fn wild_colors (&self) -> u32
+	pub fn wild_colors (&self) -> u64
 	{
 		self.wild_colors
 	}
 
-	/// Answer `true` if the receiver is color locked, or `false` otherwise.
+	/// Answer `true` if the receiver is color locked, i.e., if any of its
+	/// colors are [subject to the lock], or `false` otherwise.
+	///
+	/// [subject to the lock]: Board::lock_colors
 	pub fn color_locked (&self) -> bool
 	{
-		self.color_locked
+		self.lock_colors != 0
 	}
 
-	/// Answer the count of removable [stones].
+	/// Answer the bitwise OR of the [colors] that are subject to the color
+	/// lock, i.e., that may not be played in two sequential triplets. This is
+	/// `u64::MAX` if every color is locked, e.g., `colorlock = true`, or `0`
+	/// if the receiver is not [color locked].
 	///
-	/// [stones]: AnyStone
-	pub fn removable_stones (&self) -> u32
+	/// [colors]: OrdinaryStone::color
+	/// [color locked]: Board::color_locked
+	pub fn lock_colors (&self) -> u64
 	{
-		self.removable_stones
+		self.lock_colors
 	}
 
-	/// Remove the [stone] at the specified location, capturing it, and
-	/// asserting that it has the specified color. The color information is
-	/// needed for proper treatment of [wild stones]. Answer a closure that can
-	/// reverse the effect of this removal.
+	/// Answer the character that represents the specified [color], e.g., to
+	/// name it for a player, or `None` if no character represents it.
 	///
-	/// [stone]: AnyStone
-	/// [wild stones]: WildStone
-	#[must_use]
-	pub fn remove (
+	/// [color]: OrdinaryStone::color
+	pub fn color_rep (&self, color: u64) -> Option<char>
+	{
+		self.colors.iter()
+			.find(|(_, &other)| other == color)
+			.map(|(&rep, _)| rep)
+	}
+
+	/// Answer the rule that determines when [survivors] disappear.
+	///
+	/// [survivors]: SurvivorStone
+	pub fn survivor_rule (&self) -> SurvivorRule
+	{
+		self.survivor_rule
+	}
+
+	/// Answer the rule that determines whether [stones] fall into vacated
+	/// cells.
+	///
+	/// [stones]: AnyStone
+	pub fn gravity (&self) -> GravityRule
+	{
+		self.gravity
+	}
+
+	/// Answer the side of the board from which [stones] are accessed.
+	///
+	/// [stones]: AnyStone
+	pub fn access (&self) -> AccessDirection
+	{
+		self.access
+	}
+
+	/// Answer the properties authored in the legend, in the order that they
+	/// were encountered. Defaulted properties are not included.
+	pub fn properties (
+		&self) -> impl Iterator<Item = (&PropertyKey, &PropertyValue)>
+	{
+		self.property_order.iter()
+			.filter_map(|key| self.properties.get_key_value(key))
+	}
+
+	/// Answer the origin of the displayed coordinates.
+	pub fn coordinate_origin (&self) -> CoordinateOrigin
+	{
+		self.origin
+	}
+
+	/// Set the origin of the displayed coordinates.
+	pub fn set_coordinate_origin (&mut self, origin: CoordinateOrigin)
+	{
+		self.origin = origin;
+	}
+
+	/// Answer the displayed coordinates of the specified point, according to
+	/// the [coordinate origin](Board::coordinate_origin).
+	pub fn display_point (&self, p: Point) -> Point
+	{
+		self.origin.convert(p, self.height)
+	}
+
+	/// Answer the expected solution given by the footer of the board's source,
+	/// if any. The expected solution is not validated during parsing; use
+	/// [`is_valid_solution`] to check it.
+	///
+	/// [`is_valid_solution`]: Board::is_valid_solution
+	pub fn expected_solution (&self) -> Option<Vec<Point>>
+	{
+		self.expected_solution.clone()
+	}
+
+	/// Answer the [color] of the most recently completed triplet, if the
+	/// receiver is [color locked]; the next triplet may not be played in this
+	/// color. Answer `0` if no color is locked.
+	///
+	/// [color]: OrdinaryStone::color
+	/// [color locked]: Board::color_locked
+	pub fn locked_color (&self) -> u64
+	{
+		self.locked_color
+	}
+
+	/// Answer the count of removable [stones].
+	///
+	/// [stones]: AnyStone
+	pub fn removable_stones (&self) -> u32
+	{
+		self.removable_stones
+	}
+
+	/// Answer the row of the lowest [stone] in the specified column, or
+	/// `None` if the column is empty.
+	///
+	/// [stone]: AnyStone
+	pub fn lowest_stone_row (&self, column: u32) -> Option<u32>
+	{
+		let clearance = match self.clearances.get(column as usize)
+		{
+			Some(&clearance) =>
+			{
+				debug_assert_eq!(clearance, self.clearance(column));
+				clearance
+			},
+			None => self.clearance(column)
+		};
+		(clearance < self.height).then(|| self.height - 1 - clearance)
+	}
+
+	/// Answer the [bitboards] derived from the grid, if any.
+	///
+	/// [bitboards]: Bitboards
+	pub(crate) fn bitboards (&self) -> Option<&Bitboards>
+	{
+		self.bitboards.as_ref()
+	}
+
+	/// Answer the receiver without the bitboards derived from its grid, so
+	/// that every query scans the grid instead, just as for a board too large
+	/// to have bitboards. This is slower, so it is only useful for benchmarks
+	/// and for proving that both representations agree. [`reset`] derives the
+	/// bitboards anew.
+	///
+	/// [`reset`]: Board::reset
+	pub fn without_bitboards (mut self) -> Board
+	{
+		self.bitboards = None;
+		self
+	}
+
+	/// Place the specified [stone] into the grid at the specified index,
+	/// maintaining the [bitboards].
+	///
+	/// [stone]: AnyStone
+	/// [bitboards]: Bitboards
+	fn set_cell (&mut self, index: usize, stone: AnyStone)
+	{
+		if let Some(bitboards) = &mut self.bitboards
+		{
+			bitboards.set(index, &self.grid[index], &stone);
+		}
+		self.grid[index] = stone;
+	}
+
+	/// Answer `true` if sweeping away the [survivors] among the cells of the
+	/// specified mask could remove any, `false` if the [bitboards] prove that
+	/// it could not. Absent bitboards, answer `true`.
+	///
+	/// [survivors]: SurvivorStone
+	/// [bitboards]: Bitboards
+	fn may_sweep (&self, mask: impl Fn(&Bitboards) -> u128) -> bool
+	{
+		match &self.bitboards
+		{
+			Some(bitboards) =>
+			{
+				let mask = mask(bitboards);
+				bitboards.survivors & mask != 0
+					&& bitboards.removable & mask == 0
+			},
+			None => true
+		}
+	}
+
+	/// Answer the number of empty cells at the bottom of the specified column.
+	fn clearance (&self, column: u32) -> u32
+	{
+		(0..self.height).rev()
+			.map(|row| self.grid[self.index((column, row))])
+			.take_while(|stone| matches!(stone, AnyStone::None(_)))
+			.count() as u32
+	}
+
+	/// Measure the [clearance] of every column afresh.
+	///
+	/// [clearance]: Board::clearance
+	fn measure_clearances (&mut self)
+	{
+		self.clearances = (0..self.width).map(|x| self.clearance(x)).collect();
+	}
+
+	/// Answer the receiver, having [measured] the [clearance] of every column.
+	///
+	/// [measured]: Board::measure_clearances
+	/// [clearance]: Board::clearance
+	fn with_clearances (mut self) -> Board
+	{
+		self.measure_clearances();
+		self.bitboards = Bitboards::new(self.width, &self.grid);
+		self
+	}
+
+	/// Update the [clearances] of the columns of the specified points, which
+	/// the grid has just changed. If the clearances have not been [measured]
+	/// yet, then measure every column instead.
+	///
+	/// [clearances]: Board::clearance
+	/// [measured]: Board::measure_clearances
+	fn update_clearances (&mut self, points: impl Iterator<Item=Point>)
+	{
+		if self.clearances.len() != self.width as usize
+		{
+			self.measure_clearances();
+			return
+		}
+		for (column, _) in points
+		{
+			self.clearances[column as usize] = self.clearance(column);
+		}
+	}
+
+	/// Update the [clearances] of every column that the specified removal
+	/// touches, whether it has just been applied or reversed.
+	///
+	/// [clearances]: Board::clearance
+	fn update_clearances_for (
+		&mut self,
+		p: Point,
+		blasted: &[Point],
+		falls: &[(Point, Point)],
+		survivors: &[Point])
+	{
+		self.update_clearances(once(p)
+			.chain(blasted.iter().copied())
+			.chain(falls.iter().flat_map(|&(from, to)| [from, to]))
+			.chain(survivors.iter().copied()));
+	}
+
+	/// Remove the [stone] at the specified location, capturing it, and
+	/// asserting that it has the specified color. The color information is
+	/// needed for proper treatment of [wild stones]: a wild stone consumes the
+	/// specified color, so specify the color of its triplet even if the wild
+	/// stone begins it. A wild stone removed with color `0` consumes no color
+	/// at all. Answer a closure that can reverse the effect of this removal.
+	///
+	/// # Panics
+	///
+	/// If the location holds no removable stone, e.g., if it is empty or holds
+	/// a survivor or toggle stone; if `color` is neither `0` nor the color of
+	/// the ordinary or armored stone there; if the wild stone there cannot
+	/// provide a nonzero `color`; or if `color` is `0` for a rainbow stone.
+	/// [`play`] validates a whole triplet instead.
+	///
+	/// [stone]: AnyStone
+	/// [wild stones]: WildStone
+	/// [`play`]: Board::play
+	#[must_use]
+	pub fn remove (
 		&mut self,
 		p: Point,
 		s: &mut AnyStone,
-		color: u32) -> Box<dyn for<'r> FnMut(&'r mut Board)>
+		color: u64) -> Box<dyn for<'r> FnMut(&'r mut Board)>
 	{
-		let index = (p.1 * self.width + p.0) as usize;
+		let removal = self.apply_removal(p, color);
+		*s = removal.stone;
+		Box::new(move |board: &mut Board| board.reverse_removal(&removal))
+	}
+
+	/// Remove the [stone] at the specified location, asserting that it has
+	/// the specified color, just as [`remove`] does, but record the removal on
+	/// the undo stack, so that [`undo`] can reverse it. This forgets any moves
+	/// that [`redo`] could have replayed.
+	///
+	/// The undo stack only tracks moves played by this method and by
+	/// [`redo`], so intermixing other mutations, e.g., [`force_remove`] or
+	/// [`play`], leaves the stacks meaningless.
+	///
+	/// # Panics
+	///
+	/// Under the same conditions as [`remove`].
+	///
+	/// [stone]: AnyStone
+	/// [`remove`]: Board::remove
+	/// [`undo`]: Board::undo
+	/// [`redo`]: Board::redo
+	/// [`force_remove`]: Board::force_remove
+	/// [`play`]: Board::play
+	pub fn play_undoable (&mut self, p: Point, color: u64)
+	{
+		let removal = self.apply_removal(p, color);
+		self.undo_stack.push(removal);
+		self.redo_stack.clear();
+	}
+
+	/// Reverse the most recent move on the undo stack, making it available to
+	/// [`redo`]. Answer `true` if a move was reversed, `false` if the undo
+	/// stack was empty.
+	///
+	/// [`redo`]: Board::redo
+	pub fn undo (&mut self) -> bool
+	{
+		match self.undo_stack.pop()
+		{
+			Some(removal) =>
+			{
+				self.reverse_removal(&removal);
+				self.redo_stack.push((removal.p, removal.color));
+				true
+			},
+			None => false
+		}
+	}
+
+	/// Replay the move most recently reversed by [`undo`]. Answer `true` if a
+	/// move was replayed, `false` if there was nothing to replay.
+	///
+	/// [`undo`]: Board::undo
+	pub fn redo (&mut self) -> bool
+	{
+		match self.redo_stack.pop()
+		{
+			Some((p, color)) =>
+			{
+				let removal = self.apply_removal(p, color);
+				self.undo_stack.push(removal);
+				true
+			},
+			None => false
+		}
+	}
+
+	/// Restore the receiver to the state in which it was constructed, e.g., to
+	/// restart a level. Unlike [`undo`], this also reverses [forcible
+	/// removals]. The undo and redo stacks are emptied, and the highlight is
+	/// cleared.
+	///
+	/// [`undo`]: Board::undo
+	/// [forcible removals]: Board::force_remove
+	pub fn reset (&mut self)
+	{
+		self.turn = 0;
+		self.wild_colors = self.initial_wild_colors;
+		self.locked_color = 0;
+		self.highlight.clear();
+		self.grid = self.initial.clone();
+		self.removable_stones =
+			self.grid.iter().filter(|s| s.is_removable()).count() as u32;
+		self.undo_stack.clear();
+		self.redo_stack.clear();
+		self.measure_clearances();
+		self.bitboards = Bitboards::new(self.width, &self.grid);
+	}
+
+	/// Remove the [stone] at the specified location, asserting that it has
+	/// the specified color. Answer the record needed to [reverse] the removal.
+	///
+	/// [stone]: AnyStone
+	/// [reverse]: Board::reverse_removal
+	fn apply_removal (&mut self, p: Point, color: u64) -> Removal
+	{
+		let index = self.index(p);
 		let stone = self.grid[index].for_board(self);
-		*s = stone;
-		match stone
+		// Determine the color of the stone, and the wild color to consume, if
+		// any. A wild stone only consumes a color once the triplet's color is
+		// known.
+		let (stone_color, wild_color) = match stone
 		{
 			AnyStone::Ordinary(o) =>
 			{
 				assert!(color == 0 || color == o.color);
-				self.grid[index] = AnyStone::None(NoStone);
-				self.turn += 1;
-				self.removable_stones -= 1;
-				let survivors = self.remove_survivors(p);
-				Box::new(move |board: &mut Board|
-				{
-					board.add_survivors(&survivors);
-					board.removable_stones += 1;
-					board.turn -= 1;
-					board.grid[index] = stone;
-				})
+				(o.color, 0)
 			},
-			AnyStone::Wild(_) if color == 0 =>
+			AnyStone::Wild(_) if color == 0 => (0, 0),
+			AnyStone::Wild(w) =>
 			{
-				self.grid[index] = AnyStone::None(NoStone);
-				self.turn += 1;
-				self.removable_stones -= 1;
-				let survivors = self.remove_survivors(p);
-				Box::new(move |board: &mut Board|
-				{
-					board.add_survivors(&survivors);
-					board.removable_stones += 1;
-					board.turn -= 1;
-					board.grid[index] = stone;
-				})
+				assert_ne!(w.colors(self) & color, 0);
+				(color, color)
 			},
-			AnyStone::Wild(_) =>
-			{
-				assert_ne!(self.wild_colors & color, 0);
-				self.grid[index] = AnyStone::None(NoStone);
-				self.turn += 1;
-				self.removable_stones -= 1;
-				self.wild_colors &= !color;
-				let survivors = self.remove_survivors(p);
-				Box::new(move |board: &mut Board|
-				{
-					board.add_survivors(&survivors);
-					board.wild_colors |= color;
-					board.removable_stones += 1;
-					board.turn -= 1;
-					board.grid[index] = stone;
-				})
+			AnyStone::Bomb(_) => (color, 0),
+			AnyStone::Rainbow(_) =>
+			{
+				assert_ne!(color, 0);
+				(color, 0)
+			},
+			AnyStone::Armored(a) =>
+			{
+				assert!(color == 0 || color == a.color());
+				(a.color(), 0)
 			},
 			_ => unreachable!()
+		};
+		// An armored stone only yields to its final hit.
+		let remnant = match stone
+		{
+			AnyStone::Armored(a) => a.struck(),
+			_ => AnyStone::None(NoStone)
+		};
+		self.set_cell(index, remnant);
+		let turn = self.turn;
+		self.turn = turn.saturating_add(1);
+		if !remnant.is_removable()
+		{
+			self.removable_stones -= 1;
+		}
+		self.wild_colors &= !wild_color;
+		// Completing a triplet on a color-locked board locks its color, if
+		// that color is subject to the lock, and otherwise releases the lock.
+		let locked_color = self.locked_color;
+		if self.lock_colors != 0 && self.turn % 3 == 0
+		{
+			self.locked_color = stone_color & self.lock_colors;
+		}
+		let blasted = match stone
+		{
+			AnyStone::Bomb(_) => self.detonate(p),
+			_ => vec![]
+		};
+		let falls = self.fall(p, &blasted);
+		let survivors = self.remove_survivors_near(p, &blasted, &falls);
+		let points = blasted.iter().map(|&(q, _)| q).collect::<Vec<_>>();
+		self.update_clearances_for(p, &points, &falls, &survivors);
+		Removal {
+			p,
+			color,
+			stone,
+			turn,
+			wild_color,
+			locked_color,
+			blasted,
+			falls,
+			survivors
+		}
+	}
+
+	/// Detonate the bomb formerly at the specified point, which has already
+	/// been removed, removing every adjacent [ordinary stone] and [bomb].
+	/// Bombs caught in the blast explode in turn. Answer the points and stones
+	/// caught in the blast, in the order of their removal.
+	///
+	/// [ordinary stone]: OrdinaryStone
+	/// [bomb]: BombStone
+	fn detonate (&mut self, p: Point) -> Vec<(Point, AnyStone)>
+	{
+		let mut blasted = Vec::<(Point, AnyStone)>::new();
+		let mut bombs = vec![p];
+		while let Some((column, row)) = bombs.pop()
+		{
+			let columns =
+				column.saturating_sub(1)..=(column + 1).min(self.width - 1);
+			for y in row.saturating_sub(1)..=(row + 1).min(self.height - 1)
+			{
+				for x in columns.clone()
+				{
+					// A stone is only ever removed once, because its cell is
+					// emptied immediately.
+					let index = self.index((x, y));
+					let stone = self.grid[index];
+					match stone
+					{
+						AnyStone::Ordinary(_) => {},
+						AnyStone::Bomb(_) => bombs.push((x, y)),
+						_ => continue
+					}
+					self.set_cell(index, AnyStone::None(NoStone));
+					self.removable_stones -= 1;
+					blasted.push(((x, y), stone));
+				}
+			}
+		}
+		blasted
+	}
+
+	/// If the board has [gravity], then let the stones above the specified
+	/// point, and above the specified stones caught in a blast, fall into the
+	/// vacated cells. Answer the falls, as pairs of source and destination
+	/// points, in the order that they occurred.
+	///
+	/// [gravity]: GravityRule::Down
+	fn fall (
+		&mut self,
+		p: Point,
+		blasted: &[(Point, AnyStone)]) -> Vec<(Point, Point)>
+	{
+		let mut falls = Vec::<(Point, Point)>::new();
+		if self.gravity == GravityRule::None
+		{
+			return falls
+		}
+		let vacated = once(p).chain(blasted.iter().map(|&(q, _)| q));
+		for (column, row) in vacated
+		{
+			// A cell that is still occupied, e.g., by a struck armored stone,
+			// was not vacated.
+			let index = self.index((column, row));
+			if !matches!(self.grid[index], AnyStone::None(_))
+			{
+				continue
+			}
+			// Find the bottom of the run of cells that contains the vacated
+			// cell. Toggles bound the run, because they never move.
+			let is_toggle = |board: &Board, row: u32|
+				matches!(board.grid[board.index((column, row))],
+					AnyStone::Toggle(_));
+			let mut floor = row;
+			while floor + 1 < self.height && !is_toggle(self, floor + 1)
+			{
+				floor += 1;
+			}
+			// Settle the stones of the run onto the floor. Settling is
+			// idempotent, so a run with several vacated cells is settled
+			// completely by its first.
+			let mut rest = floor;
+			for y in (0..=floor).rev()
+			{
+				let index = self.index((column, y));
+				match self.grid[index]
+				{
+					AnyStone::Toggle(_) => break,
+					AnyStone::None(_) => continue,
+					stone =>
+					{
+						if y != rest
+						{
+							let target = self.index((column, rest));
+							self.set_cell(target, stone);
+							self.set_cell(index, AnyStone::None(NoStone));
+							falls.push(((column, y), (column, rest)));
+						}
+						rest = rest.saturating_sub(1);
+					}
+				}
+			}
+		}
+		falls
+	}
+
+	/// Remove the [survivors] released by the removal of the stone at the
+	/// specified point, together with the specified stones caught in a blast
+	/// and the specified falls. Answer the removed survivors.
+	///
+	/// [survivors]: SurvivorStone
+	#[must_use]
+	fn remove_survivors_near (
+		&mut self,
+		p: Point,
+		blasted: &[(Point, AnyStone)],
+		falls: &[(Point, Point)]) -> Vec<Point>
+	{
+		let mut survivors = self.remove_survivors(p);
+		for &(q, _) in blasted
+		{
+			survivors.append(&mut self.remove_survivors(q));
+		}
+		for &(from, to) in falls
+		{
+			survivors.append(&mut self.remove_survivors(from));
+			survivors.append(&mut self.remove_survivors(to));
+		}
+		survivors
+	}
+
+	/// Reverse the specified removal, which must be the most recent removal
+	/// that has not yet been reversed.
+	fn reverse_removal (&mut self, removal: &Removal)
+	{
+		self.add_survivors(&removal.survivors);
+		for &(from, to) in removal.falls.iter().rev()
+		{
+			let (source, target) = (self.index(from), self.index(to));
+			self.set_cell(source, self.grid[target]);
+			self.set_cell(target, AnyStone::None(NoStone));
+		}
+		for &(q, stone) in removal.blasted.iter().rev()
+		{
+			let index = self.index(q);
+			self.set_cell(index, stone);
+		}
+		self.locked_color = removal.locked_color;
+		self.wild_colors |= removal.wild_color;
+		// A struck armored stone was not removed.
+		let index = self.index(removal.p);
+		let cleared = !self.grid[index].is_removable();
+		self.removable_stones +=
+			cleared as u32 + removal.blasted.len() as u32;
+		self.turn = removal.turn;
+		self.set_cell(index, removal.stone);
+		let blasted =
+			removal.blasted.iter().map(|&(q, _)| q).collect::<Vec<_>>();
+		self.update_clearances_for(
+			removal.p, &blasted, &removal.falls, &removal.survivors);
+	}
+
+	/// Remove all [survivors] from the row of the specified point, but only if
+	/// there are no removable stones in the row with them. If the board's
+	/// [survivor rule] permits, then do likewise for the column of the
+	/// specified point. Answer the removed survivors.
+	///
+	/// [survivors]: SurvivorStone
+	/// [survivor rule]: SurvivorRule
+	#[must_use]
+	fn remove_survivors (&mut self, p: Point) -> Vec<Point>
+	{
+		let mut survivors = Vec::<Point>::new();
+		if self.may_sweep(|bitboards| bitboards.row(p.1))
+		{
+			survivors.append(&mut self.remove_survivors_among(
+				(0..self.width).map(|x| (x, p.1))));
+		}
+		if self.survivor_rule == SurvivorRule::RowOrColumn
+			&& self.may_sweep(|bitboards| bitboards.column(p.0))
+		{
+			survivors.append(&mut self.remove_survivors_among(
+				(0..self.height).map(|y| (p.0, y))));
+		}
+		survivors
+	}
+
+	/// Remove the [survivors] whose rows contain no removable stones from the
+	/// outset, or whose columns contain none, if the board's [survivor rule]
+	/// permits. Survivors are otherwise only swept away when a stone leaves
+	/// their row or column, so such a survivor would never disappear, and
+	/// would forever obstruct the stones beyond it. The initial grid omits
+	/// the removed survivors too.
+	///
+	/// [survivors]: SurvivorStone
+	/// [survivor rule]: SurvivorRule
+	fn with_released_survivors (mut self) -> Board
+	{
+		let lines = (0..self.height).map(|row| (0, row))
+			.chain((0..self.width).map(|column| (column, 0)))
+			.collect::<Vec<_>>();
+		for p in lines
+		{
+			let _ = self.remove_survivors(p);
 		}
+		self.initial = self.grid.clone();
+		self
+	}
+
+	/// Remove all [survivors] from the specified points, but only if there are
+	/// no removable stones among them. Answer the removed survivors.
+	///
+	/// [survivors]: SurvivorStone
+	#[must_use]
+	fn remove_survivors_among (
+		&mut self,
+		points: impl Iterator<Item=Point> + Clone) -> Vec<Point>
+	{
+		let removable = points.clone()
+			.map(|p| self.grid[self.index(p)])
+			.filter(|s| s.is_removable())
+			.count();
+		if removable == 0
+		{
+			let mut survivors = Vec::<Point>::new();
+			for p in points
+			{
+				let index = self.index(p);
+				match self.grid[index]
+				{
+					AnyStone::Survivor(_) =>
+					{
+						survivors.push(p);
+						self.set_cell(index, AnyStone::None(NoStone));
+					},
+					_ => {}
+				}
+			}
+			survivors
+		}
+		else
+		{
+			vec![]
+		}
+	}
+
+	/// Add [survivors] to the specified locations.
+	///
+	/// [survivors]: SurvivorStone
+	fn add_survivors (&mut self, survivors: &Vec<Point>)
+	{
+		for p in survivors
+		{
+			let index = self.index(*p);
+			self.set_cell(index, AnyStone::Survivor(SurvivorStone));
+		}
+	}
+
+	/// Forcibly remove the [stone] at the specified location without doing any
+	/// accounting other than incrementing the turn and maintaining the count
+	/// of [removable stones]. This is a destructive operation, and should not
+	/// be used for computing a board solution. An [armored stone] merely loses
+	/// one hit.
+	///
+	/// [stone]: AnyStone
+	/// [removable stones]: Board::removable_stones
+	/// [armored stone]: ArmoredStone
+	pub fn force_remove (&mut self, p: Point)
+	{
+		let _ = self.force_removal(p);
+	}
+
+	/// Forcibly remove the [stone] at the specified location, just as
+	/// [`force_remove`] does. Answer the consequences of the removal, i.e.,
+	/// the points of the stones caught in the blast, the falls, and the
+	/// survivors swept away, as they are recorded by a [removal].
+	///
+	/// [stone]: AnyStone
+	/// [`force_remove`]: Board::force_remove
+	/// [removal]: Removal
+	fn force_removal (
+		&mut self,
+		p: Point) -> (Vec<Point>, Vec<(Point, Point)>, Vec<Point>)
+	{
+		let index = self.index(p);
+		let stone = self.grid[index];
+		let remnant = match stone
+		{
+			AnyStone::Armored(a) => a.struck(),
+			_ => AnyStone::None(NoStone)
+		};
+		if stone.is_removable() && !remnant.is_removable()
+		{
+			self.removable_stones -= 1;
+		}
+		self.set_cell(index, remnant);
+		self.turn = self.turn.saturating_add(1);
+		let blasted = match stone
+		{
+			AnyStone::Bomb(_) => self.detonate(p),
+			_ => vec![]
+		};
+		let falls = self.fall(p, &blasted);
+		let survivors = self.remove_survivors_near(p, &blasted, &falls);
+		let blasted = blasted.into_iter().map(|(q, _)| q).collect::<Vec<_>>();
+		self.update_clearances_for(p, &blasted, &falls, &survivors);
+		(blasted, falls, survivors)
+	}
+
+	/// Answer the [stone] at `(x,y)`, where the origin `(0,0)` is the
+	/// uppermost leftmost grid cell, in the state given by the current turn.
+	/// Answer `None` if the point lies outside the board.
+	///
+	/// [stone]: AnyStone
+	pub fn get (&self, (x, y): Point) -> Option<AnyStone>
+	{
+		(x < self.width && y < self.height)
+			.then(|| self.grid[self.index((x, y))].for_board(self))
+	}
+
+	/// Apply the specified closure to the [stone] at `(x,y)`, where the origin
+	/// `(0,0)` is the uppermost leftmost grid cell.
+	///
+	/// [stone]: AnyStone
+	pub fn stone_do (
+		&self,
+		p: Point,
+		action: &mut dyn for<'r, 's> FnMut(&'r Board, &'s AnyStone))
+	{
+		let index = self.index(p);
+		let stone = self.grid[index].for_board(self);
+		action(self, &stone)
+	}
+
+	/// Answer an iterator over every cell of the grid, in row-major order,
+	/// that yields the point of the cell and its [stone], in the state given
+	/// by the current turn.
+	///
+	/// [stone]: AnyStone
+	pub fn iter_stones (&self) -> impl Iterator<Item = (Point, AnyStone)> + '_
+	{
+		self.grid.iter()
+			.enumerate()
+			.map(|(index, stone)| (self.point(index), stone.for_board(self)))
+	}
+
+	/// Answer an iterator over the rows of the grid, from top to bottom, each
+	/// of which is an iterator over its [stones], from left to right, in the
+	/// state given by the current turn.
+	///
+	/// [stones]: AnyStone
+	pub fn iter_rows (
+		&self) -> impl Iterator<Item = impl Iterator<Item = AnyStone> + '_> + '_
+	{
+		self.grid.chunks(self.width as usize)
+			.map(|row| row.iter().map(|stone| stone.for_board(self)))
+	}
+
+	/// Answer the grid as a vector of its rows, from top to bottom, each of
+	/// which is a vector of its [stones], from left to right, in the state
+	/// given by the current turn, e.g., for a consumer that would rather
+	/// index by row and column. The answer has [`height`] rows of [`width`]
+	/// stones apiece, so the stone at `(x,y)` is at `[y][x]`.
+	///
+	/// [stones]: AnyStone
+	/// [`height`]: Board::height
+	/// [`width`]: Board::width
+	pub fn grid_2d (&self) -> Vec<Vec<AnyStone>>
+	{
+		self.iter_rows().map(|row| row.collect()).collect()
+	}
+
+	/// Apply the specified closure while the specified [stone] is highlighted.
+	///
+	/// [stone]: AnyStone
+	pub fn with_highlight (
+		&mut self,
+		p: Point,
+		action: &mut dyn for<'r> FnMut(&'r Board))
+	{
+		self.with_highlight_many(&[p], action);
+	}
+
+	/// Apply the specified closure while the [stones] at the specified points
+	/// are highlighted, e.g., to show a whole triplet at once.
+	///
+	/// [stones]: AnyStone
+	pub fn with_highlight_many (
+		&mut self,
+		points: &[Point],
+		action: &mut dyn for<'r> FnMut(&'r Board))
+	{
+		self.highlight = points.to_vec();
+		action(self);
+		self.highlight.clear();
+	}
+
+	/// Render the receiver in the specified style with the specified [stone]
+	/// highlighted, and then [remove] it as a stone of the specified color,
+	/// i.e., the color of its triplet, without retaining the means to undo the
+	/// removal. Answer the rendition. This is a single step of an interactive
+	/// hint loop. Unlike a [forcible removal], this consumes wild colors and
+	/// honors the color lock, so the board remains fit for solving, e.g., by
+	/// the [interactive assist] that precedes each hint. That is why the
+	/// color must be given: a wild stone cannot know which color to consume,
+	/// so take the colors from [`solution_colors`]. The style is given, too,
+	/// rather than inferred, so that a caller can render for a terminal or
+	/// for a file alike.
+	///
+	/// # Panics
+	///
+	/// Under the same conditions as [`remove`].
+	///
+	/// [stone]: AnyStone
+	/// [remove]: Board::remove
+	/// [forcible removal]: Board::force_remove
+	/// [interactive assist]: Board::solve_interactive_assist
+	/// [`solution_colors`]: Board::solution_colors
+	/// [`remove`]: Board::remove
+	pub fn advance_hint (
+		&mut self,
+		p: Point,
+		color: u64,
+		style: RenderStyle) -> String
+	{
+		let mut rendition = String::new();
+		self.with_highlight(p, &mut |board| rendition = board.render(style));
+		let _ = self.apply_removal(p, color);
+		rendition
+	}
+
+	/// Answer a copy of the receiver rotated 90° clockwise, so that its width
+	/// and height are swapped. The [access direction] does not rotate with the
+	/// stones, so a rotated board is generally a different puzzle than the
+	/// original, and its solvability may differ. Four successive rotations
+	/// produce a board equal to the original.
+	///
+	/// [access direction]: Board::access
+	pub fn rotate_cw (&self) -> Board
+	{
+		self.rotated(|board, (column, row)| (row, board.height - 1 - column))
+	}
+
+	/// Answer a copy of the receiver rotated 90° counterclockwise, so that its
+	/// width and height are swapped. The caveats of [`rotate_cw`] apply.
+	///
+	/// [`rotate_cw`]: Board::rotate_cw
+	pub fn rotate_ccw (&self) -> Board
+	{
+		self.rotated(|board, (column, row)| (board.width - 1 - row, column))
+	}
+
+	/// Answer `true` if the receiver is its own mirror image, i.e., if
+	/// reflecting it across the axis parallel to its [access direction] maps
+	/// every stone onto an identical stone. Stones accessed from the bottom
+	/// or the top are reflected left to right; otherwise, they are reflected
+	/// top to bottom, which is never a symmetry under [gravity]. Every
+	/// sequence of moves on a symmetric board has a [mirror image] that
+	/// succeeds or fails just as it does.
+	///
+	/// [access direction]: Board::access
+	/// [gravity]: GravityRule::Down
+	/// [mirror image]: Board::mirror
+	pub fn is_mirror_symmetric (&self) -> bool
+	{
+		let vertical = matches!(
+			self.access,
+			AccessDirection::FromLeft | AccessDirection::FromRight);
+		if vertical && self.gravity != GravityRule::None
+		{
+			return false
+		}
+		(0..self.grid.len()).all(|index|
+		{
+			let image = self.index(self.mirror(self.point(index)));
+			self.grid[index] == self.grid[image]
+		})
+	}
+
+	/// Answer the reflection of the specified point across the axis parallel
+	/// to the receiver's [access direction], as described for
+	/// [`is_mirror_symmetric`].
+	///
+	/// [access direction]: Board::access
+	/// [`is_mirror_symmetric`]: Board::is_mirror_symmetric
+	pub fn mirror (&self, (column, row): Point) -> Point
+	{
+		match self.access
+		{
+			AccessDirection::FromBottom | AccessDirection::FromTop =>
+				(self.width - 1 - column, row),
+			AccessDirection::FromLeft | AccessDirection::FromRight =>
+				(column, self.height - 1 - row)
+		}
+	}
+
+	/// Answer a copy of the receiver with its width and height swapped, such
+	/// that each point of the copy holds the [stone] found at the point of the
+	/// receiver computed by `source`.
+	///
+	/// [stone]: AnyStone
+	fn rotated (&self, source: impl Fn(&Board, Point) -> Point) -> Board
+	{
+		let mut board = self.clone();
+		board.width = self.height;
+		board.height = self.width;
+		board.highlight.clear();
+		board.undo_stack.clear();
+		board.redo_stack.clear();
+		board.properties.insert(
+			PropertyKey::Width, PropertyValue::U32(board.width));
+		if let Some(height) = board.properties.get_mut(&PropertyKey::Height)
+		{
+			*height = PropertyValue::U32(board.height);
+		}
+		let rotate = |grid: &[AnyStone]| (0..grid.len())
+			.map(|index| board.point(index))
+			.map(|p| grid[self.index(source(self, p))])
+			.collect::<Vec<_>>();
+		let (grid, initial) = (rotate(&self.grid), rotate(&self.initial));
+		board.grid = grid;
+		board.initial = initial;
+		board.measure_clearances();
+		board.bitboards = Bitboards::new(board.width, &board.grid);
+		board
+	}
+
+	/// Answer a copy of the receiver whose colors are renumbered into a
+	/// canonical order, so that boards that differ only in the assignment of
+	/// [colors] to characters compare equal once canonicalized, e.g., for
+	/// deduplicating a library of levels. The colors are renumbered in the
+	/// order of their first appearance in the grid, scanning row by row from
+	/// the upper left cell; the colors that do not appear in the grid, e.g.,
+	/// wild colors whose stones are gone, follow in the order of their
+	/// characters. The characters of the stones and the display properties
+	/// are unchanged. The undo and redo stacks of the copy are empty.
+	///
+	/// [colors]: OrdinaryStone::color
+	pub fn canonicalize (&self) -> Board
+	{
+		let mut named = self.colors.iter()
+			.map(|(&rep, &color)| (rep, color))
+			.collect::<Vec<_>>();
+		named.sort_unstable();
+		let others = self.grid.iter()
+			.chain(self.initial.iter())
+			.filter_map(|stone| match stone
+			{
+				AnyStone::Wild(w) => w.restriction,
+				_ => None
+			})
+			.fold(
+				self.wild_colors | self.initial_wild_colors | self.locked_color,
+				|mask, restriction| mask | restriction);
+		let mut allocation = Vec::<u64>::new();
+		for color in self.grid.iter()
+			.filter_map(|stone| match stone
+			{
+				AnyStone::Ordinary(o) => Some(o.color),
+				AnyStone::Armored(a) => Some(a.stone.color),
+				_ => None
+			})
+			.chain(named.into_iter().map(|(_, color)| color))
+			.chain((0..u64::BITS).map(|bit| others & 1 << bit))
+			.filter(|&color| color != 0)
+		{
+			if !allocation.contains(&color)
+			{
+				allocation.push(color);
+			}
+		}
+		let renumber = |mask: u64| renumber_colors(&allocation, mask);
+		let mut board = self.clone();
+		board.undo_stack.clear();
+		board.redo_stack.clear();
+		board.grid = self.grid.iter()
+			.map(|&stone| recolor(stone, &renumber))
+			.collect();
+		board.initial = self.initial.iter()
+			.map(|&stone| recolor(stone, &renumber))
+			.collect();
+		board.wild_colors = renumber(self.wild_colors);
+		board.initial_wild_colors = renumber(self.initial_wild_colors);
+		board.locked_color = renumber(self.locked_color);
+		if self.lock_colors != u64::MAX
+		{
+			board.lock_colors = renumber(self.lock_colors);
+		}
+		board.colors.values_mut().for_each(|color| *color = renumber(*color));
+		if let Some(PropertyValue::U64(mask)) =
+			board.properties.get_mut(&PropertyKey::Wild)
+		{
+			*mask = renumber(*mask);
+		}
+		board.bitboards = Bitboards::new(board.width, &board.grid);
+		board
+	}
+
+	/// Answer the textual representation of the receiver, in the same format
+	/// accepted by [`parse`], such that parsing the answer yields an equivalent
+	/// board. The legend comprises the properties authored in the original
+	/// legend, in their original order; the width of the board is implied by
+	/// the grid, whose rows are fully populated. The footer, if any, gives the
+	/// [expected solution], one triplet per line.
+	///
+	/// Only the current state of the board is described: toggle stones are
+	/// written as they stand now, and only the colors of the wild stones that
+	/// remain are written, so the `wild` property is omitted once every wild
+	/// color is consumed. The turn and any triplet in progress are lost, as
+	/// is the progress of toggle stones through their [periods].
+	///
+	/// [`parse`]: Board::parse
+	/// [expected solution]: Board::expected_solution
+	/// [periods]: ToggleStone::period
+	pub fn to_tsb (&self) -> String
+	{
+		let mut tsb = String::new();
+		for (key, value) in self.properties()
+		{
+			// Once every wild color is consumed, the property would be empty,
+			// which the legend parser rejects.
+			if *key == PropertyKey::Wild && self.wild_colors == 0
+			{
+				continue
+			}
+			tsb.push_str(&format!("{} = {}\n", key, self.term(key, value)));
+		}
+		// Without a preceding line, the separator would be mistaken for a row
+		// of the grid.
+		if tsb.is_empty()
+		{
+			tsb.push('\n');
+		}
+		tsb.push_str("---\n");
+		tsb.push_str(&self.grid_text());
+		if let Some(solution) = &self.expected_solution
+		{
+			tsb.push('\n');
+			for triplet in solution.chunks(3)
+			{
+				let moves = triplet.iter()
+					.map(|(column, row)| format!("{},{}", column, row))
+					.collect::<Vec<_>>();
+				tsb.push_str(&format!("solution = {}\n", moves.join(" ")));
+			}
+		}
+		tsb
+	}
+
+	/// Answer the rows of the grid as they appear in the [text format], i.e.,
+	/// the codes of the stones of each row, separated by single spaces, with
+	/// each row terminated by a newline.
+	///
+	/// [text format]: Board::to_tsb
+	fn grid_text (&self) -> String
+	{
+		let mut text = String::new();
+		for row in 0..self.height
+		{
+			let tokens = (0..self.width)
+				.map(|column|
+				{
+					let stone = &self.grid[self.index((column, row))];
+					self.code(stone)
+				})
+				.collect::<Vec<_>>();
+			text.push_str(&tokens.join(" "));
+			text.push('\n');
+		}
+		text
+	}
+
+	/// Answer the content hash of the grid, for use as the `checksum`
+	/// property of a level pack that must detect tampering or corruption.
+	/// This is the 64-bit [FNV-1a] hash of the UTF-8 encoding of the rows of
+	/// the grid exactly as [`to_tsb`] writes them, i.e., the codes of the
+	/// stones of each row separated by single spaces, with each row
+	/// terminated by a newline. The hash reflects the current state of the
+	/// grid, and is independent of the legend, the platform, and the colors
+	/// allocated to the stones. A declared checksum is verified against the
+	/// grid as written, i.e., before any idle [survivors] are released, so it
+	/// may be computed directly from the rows of the file.
+	///
+	/// [FNV-1a]: http://www.isthe.com/chongo/tech/comp/fnv/
+	/// [`to_tsb`]: Board::to_tsb
+	/// [survivors]: SurvivorStone
+	pub fn content_hash (&self) -> u64
+	{
+		self.grid_text().bytes().fold(
+			0xcbf2_9ce4_8422_2325,
+			|hash, byte| (hash ^ byte as u64).wrapping_mul(0x100_0000_01b3))
+	}
+
+	/// Answer the single-line compact encoding of the receiver, e.g.,
+	/// `wild=g;w3:rgr/*_`, as accepted by [`from_compact`]. The encoding
+	/// comprises the `wild`, `colorlock`, `armor`, `survivorrule`, `gravity`,
+	/// and `access` properties and the display properties, if present in the
+	/// legend, each of the form `key=value;`, then the width of the board, as
+	/// `w«width»:`, and finally the codes of the stones, in row-major order.
+	/// The codes are the same as the tokens of the grid of the [text format].
+	///
+	/// The compact encoding is meant for test fixtures and assertion messages,
+	/// not for storage. Just as for [`to_tsb`], only the current state of the
+	/// board is described; moreover, the expected solution is omitted. Without
+	/// the `wild` property, which is written only if the legend has one, the
+	/// wild color space is not preserved.
+	/// Because the codes of [armored stones] end with digits, a board whose
+	/// ordinary stones are represented by the digits `2` through `9` may not
+	/// survive a round trip.
+	///
+	/// [`from_compact`]: Board::from_compact
+	/// [armored stones]: ArmoredStone
+	/// [text format]: Board::parse
+	/// [`to_tsb`]: Board::to_tsb
+	pub fn to_compact (&self) -> String
+	{
+		let mut compact = String::new();
+		let keys = [
+			PropertyKey::Wild,
+			PropertyKey::ColorLock,
+			PropertyKey::Armor,
+			PropertyKey::SurvivorRule,
+			PropertyKey::Gravity,
+			PropertyKey::Access
+		];
+		let display = self.properties()
+			.map(|(key, _)| key)
+			.filter(|key| matches!(key, PropertyKey::Display(_)))
+			.cloned();
+		for key in keys.into_iter().chain(display)
+		{
+			if let Some(value) = self.properties.get(&key)
+			{
+				compact.push_str(
+					&format!("{}={};", key, self.term(&key, value)));
+			}
+		}
+		compact.push_str(&format!("w{}:", self.width));
+		compact.extend(self.grid.iter().map(|stone|
+		{
+			// Delimit the colors of a restricted wild stone, because the
+			// compact encoding does not separate its codes.
+			let code = self.code(stone);
+			match code.strip_prefix('*')
+			{
+				Some(own) if !own.is_empty() => format!("*({})", own),
+				_ => code
+			}
+		}));
+		compact
+	}
+
+	/// Answer the code that represents the current state of the specified
+	/// [stone] in the grid of the [text format].
+	///
+	/// [stone]: AnyStone
+	/// [text format]: Board::parse
+	fn code (&self, stone: &AnyStone) -> String
+	{
+		match stone.for_board(self)
+		{
+			AnyStone::None(_) => "_".to_string(),
+			AnyStone::Ordinary(o) => o.rep.to_string(),
+			AnyStone::Survivor(_) => "#".to_string(),
+			AnyStone::Wild(w) => match w.restriction
+			{
+				// Only the colors that the stone can still provide are wild
+				// colors of the written board.
+				Some(mask) if mask & self.wild_colors == 0 =>
+					"*-".to_string(),
+				Some(mask) =>
+				{
+					let mask = mask & self.wild_colors;
+					let mut own = self.colors.iter()
+						.filter(|(_, color)| *color & mask != 0)
+						.collect::<Vec<_>>();
+					own.sort_by_key(|(_, color)| **color);
+					once('*').chain(own.into_iter().map(|(c, _)| *c))
+						.collect()
+				},
+				None => "*".to_string()
+			},
+			AnyStone::Toggle(t) if t.period == 1 && t.is_open() =>
+				"/".to_string(),
+			AnyStone::Toggle(t) if t.period == 1 => "+".to_string(),
+			AnyStone::Toggle(t) if t.is_open() => format!("/{}", t.period),
+			AnyStone::Toggle(t) => format!("+{}", t.period),
+			AnyStone::Bomb(_) => "o".to_string(),
+			AnyStone::Armored(a) =>
+				format!("{}{}", a.stone.rep, a.hits_remaining),
+			AnyStone::Rainbow(_) => "@".to_string()
+		}
+	}
+
+	/// Answer the legend term that denotes the specified value of the
+	/// specified property, i.e., the inverse of [`parse_legend`].
+	///
+	/// [`parse_legend`]: Board::parse_legend
+	fn term (&self, key: &PropertyKey, value: &PropertyValue) -> String
+	{
+		match (key, value)
+		{
+			// Wild stones consume their colors, so consult the board rather
+			// than the legend. Colors were allocated in the order that their
+			// characters were encountered, so sort the characters by color.
+			(PropertyKey::Wild, _) =>
+			{
+				let mut wild = self.colors.iter()
+					.filter(|(_, color)| *color & self.wild_colors != 0)
+					.collect::<Vec<_>>();
+				wild.sort_by_key(|(_, color)| **color);
+				wild.into_iter().map(|(c, _)| c).collect()
+			},
+			// The grid may have changed since the board was parsed, so
+			// checksum the grid as it stands now.
+			(PropertyKey::Checksum, _) => format!("{:x}", self.content_hash()),
+			(_, PropertyValue::Bool(b)) => b.to_string(),
+			(_, PropertyValue::U32(n)) => n.to_string(),
+			(_, PropertyValue::U64(n)) => n.to_string(),
+			(_, PropertyValue::String(s)) => s.clone()
+		}
+	}
+
+	/// Render the receiver in the specified style. The rendition comprises the
+	/// turn number, the coordinates of the highlighted stones (if any), and
+	/// the grid enclosed in a [heavy](BorderStyle::Heavy) box.
+	pub fn render (&self, style: RenderStyle) -> String
+	{
+		self.render_framed(style, BorderStyle::Heavy)
+	}
+
+	/// Render the receiver with ANSI colors, just like [`Display`], but enclose
+	/// the grid in a box of the specified style.
+	pub fn render_with_border (&self, border: BorderStyle) -> String
+	{
+		self.render_framed(RenderStyle::Ansi, border)
+	}
+
+	/// Render the receiver in the specified style, enclosing the grid in a box
+	/// of the specified style.
+	fn render_framed (&self, style: RenderStyle, border: BorderStyle) -> String
+	{
+		let frame = border.frame();
+		let mut out = format!("Turn #{}", self.turn.saturating_add(1));
+		let points = self.highlight.iter()
+			.map(|&p| format!("{:?}", self.display_point(p)))
+			.collect::<Vec<_>>()
+			.join(" ");
+		match (self.highlight.is_empty(), style)
+		{
+			(false, RenderStyle::Ansi) => out.push_str(
+				&format!(": \u{1b}[38;5;15m{}\u{1b}[0m", points)),
+			(false, RenderStyle::Plain) =>
+				out.push_str(&format!(": {}", points)),
+			(true, _) => {}
+		}
+		out.push('\n');
+		// Write the top of the box.
+		let span = ((self.width << 1) - 1) as usize;
+		if let Some([nw, ne, _, _, _, h]) = frame
+		{
+			let rule = h.to_string().repeat(span);
+			out.push_str(&format!("{}{}{}\n", nw, rule, ne));
+		}
+		// Write the contents of the box. The separators flank the cells, so a
+		// plain highlight just replaces the separators on either side; a run of
+		// adjacent highlighted cells is bracketed as a whole. Without a frame,
+		// there are no separators at the edges unless highlighted.
+		for row in 0..self.height
+		{
+			let edge = frame.map(|[_, _, _, _, v, _]| v);
+			let mut separators = vec![Some(' '); self.width as usize + 1];
+			separators[0] = edge;
+			separators[self.width as usize] = edge;
+			let mut cells = Vec::with_capacity(self.width as usize);
+			for column in 0..self.width
+			{
+				let index = self.index((column, row));
+				let stone = self.grid[index].for_board(self);
+				let highlighted = self.highlight.contains(&(column, row));
+				cells.push(match style
+				{
+					RenderStyle::Ansi =>
+					{
+						let highlight =
+							if highlighted { "\u{1b}[48;5;231m" } else { "" };
+						format!(
+							"{}{}\u{1b}[0m",
+							highlight,
+							self.glyph(&stone, style))
+					},
+					RenderStyle::Plain =>
+					{
+						if highlighted
+						{
+							let left = &mut separators[column as usize];
+							*left = match left
+							{
+								Some(']') => Some(' '),
+								_ => Some('[')
+							};
+							separators[column as usize + 1] = Some(']');
+						}
+						self.glyph(&stone, style)
+					}
+				});
+			}
+			for (separator, cell) in separators.iter().zip(&cells)
+			{
+				out.extend(separator);
+				out.push_str(cell);
+			}
+			out.extend(separators[self.width as usize]);
+			out.push('\n');
+		}
+		// Write the bottom of the box.
+		if let Some([_, _, se, sw, _, h]) = frame
+		{
+			let rule = h.to_string().repeat(span);
+			out.push_str(&format!("{}{}{}\n", sw, rule, se));
+		}
+		out
+	}
+
+	/// Answer the glyph that represents the specified [stone] in the specified
+	/// style, honoring any display properties from the legend.
+	///
+	/// [stone]: AnyStone
+	fn glyph (&self, stone: &AnyStone, style: RenderStyle) -> String
+	{
+		match (stone, style)
+		{
+			(AnyStone::None(_), RenderStyle::Plain) => " ".to_string(),
+			(AnyStone::Ordinary(o), RenderStyle::Plain) => o.rep.to_string(),
+			(AnyStone::Survivor(_), RenderStyle::Plain) => "#".to_string(),
+			(AnyStone::Wild(_), RenderStyle::Plain) => "*".to_string(),
+			(AnyStone::Toggle(t), RenderStyle::Plain) if t.is_open() =>
+				"/".to_string(),
+			(AnyStone::Toggle(_), RenderStyle::Plain) => "+".to_string(),
+			(AnyStone::Bomb(_), RenderStyle::Plain) => "o".to_string(),
+			(AnyStone::Rainbow(_), RenderStyle::Plain) => "@".to_string(),
+			(AnyStone::Armored(a), RenderStyle::Plain) => a.to_string(),
+			(AnyStone::Armored(a), RenderStyle::Ansi) =>
+			{
+				match self.properties.get(&PropertyKey::Display(a.stone.rep))
+				{
+					Some(PropertyValue::String(display)) => format!(
+						"\u{1b}[38;5;{}m{}", display, a.hits_remaining),
+					_ => a.to_string()
+				}
+			},
+			(AnyStone::Ordinary(o), RenderStyle::Ansi) =>
+			{
+				match self.properties.get(&PropertyKey::Display(o.rep))
+				{
+					Some(PropertyValue::String(display)) =>
+						format!("\u{1b}[38;5;{}m{}", display, o.rep),
+					_ => o.to_string()
+				}
+			},
+			(s, RenderStyle::Ansi) => s.to_string()
+		}
+	}
+
+	/// Render the initial state of the receiver with every cell cleared by the
+	/// specified solution labeled by the 1-based number of the move that
+	/// cleared it. Explicitly removed stones are labeled in white. Stones
+	/// swept away as a consequence of some move, i.e., [survivors] and stones
+	/// caught in the blast of a [bomb], are labeled in gray and prefixed by
+	/// `#`. Cells that the solution never clears show their stones as usual.
+	/// On a board with [gravity], each stone is labeled in its original cell,
+	/// wherever it has fallen by the time that it is cleared. An [armored
+	/// stone] is labeled by the move that strikes its final hit.
+	///
+	/// [survivors]: SurvivorStone
+	/// [bomb]: BombStone
+	/// [gravity]: GravityRule::Down
+	/// [armored stone]: ArmoredStone
+	pub fn render_solution (&self, moves: &[Point]) -> String
+	{
+		let labels = self.solution_labels(moves);
+		// Every cell is as wide as the widest label.
+		let cell = labels.iter()
+			.flatten()
+			.map(|label| label.to_string().chars().count())
+			.max()
+			.unwrap_or(1);
+		let rule = H_LINE.to_string().repeat(
+			(self.width as usize * (cell + 1)) - 1);
+		let mut out = format!("{}{}{}\n", NW_CORNER, rule, NE_CORNER);
+		for row in 0..self.height
+		{
+			out.push(V_LINE);
+			for column in 0..self.width
+			{
+				let index = self.index((column, row));
+				// Every glyph occupies exactly one column.
+				let (text, width) = match labels[index]
+				{
+					Some(label @ SolutionLabel::Move(_)) =>
+					{
+						let label = label.to_string();
+						let width = label.chars().count();
+						(format!("\u{1b}[38;5;15m{}", label), width)
+					},
+					Some(label @ SolutionLabel::Cascade(_)) =>
+					{
+						let label = label.to_string();
+						let width = label.chars().count();
+						(format!("\u{1b}[38;5;8m{}", label), width)
+					},
+					None => (
+						self.glyph(
+							&self.grid[index].for_board(self),
+							RenderStyle::Ansi),
+						1)
+				};
+				out.push_str(&" ".repeat(cell - width));
+				out.push_str(&text);
+				out.push_str("\u{1b}[0m");
+				if column != self.width - 1 { out.push(' '); }
+			}
+			out.push(V_LINE);
+			out.push('\n');
+		}
+		out.push_str(&format!("{}{}{}\n", SW_CORNER, rule, SE_CORNER));
+		out
+	}
+
+	/// Replay the specified solution on a scratch copy of the receiver. Answer
+	/// the [label](SolutionLabel) of every cell, noting which move clears it,
+	/// or `None` if the solution never clears it.
+	fn solution_labels (&self, moves: &[Point]) -> Vec<Option<SolutionLabel>>
+	{
+		// Stones may fall, so track the original cell of the stone that
+		// occupies each cell of the scratch board. A stone is only ever
+		// cleared once, so the first move that sweeps it away claims it.
+		let mut board = self.clone();
+		let mut labels = vec![None::<SolutionLabel>; self.grid.len()];
+		let mut origins = (0..self.grid.len()).map(Some).collect::<Vec<_>>();
+		for (n, &p) in moves.iter().enumerate()
+		{
+			let number = n as u32 + 1;
+			let (blasted, falls, survivors) = board.force_removal(p);
+			let mut clear = |origins: &mut Vec<Option<usize>>, q, label|
+			{
+				if let Some(origin) = origins[self.index(q)].take()
+				{
+					labels[origin].get_or_insert(label);
+				}
+			};
+			// A struck armored stone is labeled by its final hit.
+			if matches!(board.grid[self.index(p)], AnyStone::None(_))
+			{
+				clear(&mut origins, p, SolutionLabel::Move(number));
+			}
+			for q in blasted
+			{
+				clear(&mut origins, q, SolutionLabel::Cascade(number));
+			}
+			for (from, to) in falls
+			{
+				origins[self.index(to)] = origins[self.index(from)].take();
+			}
+			for q in survivors
+			{
+				clear(&mut origins, q, SolutionLabel::Cascade(number));
+			}
+		}
+		labels
+	}
+}
+
+const NW_CORNER: char = '\u{250F}';
+const NE_CORNER: char = '\u{2513}';
+const SE_CORNER: char = '\u{251B}';
+const SW_CORNER: char = '\u{2517}';
+const V_LINE: char = '\u{2503}';
+const H_LINE: char = '\u{2501}';
+
+/// The label of a cell cleared by a solution, as [rendered] for the whole
+/// solution at once.
+///
+/// [rendered]: Board::render_solution
+#[derive(Copy, Clone, Debug)]
+enum SolutionLabel
+{
+	/// The cell was cleared by the 1-based move.
+	Move (u32),
+
+	/// The cell held a [survivor](SurvivorStone), or a stone caught in the
+	/// blast of a [bomb](BombStone), that was swept away by the 1-based move.
+	Cascade (u32)
+}
+
+impl Display for SolutionLabel
+{
+	fn fmt (&self, f: &mut Formatter) -> Result
+	{
+		match self
+		{
+			SolutionLabel::Move(n) => write!(f, "{}", n),
+			SolutionLabel::Cascade(n) => write!(f, "#{}", n)
+		}
+	}
+}
+
+/// The record of a single [removal](Board::remove), which suffices to reverse
+/// it or, by way of its point and color, to replay it.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Removal
+{
+	/// The point of the removed stone.
+	p: Point,
+
+	/// The color asserted for the removed stone.
+	color: u64,
+
+	/// The removed [stone](AnyStone).
+	stone: AnyStone,
+
+	/// The [turn](Board::turn) prior to the removal.
+	turn: u32,
+
+	/// The wild color consumed by the removal, or `0` if none.
+	wild_color: u64,
+
+	/// The [locked color](Board::locked_color) prior to the removal.
+	locked_color: u64,
+
+	/// The points and stones caught in the blast, if the removed stone was a
+	/// [bomb](BombStone), in the order of their removal.
+	blasted: Vec<(Point, AnyStone)>,
+
+	/// The source and destination points of the stones that fell into vacated
+	/// cells, if the board has [gravity](GravityRule::Down), in the order
+	/// that they fell.
+	falls: Vec<(Point, Point)>,
+
+	/// The [survivors](SurvivorStone) swept away by the removal.
+	survivors: Vec<Point>
+}
+
+impl PartialEq for Board
+{
+	fn eq (&self, other: &Self) -> bool
+	{
+		let display = |board: &Board| board.properties.iter()
+			.filter(|(key, _)| matches!(key, PropertyKey::Display(_)))
+			.map(|(key, value)| (key.clone(), value.clone()))
+			.collect::<HashMap<_, _>>();
+		self.turn == other.turn
+			&& self.wild_colors == other.wild_colors
+			&& self.lock_colors == other.lock_colors
+			&& self.locked_color == other.locked_color
+			&& self.survivor_rule == other.survivor_rule
+			&& self.gravity == other.gravity
+			&& self.access == other.access
+			&& self.width == other.width
+			&& self.height == other.height
+			&& self.grid == other.grid
+			&& self.expected_solution == other.expected_solution
+			&& display(self) == display(other)
+	}
+}
+
+impl Eq for Board {}
+
+impl Display for Board
+{
+	/// We use ANSI colors and Unicode box characters to draw pretty
+	/// representations of the board, so VT 100 or similar is required for best
+	/// effect. Use [`render`](Board::render) to choose another style.
+	fn fmt (&self, f: &mut Formatter) -> Result
+	{
+		write!(f, "{}", self.render(RenderStyle::Ansi))
+	}
+}
+
+/// How to [render](Board::render) a board.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub enum RenderStyle
+{
+	/// Use ANSI colors and Unicode box characters, as for a VT 100 or
+	/// similar terminal. The highlighted stones, if any, are drawn against a
+	/// white background.
+	#[default]
+	Ansi,
+
+	/// Use Unicode box characters, but no escape sequences at all, as for a
+	/// file or a terminal without color support. The highlighted stones, if
+	/// any, are enclosed in square brackets; adjacent highlighted stones of a
+	/// row are enclosed together.
+	Plain
+}
+
+/// The box drawn around the grid of a [rendered](Board::render_with_border)
+/// board.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub enum BorderStyle
+{
+	/// Use heavy Unicode box characters, e.g., `┏━┓`.
+	#[default]
+	Heavy,
+
+	/// Use light Unicode box characters, e.g., `┌─┐`, which more fonts render
+	/// well.
+	Light,
+
+	/// Use only ASCII characters: `+` for corners, `-` for horizontal lines,
+	/// and `|` for vertical lines.
+	Ascii,
+
+	/// Draw no box at all.
+	None
+}
+
+impl BorderStyle
+{
+	/// Answer the characters of the box: the northwest, northeast, southeast,
+	/// and southwest corners, then the vertical and horizontal lines. Answer
+	/// `None` if there is no box.
+	fn frame (self) -> Option<[char; 6]>
+	{
+		match self
+		{
+			BorderStyle::Heavy => Some(
+				[NW_CORNER, NE_CORNER, SE_CORNER, SW_CORNER, V_LINE, H_LINE]),
+			BorderStyle::Light => Some(
+				['\u{250C}', '\u{2510}', '\u{2518}', '\u{2514}', '\u{2502}',
+					'\u{2500}']),
+			BorderStyle::Ascii => Some(['+', '+', '+', '+', '|', '-']),
+			BorderStyle::None => None
+		}
+	}
+}
+
+/// The origin of the coordinates that a [board](Board) displays, e.g., in the
+/// caption of a [rendition](Board::render). Internally, the origin `(0,0)` is
+/// always the uppermost leftmost grid cell; only the displayed coordinates are
+/// affected.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CoordinateOrigin
+{
+	/// The uppermost leftmost cell is `(0,0)`, and rows count downward.
+	#[default]
+	TopLeft0,
+
+	/// The lowermost leftmost cell is `(0,0)`, and rows count upward.
+	BottomLeft0,
+
+	/// The uppermost leftmost cell is `(1,1)`, and rows count downward.
+	TopLeft1
+}
+
+impl CoordinateOrigin
+{
+	/// Answer the displayed coordinates of the specified point of a board of
+	/// the specified height.
+	pub fn convert (self, (column, row): Point, height: u32) -> Point
+	{
+		match self
+		{
+			CoordinateOrigin::TopLeft0 => (column, row),
+			CoordinateOrigin::BottomLeft0 => (column, height - 1 - row),
+			CoordinateOrigin::TopLeft1 => (column + 1, row + 1)
+		}
+	}
+}
+
+impl FromStr for CoordinateOrigin
+{
+	type Err = ();
+
+	fn from_str (s: &str) -> result::Result<Self, Self::Err>
+	{
+		match s
+		{
+			"topleft0" => Ok(CoordinateOrigin::TopLeft0),
+			"bottomleft0" => Ok(CoordinateOrigin::BottomLeft0),
+			"topleft1" => Ok(CoordinateOrigin::TopLeft1),
+			_ => Err(())
+		}
+	}
+}
+
+impl Display for CoordinateOrigin
+{
+	fn fmt (&self, f: &mut Formatter) -> Result
+	{
+		match self
+		{
+			CoordinateOrigin::TopLeft0 => write!(f, "topleft0"),
+			CoordinateOrigin::BottomLeft0 => write!(f, "bottomleft0"),
+			CoordinateOrigin::TopLeft1 => write!(f, "topleft1")
+		}
+	}
+}
+
+/******************************************************************************
+ *                             Bitboard support.                              *
+ ******************************************************************************/
+
+/// The maximum number of cells that [bitboards] can represent.
+///
+/// [bitboards]: Bitboards
+const BITBOARD_CELLS: usize = u128::BITS as usize;
+
+/// A derived representation of the grid of a [board] as bitboards, i.e., as
+/// masks with one bit per cell, in row-major order, such that the bit of the
+/// cell at index `i` of the grid is `1 << i`. Each mask selects the cells that
+/// hold a particular kind of [stone], so that questions about many cells at
+/// once, e.g., "how many stones of this color remain?" or "does this row
+/// hold any removable stones?", can be answered with a few bitwise
+/// operations rather than by visiting every cell. Only boards with at most
+/// [`BITBOARD_CELLS`] cells have bitboards.
+///
+/// [board]: Board
+/// [stone]: AnyStone
+#[derive(Clone, Debug)]
+pub(crate) struct Bitboards
+{
+	/// The width of the board, i.e., the row stride of every mask.
+	width: u32,
+
+	/// The height of the board.
+	height: u32,
+
+	/// The cells of the [ordinary] and [armored] stones of each color, as
+	/// pairs of the color and its cells, in order of first occurrence.
+	///
+	/// [ordinary]: OrdinaryStone
+	/// [armored]: ArmoredStone
+	pub(crate) colors: Vec<(u64, u128)>,
+
+	/// The cells of the [armored stones](ArmoredStone).
+	pub(crate) armored: u128,
+
+	/// The cells of the [wild stones](WildStone).
+	pub(crate) wilds: u128,
+
+	/// The cells of the [survivors](SurvivorStone).
+	pub(crate) survivors: u128,
+
+	/// The cells of the [bombs](BombStone).
+	pub(crate) bombs: u128,
+
+	/// The cells of the [rainbow stones](RainbowStone).
+	pub(crate) rainbows: u128,
+
+	/// The cells of the removable [stones](AnyStone).
+	pub(crate) removable: u128
+}
+
+impl Bitboards
+{
+	/// Derive the bitboards of the specified grid, which has the specified
+	/// width. Answer `None` if the grid has too many cells.
+	fn new (width: u32, grid: &[AnyStone]) -> Option<Bitboards>
+	{
+		if grid.len() > BITBOARD_CELLS
+		{
+			return None
+		}
+		let mut bitboards = Bitboards {
+			width,
+			height: grid.len() as u32 / width,
+			colors: vec![],
+			armored: 0,
+			wilds: 0,
+			survivors: 0,
+			bombs: 0,
+			rainbows: 0,
+			removable: 0
+		};
+		for (index, stone) in grid.iter().enumerate()
+		{
+			bitboards.set(index, &AnyStone::None(NoStone), stone);
+		}
+		Some(bitboards)
+	}
+
+	/// Update the receiver to reflect the replacement of the old [stone] at
+	/// the specified index of the grid by the new one.
+	///
+	/// [stone]: AnyStone
+	fn set (&mut self, index: usize, old: &AnyStone, new: &AnyStone)
+	{
+		let bit = 1u128 << index;
+		for (stone, on) in [(old, false), (new, true)]
+		{
+			let update = |mask: &mut u128|
+				if on { *mask |= bit } else { *mask &= !bit };
+			let color = match stone
+			{
+				AnyStone::Ordinary(o) => Some(o.color),
+				AnyStone::Armored(a) => Some(a.color()),
+				_ => None
+			};
+			if let Some(color) = color
+			{
+				match self.colors.iter_mut().find(|(c, _)| *c == color)
+				{
+					Some((_, mask)) => update(mask),
+					None => self.colors.push((color, bit))
+				}
+			}
+			match stone
+			{
+				AnyStone::Armored(_) => update(&mut self.armored),
+				AnyStone::Wild(_) => update(&mut self.wilds),
+				AnyStone::Survivor(_) => update(&mut self.survivors),
+				AnyStone::Bomb(_) => update(&mut self.bombs),
+				AnyStone::Rainbow(_) => update(&mut self.rainbows),
+				_ => {}
+			}
+			if stone.is_removable()
+			{
+				update(&mut self.removable);
+			}
+		}
+	}
+
+	/// Answer the mask of the cells of the specified row.
+	pub(crate) fn row (&self, row: u32) -> u128
+	{
+		(u128::MAX >> (u128::BITS - self.width)) << (row * self.width)
+	}
+
+	/// Answer the mask of the cells of the specified column.
+	pub(crate) fn column (&self, column: u32) -> u128
+	{
+		(0..self.height).fold(0, |mask, row|
+			mask | 1 << (row * self.width + column))
+	}
+
+	/// Answer the points of the cells of the specified mask, in row-major
+	/// order.
+	pub(crate) fn points (&self, mut cells: u128) -> impl Iterator<Item=Point>
+	{
+		let width = self.width;
+		from_fn(move ||
+		{
+			if cells == 0
+			{
+				return None
+			}
+			let index = cells.trailing_zeros();
+			cells &= cells - 1;
+			Some((index % width, index / width))
+		})
+	}
+
+	/// Answer the mask of the cells of the specified color, which is empty if
+	/// no stone has ever had the color.
+	pub(crate) fn color (&self, color: u64) -> u128
+	{
+		self.colors.iter()
+			.find(|(c, _)| *c == color)
+			.map_or(0, |&(_, mask)| mask)
+	}
+}
+
+/******************************************************************************
+ *                                SVG support.                                *
+ ******************************************************************************/
+
+/// The size, in pixels, of a cell of an SVG rendition.
+const SVG_CELL: u32 = 40;
+
+/// The inset, in pixels, of a stone within its cell.
+const SVG_INSET: u32 = 2;
+
+/// The fill of [survivors](SurvivorStone).
+const SVG_SURVIVOR: &str = "#808080";
+
+/// The fill of closed [toggles](ToggleStone). Open toggles are outlined in
+/// this color instead.
+const SVG_TOGGLE: &str = "#444444";
+
+/// The fill of [bombs](BombStone).
+const SVG_BOMB: &str = "#303030";
+
+/// The outline of [armored stones](ArmoredStone), which are otherwise filled
+/// like ordinary stones.
+const SVG_ARMOR: &str = "#c0c0c0";
+
+/// The fill of [ordinary stones](OrdinaryStone) without display properties.
+const SVG_DEFAULT: &str = "#c0c0c0";
+
+/// The stops of the gradient that fills [wild stones](WildStone).
+const SVG_WILD_STOPS: [&str; 5] =
+	["#ff5f5f", "#ffd75f", "#5fd75f", "#5f87ff", "#d75fff"];
+
+impl Board
+{
+	/// Answer a standalone SVG image of the receiver, comprising one rounded
+	/// rectangle per stone. Ordinary stones are filled with the colors given
+	/// by the display properties of the legend and labeled by their
+	/// characters; [survivors] are gray, [wild stones] are filled with a
+	/// gradient, [toggles] are outlined when open and filled when closed, and
+	/// [bombs] are black and labeled by `o`.
+	///
+	/// [survivors]: SurvivorStone
+	/// [wild stones]: WildStone
+	/// [toggles]: ToggleStone
+	/// [bombs]: BombStone
+	pub fn to_svg (&self) -> String
+	{
+		self.svg(&vec![None; self.grid.len()])
+	}
+
+	/// Answer a standalone SVG image of the initial state of the receiver,
+	/// just like [`to_svg`], but with every stone cleared by the specified
+	/// solution labeled by the 1-based number of the move that cleared it.
+	/// Stones swept away as a consequence of some move, i.e., [survivors] and
+	/// stones caught in the blast of a [bomb], are labeled by that move,
+	/// prefixed by `#`.
+	///
+	/// [`to_svg`]: Board::to_svg
+	/// [survivors]: SurvivorStone
+	/// [bomb]: BombStone
+	pub fn solution_to_svg (&self, moves: &[Point]) -> String
+	{
+		self.svg(&self.solution_labels(moves))
+	}
+
+	/// Answer a standalone SVG image of the receiver, labeling the stones by
+	/// the specified solution labels where present.
+	fn svg (&self, labels: &[Option<SolutionLabel>]) -> String
+	{
+		let width = self.width * SVG_CELL;
+		let height = self.height * SVG_CELL;
+		let mut out = String::new();
+		out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+		out.push_str(&format!(
+			"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" \
+				height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+			width, height, width, height));
+		out.push_str(
+			"<defs><linearGradient id=\"wild\" x1=\"0\" y1=\"0\" x2=\"1\" \
+				y2=\"1\">");
+		for (n, stop) in SVG_WILD_STOPS.iter().enumerate()
+		{
+			out.push_str(&format!(
+				"<stop offset=\"{}\" stop-color=\"{}\"/>",
+				n as f64 / (SVG_WILD_STOPS.len() - 1) as f64,
+				stop));
+		}
+		out.push_str("</linearGradient></defs>\n");
+		for (index, stone) in self.grid.iter().enumerate()
+		{
+			let (column, row) = self.point(index);
+			let (fill, stroke, text) = match stone.for_board(self)
+			{
+				AnyStone::None(_) => continue,
+				AnyStone::Ordinary(o) =>
+				{
+					let fill = self.svg_fill(o.rep);
+					(fill, "none".to_string(), Some(o.rep.to_string()))
+				},
+				AnyStone::Survivor(_) =>
+					(SVG_SURVIVOR.to_string(), "none".to_string(), None),
+				AnyStone::Wild(_) =>
+					("url(#wild)".to_string(), "none".to_string(), None),
+				AnyStone::Toggle(t) if t.is_open() =>
+					("none".to_string(), SVG_TOGGLE.to_string(), None),
+				AnyStone::Toggle(_) =>
+					(SVG_TOGGLE.to_string(), SVG_TOGGLE.to_string(), None),
+				AnyStone::Bomb(_) => (
+					SVG_BOMB.to_string(),
+					"none".to_string(),
+					Some("o".to_string())),
+				AnyStone::Armored(a) => (
+					self.svg_fill(a.stone.rep),
+					SVG_ARMOR.to_string(),
+					Some(format!("{}{}", a.stone.rep, a.hits_remaining))),
+				AnyStone::Rainbow(_) => (
+					"url(#wild)".to_string(),
+					"none".to_string(),
+					Some("@".to_string()))
+			};
+			out.push_str(&format!(
+				"<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"6\" \
+					fill=\"{}\" stroke=\"{}\" stroke-width=\"2\"/>\n",
+				column * SVG_CELL + SVG_INSET,
+				row * SVG_CELL + SVG_INSET,
+				SVG_CELL - 2 * SVG_INSET,
+				SVG_CELL - 2 * SVG_INSET,
+				fill,
+				stroke));
+			// A solution label supersedes the character of an ordinary stone.
+			let (text, ink) = match labels[index]
+			{
+				Some(label) => (Some(label.to_string()), "#ffffff"),
+				None if fill.starts_with('#') && svg_is_light(&fill) =>
+					(text, "#000000"),
+				None => (text, "#ffffff")
+			};
+			if let Some(text) = text
+			{
+				out.push_str(&format!(
+					"<text x=\"{}\" y=\"{}\" fill=\"{}\" stroke=\"#000000\" \
+						stroke-width=\"{}\" font-family=\"monospace\" \
+						font-size=\"18\" font-weight=\"bold\" \
+						text-anchor=\"middle\" \
+						dominant-baseline=\"central\">{}</text>\n",
+					column * SVG_CELL + SVG_CELL / 2,
+					row * SVG_CELL + SVG_CELL / 2,
+					ink,
+					if labels[index].is_some() { "0.75" } else { "0" },
+					xml_escape(&text)));
+			}
+		}
+		out.push_str("</svg>\n");
+		out
+	}
+
+	/// Answer the SVG fill for the ordinary stone represented by the
+	/// specified character, as given by its display property.
+	fn svg_fill (&self, rep: char) -> String
+	{
+		match self.properties.get(&PropertyKey::Display(rep))
+		{
+			Some(PropertyValue::String(display)) => match display.parse::<u8>()
+			{
+				Ok(index) =>
+				{
+					let (r, g, b) = ansi_to_rgb(index);
+					format!("#{:02x}{:02x}{:02x}", r, g, b)
+				},
+				Err(_) => SVG_DEFAULT.to_string()
+			},
+			_ => SVG_DEFAULT.to_string()
+		}
+	}
+}
+
+/// Answer the RGB components of the specified index into the ANSI 256-color
+/// palette: 16 system colors, then a 6×6×6 color cube, then 24 grays.
+fn ansi_to_rgb (index: u8) -> (u8, u8, u8)
+{
+	const SYSTEM: [(u8, u8, u8); 16] = [
+		(0x00, 0x00, 0x00), (0x80, 0x00, 0x00),
+		(0x00, 0x80, 0x00), (0x80, 0x80, 0x00),
+		(0x00, 0x00, 0x80), (0x80, 0x00, 0x80),
+		(0x00, 0x80, 0x80), (0xc0, 0xc0, 0xc0),
+		(0x80, 0x80, 0x80), (0xff, 0x00, 0x00),
+		(0x00, 0xff, 0x00), (0xff, 0xff, 0x00),
+		(0x00, 0x00, 0xff), (0xff, 0x00, 0xff),
+		(0x00, 0xff, 0xff), (0xff, 0xff, 0xff)
+	];
+	const LEVELS: [u8; 6] = [0x00, 0x5f, 0x87, 0xaf, 0xd7, 0xff];
+	match index
+	{
+		0..=15 => SYSTEM[index as usize],
+		16..=231 =>
+		{
+			let cube = (index - 16) as usize;
+			(LEVELS[cube / 36], LEVELS[(cube / 6) % 6], LEVELS[cube % 6])
+		},
+		_ =>
+		{
+			let gray = 8 + (index - 232) * 10;
+			(gray, gray, gray)
+		}
+	}
+}
+
+/// The names of colors that a display property may give instead of an index
+/// into the ANSI 256-color palette, paired with those indices.
+const NAMED_COLORS: [(&str, u8); 14] = [
+	("black", 0),
+	("gray", 8),
+	("red", 9),
+	("green", 10),
+	("yellow", 11),
+	("blue", 12),
+	("magenta", 13),
+	("cyan", 14),
+	("white", 15),
+	("brown", 94),
+	("purple", 93),
+	("pink", 213),
+	("orange", 208),
+	("lime", 118)
+];
+
+/// Answer the index into the ANSI 256-color palette denoted by the specified
+/// term of a display property: either an index, a [name](NAMED_COLORS), or a
+/// `#rrggbb` color, which is approximated by the nearest color in the palette.
+/// Answer `None` if the term is none of these.
+fn palette_index (term: &str) -> Option<u8>
+{
+	if let Ok(index) = term.parse::<u8>()
+	{
+		return Some(index)
+	}
+	if let Some(&(_, index)) = NAMED_COLORS.iter()
+		.find(|(name, _)| name.eq_ignore_ascii_case(term))
+	{
+		return Some(index)
+	}
+	let hex = term.strip_prefix('#')
+		.filter(|hex|
+			hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))?;
+	let component = |range| i32::from_str_radix(&hex[range], 16).ok();
+	let (r, g, b) = (component(0..2)?, component(2..4)?, component(4..6)?);
+	(0..=u8::MAX).min_by_key(|&index|
+	{
+		let (r1, g1, b1) = ansi_to_rgb(index);
+		let (dr, dg, db) = (r1 as i32 - r, g1 as i32 - g, b1 as i32 - b);
+		dr * dr + dg * dg + db * db
+	})
+}
+
+/// Answer `true` if the specified `#rrggbb` color is light enough that black
+/// text is more legible atop it than white text, `false` otherwise.
+fn svg_is_light (color: &str) -> bool
+{
+	let component = |range| u8::from_str_radix(&color[range], 16)
+		.map(|c| c as u32)
+		.unwrap_or(0);
+	let (r, g, b) = (component(1..3), component(3..5), component(5..7));
+	r * 299 + g * 587 + b * 114 > 150_000
+}
+
+/// Answer the specified text with the XML metacharacters escaped.
+fn xml_escape (text: &str) -> String
+{
+	text.chars().fold(String::new(), |mut out, c|
+	{
+		match c
+		{
+			'<' => out.push_str("&lt;"),
+			'>' => out.push_str("&gt;"),
+			'&' => out.push_str("&amp;"),
+			'"' => out.push_str("&quot;"),
+			'\'' => out.push_str("&apos;"),
+			c => out.push(c)
+		}
+		out
+	})
+}
+
+/******************************************************************************
+ *                             Property support.                              *
+ ******************************************************************************/
+
+pub type PropertyMap = HashMap<PropertyKey, PropertyValue>;
+
+/// A board property key.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PropertyKey
+{
+	/// The width, in stones, i.e., the row stride.
+	Width,
+
+	/// The height, in stones, i.e., the number of rows. If specified, then
+	/// the grid must contain exactly this many rows.
+	Height,
+
+	/// The specification of colors for [wild stones](WildStone).
+	Wild,
+
+	/// A color is locked once completed, and cannot be played until another
+	/// color has been played. Either every color is subject to the lock, or
+	/// just the colors whose characters are given.
+	ColorLock,
+
+	/// The width is established by the first row of the grid, and every row
+	/// must contain exactly that many stones.
+	StrictWidth,
+
+	/// A digit that follows the code of an ordinary stone gives the hits of
+	/// an [armored stone](ArmoredStone), rather than being a stone of its
+	/// own.
+	Armor,
+
+	/// Every property key of the legend must be known, so that a misspelled
+	/// key is reported rather than ignored.
+	Strict,
+
+	/// The [rule](SurvivorRule) that determines when
+	/// [survivors](SurvivorStone) disappear.
+	SurvivorRule,
+
+	/// The [rule](GravityRule) that determines whether stones fall into
+	/// vacated cells.
+	Gravity,
+
+	/// The [side](AccessDirection) of the board from which stones are
+	/// accessed.
+	Access,
+
+	/// The version of the text format, which must be [`FORMAT_VERSION`].
+	Version,
+
+	/// The [content hash](Board::content_hash) of the grid, in hexadecimal.
+	/// The grid must match it.
+	Checksum,
+
+	/// The specification of display properties for a stone, i.e., the index
+	/// of its color in the ANSI 256-color palette. The legend may give the
+	/// color by name or as `#rrggbb` instead, but the index is retained.
+	Display (char),
+
+	/// An unknown property.
+	Unknown (String)
+}
+
+impl Display for PropertyKey
+{
+	/// Write the key as it appears in a legend.
+	fn fmt (&self, f: &mut Formatter) -> Result
+	{
+		match self
+		{
+			PropertyKey::Width => write!(f, "width"),
+			PropertyKey::Height => write!(f, "height"),
+			PropertyKey::Wild => write!(f, "wild"),
+			PropertyKey::ColorLock => write!(f, "colorlock"),
+			PropertyKey::StrictWidth => write!(f, "strictwidth"),
+			PropertyKey::Armor => write!(f, "armor"),
+			PropertyKey::Strict => write!(f, "strict"),
+			PropertyKey::SurvivorRule => write!(f, "survivorrule"),
+			PropertyKey::Gravity => write!(f, "gravity"),
+			PropertyKey::Access => write!(f, "access"),
+			PropertyKey::Version => write!(f, "version"),
+			PropertyKey::Checksum => write!(f, "checksum"),
+			PropertyKey::Display(c) => write!(f, "{}", c),
+			PropertyKey::Unknown(key) => write!(f, "{}", key)
+		}
+	}
+}
+
+/// A board property value.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PropertyValue
+{
+	/// An arbitrary `bool`.
+	Bool (bool),
+
+	/// An arbitrary `u32`.
+	U32 (u32),
+
+	/// An arbitrary `u64`, e.g., a color mask.
+	U64 (u64),
+
+	/// Arbitrary text.
+	String (String)
+}
+
+/******************************************************************************
+ *                           Construction support.                            *
+ ******************************************************************************/
+
+/// A builder of [boards](Board) in code, as an alternative to [parsing] the
+/// text format. The builder begins with an empty grid, and each [stone] must be
+/// [set](BoardBuilder::set) explicitly:
+///
+/// ```
+/// # use tumblesolve::*;
+/// let red = OrdinaryStone::new('r', 1);
+/// let board = BoardBuilder::new(3, 1)
+///     .set((0, 0), AnyStone::Ordinary(red))
+///     .set((1, 0), AnyStone::Ordinary(red))
+///     .set((2, 0), AnyStone::Wild(WildStone::new()))
+///     .wild_colors(1)
+///     .build()
+///     .unwrap();
+/// ```
+///
+/// [parsing]: Board::parse
+/// [stone]: AnyStone
+#[derive(Clone, Debug)]
+pub struct BoardBuilder
+{
+	/// The width of the board, in stones.
+	width: u32,
+
+	/// The height of the board, in stones.
+	height: u32,
+
+	/// The [stones](AnyStone), in row-major order.
+	grid: Vec<AnyStone>,
+
+	/// The bitwise OR of the colors of the [wild stones](WildStone).
+	wild_colors: u64,
+
+	/// Whether the board is color locked.
+	color_locked: bool,
+
+	/// The characters that [name](BoardBuilder::name_color) colors, in
+	/// addition to those of the ordinary stones.
+	names: Vec<(char, u64)>,
+
+	/// The [display colors](BoardBuilder::display) of the characters.
+	display: Vec<(char, u8)>
+}
+
+impl BoardBuilder
+{
+	/// Answer a builder of a board with the specified dimensions, whose every
+	/// cell is [empty](NoStone).
+	pub fn new (width: u32, height: u32) -> BoardBuilder
+	{
+		BoardBuilder {
+			width,
+			height,
+			grid: vec![AnyStone::None(NoStone); (width * height) as usize],
+			wild_colors: 0,
+			color_locked: false,
+			names: vec![],
+			display: vec![]
+		}
+	}
+
+	/// Place the specified [stone](AnyStone) at the specified point.
+	///
+	/// # Panics
+	///
+	/// If the point lies outside the board.
+	pub fn set (&mut self, p: Point, stone: AnyStone) -> &mut BoardBuilder
+	{
+		assert!(
+			p.0 < self.width && p.1 < self.height,
+			"{:?} lies outside a {}×{} board",
+			p,
+			self.width,
+			self.height);
+		self.grid[(p.1 * self.width + p.0) as usize] = stone;
+		self
+	}
+
+	/// Set the colors of the [wild stones](WildStone), as the bitwise OR of
+	/// the masks of their [colors](OrdinaryStone::color). There must be
+	/// exactly as many wild stones as colors.
+	pub fn wild_colors (&mut self, mask: u64) -> &mut BoardBuilder
+	{
+		self.wild_colors = mask;
+		self
+	}
+
+	/// Set whether the board is [color locked](Board::color_locked).
+	pub fn color_lock (&mut self, locked: bool) -> &mut BoardBuilder
+	{
+		self.color_locked = locked;
+		self
+	}
+
+	/// Name the specified color by the specified character, as though an
+	/// [ordinary stone](OrdinaryStone) with that color and character were
+	/// present. This is only necessary for a wild color that no ordinary stone
+	/// shares, which could not otherwise be [written](Board::to_tsb).
+	pub fn name_color (&mut self, rep: char, color: u64) -> &mut BoardBuilder
+	{
+		self.names.push((rep, color));
+		self
+	}
+
+	/// Set the display color of the stones represented by the specified
+	/// character to the specified index into the ANSI 256-color palette, just
+	/// as a legend property like `r = 9` does.
+	pub fn display (&mut self, rep: char, index: u8) -> &mut BoardBuilder
+	{
+		self.display.retain(|(c, _)| *c != rep);
+		self.display.push((rep, index));
+		self
+	}
+
+	/// Answer the board, subject to the same validations as a [parsed] board.
+	/// Errors that would report a line number report `0`. The characters of
+	/// the ordinary stones must not be the codes of other kinds of stones.
+	///
+	/// The colors are renumbered in the order that the parser would allocate
+	/// them, so that the board survives a round trip through the [text format]
+	/// or the [compact encoding]: the wild colors come first, then the colors
+	/// of the ordinary stones, in row-major order. A wild color that has no
+	/// character, whether from an ordinary stone or a [name], cannot be
+	/// written, so such a board does not survive a round trip.
+	///
+	/// [parsed]: Board::parse
+	/// [text format]: Board::to_tsb
+	/// [compact encoding]: Board::to_compact
+	/// [name]: BoardBuilder::name_color
+	pub fn build (&self) -> BoardResult
+	{
+		if self.width == 0
+		{
+			return Err(ParseError::InvalidPropertyValue { line: 0 })
+		}
+		let stones = self.grid.iter()
+			.filter_map(|stone| match stone
+			{
+				AnyStone::Ordinary(o) => Some(*o),
+				AnyStone::Armored(a) => Some(a.stone),
+				_ => None
+			})
+			.collect::<Vec<_>>();
+		let names = self.names.iter()
+			.map(|&(rep, color)| OrdinaryStone::new(rep, color));
+		let mut colors = ColorMap::new();
+		for o in names.chain(stones.iter().copied())
+		{
+			let clash = colors.iter()
+				.any(|(&c, &color)| (c == o.rep) != (color == o.color));
+			let reserved = o.rep.is_whitespace() || "_#*/+o@".contains(o.rep);
+			if o.color.count_ones() != 1 || clash || reserved
+			{
+				return Err(ParseError::InvalidColor { rep: o.rep })
+			}
+			colors.insert(o.rep, o.color);
+		}
+		// Any other bits, e.g., of wild colors that have no characters, follow
+		// the colors that the parser would allocate.
+		let mut wild = colors.values()
+			.copied()
+			.filter(|color| color & self.wild_colors != 0)
+			.collect::<Vec<_>>();
+		wild.sort_unstable();
+		let restrictions = self.grid.iter()
+			.filter_map(|stone| match stone
+			{
+				AnyStone::Wild(w) => w.restriction,
+				_ => None
+			})
+			.fold(0, |mask, restriction| mask | restriction);
+		let others = colors.values()
+			.fold(self.wild_colors | restrictions, |mask, color| mask | color);
+		let mut allocation = Vec::<u64>::new();
+		for color in wild.into_iter()
+			.chain(stones.iter().map(|o| o.color))
+			.chain((0..u64::BITS).map(|bit| others & 1 << bit))
+			.filter(|&color| color != 0)
+		{
+			if !allocation.contains(&color)
+			{
+				allocation.push(color);
+			}
+		}
+		let renumber = |mask: u64| renumber_colors(&allocation, mask);
+		let grid = self.grid.iter()
+			.map(|&stone| recolor(stone, &renumber))
+			.collect::<Vec<_>>();
+		colors.values_mut().for_each(|color| *color = renumber(*color));
+		let wild_colors = renumber(self.wild_colors);
+		let mut legend = PropertyMap::new();
+		let mut order = Vec::<PropertyKey>::new();
+		legend.insert(PropertyKey::Width, PropertyValue::U32(self.width));
+		if wild_colors != 0
+		{
+			legend.insert(PropertyKey::Wild, PropertyValue::U64(wild_colors));
+			order.push(PropertyKey::Wild);
+		}
+		if self.color_locked
+		{
+			legend.insert(PropertyKey::ColorLock, PropertyValue::Bool(true));
+			order.push(PropertyKey::ColorLock);
+		}
+		for &(rep, index) in &self.display
+		{
+			let key = PropertyKey::Display(rep);
+			legend.insert(
+				key.clone(), PropertyValue::String(index.to_string()));
+			order.push(key);
+		}
+		Board::assemble(legend, order, colors, grid)
+	}
+}
+
+/// The characters that represent the colors of a [random](Board::random)
+/// board, in order of use.
+#[cfg(feature = "rand")]
+const RANDOM_COLORS: &str = "rgbycmpwkv";
+
+/// The probability that a triplet of a [random](Board::random) board includes
+/// a [wild stone](WildStone).
+#[cfg(feature = "rand")]
+const RANDOM_WILD_CHANCE: f64 = 0.1;
+
+impl Board
+{
+	/// Answer a random board of the specified dimensions, whose stones have
+	/// the specified number of colors, e.g., for fuzzing the parser and the
+	/// solver. The same seed always produces the same board.
+	///
+	/// The board is guaranteed to be solvable, because it is built backward
+	/// from a solution: each triplet is stacked beneath the stones already
+	/// placed, so the triplets can be removed in the reverse order of their
+	/// placement. Some triplets include a [wild stone], but never more than
+	/// one of each color, and never as the stone that begins the triplet. The
+	/// board is filled from the top, except for the one or two cells left
+	/// over when the area is not a multiple of three. There are no other
+	/// kinds of stones.
+	///
+	/// # Panics
+	///
+	/// If the board has fewer than three cells, or if `colors` is not between
+	/// `1` and `10`.
+	///
+	/// [wild stone]: WildStone
+	#[cfg(feature = "rand")]
+	pub fn random (width: u32, height: u32, colors: u32, seed: u64) -> Board
+	{
+		assert!(width * height >= 3, "too few cells for a triplet");
+		assert!(
+			(1..=RANDOM_COLORS.len() as u32).contains(&colors),
+			"unsupported number of colors: {}",
+			colors);
+		let mut rng = StdRng::seed_from_u64(seed);
+		let palette = RANDOM_COLORS.chars()
+			.take(colors as usize)
+			.zip((0..).map(|bit| 1u64 << bit))
+			.collect::<Vec<_>>();
+		// The number of stones already placed in each column, from the top.
+		let mut depths = vec![0u32; width as usize];
+		let mut builder = BoardBuilder::new(width, height);
+		let mut wild_colors = 0;
+		for _ in 0..width * height / 3
+		{
+			let (rep, color) = palette[rng.gen_range(0..palette.len())];
+			let wild =
+				wild_colors & color == 0 && rng.gen_bool(RANDOM_WILD_CHANCE);
+			for i in 0..3
+			{
+				let open = (0..width)
+					.filter(|&column| depths[column as usize] < height)
+					.collect::<Vec<_>>();
+				let column = open[rng.gen_range(0..open.len())];
+				let row = depths[column as usize];
+				depths[column as usize] += 1;
+				// The first stone placed is the last removed, so a wild stone
+				// here never has to choose the color of its triplet.
+				let stone = match wild && i == 0
+				{
+					true => AnyStone::Wild(WildStone::new()),
+					false => AnyStone::Ordinary(OrdinaryStone::new(rep, color))
+				};
+				builder.set((column, row), stone);
+			}
+			if wild
+			{
+				wild_colors |= color;
+			}
+		}
+		builder.wild_colors(wild_colors)
+			.build()
+			.expect("random boards are well formed")
+	}
+}
+
+/******************************************************************************
+ *                           Serialization support.                           *
+ ******************************************************************************/
+
+/// Serialization of maps as arrays of `[key, value]` pairs, sorted by key, for
+/// use with `#[serde(with = "sorted_pairs")]`. Sorting makes the output
+/// deterministic.
+#[cfg(feature = "serde")]
+mod sorted_pairs
+{
+	use std::collections::HashMap;
+	use std::hash::Hash;
+	use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+	/// Serialize the specified map as an array of pairs, sorted by key.
+	pub fn serialize<K, V, S> (
+		map: &HashMap<K, V>,
+		serializer: S) -> Result<S::Ok, S::Error>
+	where
+		K: Ord + Serialize,
+		V: Serialize,
+		S: Serializer
+	{
+		let mut pairs = map.iter().collect::<Vec<_>>();
+		pairs.sort_by_key(|(key, _)| *key);
+		pairs.serialize(serializer)
+	}
+
+	/// Deserialize a map from an array of pairs.
+	pub fn deserialize<'de, K, V, D> (
+		deserializer: D) -> Result<HashMap<K, V>, D::Error>
+	where
+		K: Eq + Hash + Deserialize<'de>,
+		V: Deserialize<'de>,
+		D: Deserializer<'de>
+	{
+		Ok(Vec::<(K, V)>::deserialize(deserializer)?.into_iter().collect())
+	}
+}
+
+/******************************************************************************
+ *                              Parsing support.                              *
+ ******************************************************************************/
+
+type BoardResult = result::Result<Board, ParseError>;
+type ColorMap = HashMap<char, u64>;
+type LegendResult = result::Result<(), ParseError>;
+type GridResult = result::Result<Vec<AnyStone>, ParseError>;
+type StoneResult = result::Result<AnyStone, ParseError>;
+type FooterResult = result::Result<Option<Vec<Point>>, ParseError>;
+
+/// The enumeration of errors that can result from [parsing] a [board].
+///
+/// [parsing]: Board::parse
+/// [board]: Board
+#[derive(Debug)]
+pub enum ParseError
+{
+	/// Invalid property syntax in board legend or footer.
+	InvalidPropertySyntax
+	{
+		/// The 1-based line number of the offending token.
+		line: usize,
+
+		/// The 1-based column number, in characters, of the offending token.
+		column: usize
+	},
+
+	/// Invalid property value for well-known property key.
+	InvalidPropertyValue
+	{
+		/// The 1-based line number of the offending property.
+		line: usize
+	},
+
+	/// Repeated color in [wild stone](WildStone) specification.
+	RepeatedWildColor
+	{
+		/// The 1-based line number of the offending property.
+		line: usize
+	},
+
+	/// A stone code has an invalid suffix, e.g., the period of a [toggle
+	/// stone](ToggleStone) or the hits of an [armored stone](ArmoredStone) is
+	/// not a single digit between `2` and `9`.
+	InvalidStoneCode
+	{
+		/// The 1-based line number of the offending stone.
+		line: usize,
+
+		/// The offending stone code.
+		code: String
+	},
+
+	/// A restricted [wild stone](WildStone) names a color that is not one of
+	/// the board's wild colors.
+	UnknownWildColor
+	{
+		/// The 1-based line number of the offending stone.
+		line: usize,
+
+		/// The character that represents the offending color.
+		rep: char
+	},
+
+	/// The `colorlock` property names a color that no stone on the board
+	/// has.
+	UnknownLockColor
+	{
+		/// The character that represents the offending color.
+		rep: char
+	},
+
+	/// Incomplete board, i.e., the last row is not fully populated.
+	IncompleteBoard
+	{
+		/// The 1-based row number of the incomplete row.
+		row: u32,
+
+		/// The number of stones in the incomplete row.
+		stones: u32
+	},
+
+	/// A row of a board with a strict width does not contain the same number
+	/// of stones as the first row.
+	RowWidthMismatch
+	{
+		/// The 1-based line number of the offending row.
+		line: usize,
+
+		/// The 1-based row number of the offending row.
+		row: u32,
+
+		/// The number of stones in the first row.
+		expected: u32,
+
+		/// The number of stones in the offending row.
+		actual: u32
+	},
+
+	/// The number of rows of a board differs from its declared height.
+	DimensionMismatch
+	{
+		/// The declared height of the board.
+		declared: u32,
+
+		/// The actual number of rows.
+		actual: u32
+	},
+
+	/// A row of a board contains more stones than its declared width.
+	RowExceedsWidth
+	{
+		/// The 1-based line number of the offending row.
+		line: usize,
+
+		/// The 1-based row number of the offending row.
+		row: u32,
+
+		/// The declared width of the board.
+		width: u32,
+
+		/// The number of stones in the offending row.
+		actual: u32
+	},
+
+	/// Wrong count of [wild stones](WildStone).
+	WrongWildCount,
+
+	/// The grid contains no stones at all, e.g., because the input is empty.
+	EmptyBoard,
+
+	/// The board can never be cleared, because the number of moves required
+	/// to remove its stones is not a multiple of three. Survivors and toggle
+	/// stones are never played, and an [armored stone](ArmoredStone) is played
+	/// once per hit. Boards with [bombs](BombStone) are exempt, because bombs
+	/// clear stones without playing them.
+	NotDivisibleByThree
+	{
+		/// The number of moves required.
+		moves: u32
+	},
+
+	/// The [JSON](Board::from_json) is malformed.
+	Json (String),
+
+	/// The [compact encoding](Board::from_compact) is malformed.
+	Compact (String),
+
+	/// An [ordinary stone](OrdinaryStone) given to a [`BoardBuilder`] has a
+	/// color that is not exactly 1 bit, or that it does not share with every
+	/// other stone represented by the same character, or that it shares with
+	/// some stone represented by a different character, or it is represented
+	/// by the code of another kind of stone. The same applies to the
+	/// [named](BoardBuilder::name_color) colors.
+	InvalidColor
+	{
+		/// The character that represents the offending stone.
+		rep: char
+	},
+
+	/// The board uses more distinct colors than a color mask can represent,
+	/// i.e., more than [`MAX_COLORS`].
+	TooManyColors
+	{
+		/// The 1-based line number that introduces the excess color.
+		line: usize
+	},
+
+	/// The legend is [strict](PropertyKey::Strict), but contains an unknown
+	/// property.
+	UnknownProperty
+	{
+		/// The 1-based line number of the offending property.
+		line: usize,
+
+		/// The offending key.
+		key: String
+	},
+
+	/// The [content hash](Board::content_hash) of the grid differs from the
+	/// [checksum](PropertyKey::Checksum) declared by the legend, e.g.,
+	/// because the grid was altered after the checksum was computed.
+	ChecksumMismatch
+	{
+		/// The declared checksum.
+		expected: u64,
+
+		/// The content hash of the grid.
+		actual: u64
+	}
+}
+
+impl Display for ParseError
+{
+	fn fmt (&self, f: &mut Formatter) -> Result
+	{
+		match self
+		{
+			ParseError::InvalidPropertySyntax { line, column } => write!(
+				f,
+				"invalid property syntax at line {}, column {}",
+				line,
+				column),
+			ParseError::InvalidPropertyValue { line } => write!(
+				f, "invalid property value at line {}", line),
+			ParseError::RepeatedWildColor { line } => write!(
+				f, "repeated wild color at line {}", line),
+			ParseError::IncompleteBoard { row, stones } => write!(
+				f,
+				"incomplete board: row {} has only {} stone(s)",
+				row,
+				stones),
+			ParseError::RowWidthMismatch { line, row, expected, actual } =>
+				write!(
+					f,
+					"row {} at line {} has {} stone(s), but the first row \
+						has {}",
+					row,
+					line,
+					actual,
+					expected),
+			ParseError::DimensionMismatch { declared, actual } => write!(
+				f,
+				"the board has {} row(s), but its declared height is {}",
+				actual,
+				declared),
+			ParseError::RowExceedsWidth { line, row, width, actual } =>
+				write!(
+					f,
+					"row {} at line {} has {} stone(s), but the board is only \
+						{} wide",
+					row,
+					line,
+					actual,
+					width),
+			ParseError::InvalidStoneCode { line, code } => write!(
+				f, "invalid stone code {:?} at line {}", code, line),
+			ParseError::UnknownWildColor { line, rep } => write!(
+				f,
+				"wild stone at line {} restricted to non-wild color {:?}",
+				line,
+				rep),
+			ParseError::UnknownLockColor { rep } => write!(
+				f, "color lock names unknown color {:?}", rep),
+			ParseError::WrongWildCount => write!(
+				f, "the number of wild stones does not match the wild colors"),
+			ParseError::EmptyBoard => write!(f, "the board has no stones"),
+			ParseError::NotDivisibleByThree { moves } => write!(
+				f,
+				"the board requires {} move(s), which is not a multiple of \
+					three",
+				moves),
+			ParseError::Json(message) => write!(f, "invalid JSON: {}", message),
+			ParseError::Compact(message) =>
+				write!(f, "invalid compact encoding: {}", message),
+			ParseError::InvalidColor { rep } =>
+				write!(f, "invalid color for stone {:?}", rep),
+			ParseError::TooManyColors { line } => write!(
+				f,
+				"too many colors at line {}; at most {} are supported",
+				line,
+				MAX_COLORS),
+			ParseError::UnknownProperty { line, key } => write!(
+				f, "unknown property {:?} at line {}", key, line),
+			ParseError::ChecksumMismatch { expected, actual } => write!(
+				f,
+				"the grid has checksum {:x}, but its declared checksum is {:x}",
+				actual,
+				expected)
+		}
+	}
+}
+
+/// The maximum number of distinct colors, including those of [wild stones],
+/// that a board may use.
+///
+/// [wild stones]: WildStone
+pub const MAX_COLORS: u32 = u64::BITS;
+
+/// The version of the text format that the [parser](Board::parse) accepts,
+/// as given by the optional `version` property.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Split the specified text into stone codes. Every character other than
+/// whitespace is a code by itself, except that the digits that immediately
+/// follow the code of a toggle stone join it, giving the period of a [toggle
+/// stone]. If `armor` is `true`, then such digits also join the code of an
+/// ordinary stone, giving the hits of an [armored stone]; otherwise, as in
+/// the original format, each digit is an ordinary stone of its own, e.g.,
+/// `g2` is a `g` stone and a `2` stone. The joined digits are validated by
+/// the [parser](Board::parse_stone), so `/1` and `r23` are errors. The colors
+/// of a restricted [wild stone] also join its `*`: if `compact` is `true`,
+/// then they must be enclosed in parentheses, e.g., `*(rb)`; otherwise,
+/// every character up to the next whitespace joins it, e.g., `*rb`.
+///
+/// [armored stone]: ArmoredStone
+/// [toggle stone]: ToggleStone
+/// [wild stone]: WildStone
+fn stone_codes (text: &str, compact: bool, armor: bool) -> Vec<String>
+{
+	let mut codes = Vec::<String>::new();
+	let mut joinable = false;
+	let mut wild = false;
+	let mut restricting = false;
+	for c in text.chars()
+	{
+		match c
+		{
+			' ' | '\t' | '\n' =>
+			{
+				joinable = false;
+				wild = false;
+			},
+			')' if restricting => restricting = false,
+			_ if restricting => codes.last_mut().unwrap().push(c),
+			'(' if wild && compact =>
+			{
+				restricting = true;
+				wild = false;
+			},
+			_ if wild && !compact => codes.last_mut().unwrap().push(c),
+			'0'..='9' if joinable => codes.last_mut().unwrap().push(c),
+			_ =>
+			{
+				codes.push(c.to_string());
+				joinable = "/+".contains(c)
+					|| armor && !"_#*o@".contains(c);
+				wild = c == '*';
+			}
+		}
+	}
+	codes
+}
+
+/// Answer the digit between `2` and `9` that constitutes the specified suffix
+/// of a stone code, i.e., the hits of an [armored stone] or the period of a
+/// [toggle stone]. Answer `None` for any other suffix, e.g., `1`, `0`, or
+/// `23`.
+///
+/// [armored stone]: ArmoredStone
+/// [toggle stone]: ToggleStone
+fn suffix_digit (suffix: &str) -> Option<u32>
+{
+	suffix.parse::<u32>()
+		.ok()
+		.filter(|digit| suffix.len() == 1 && (2..=9).contains(digit))
+}
+
+/// Allocate the next color mask, advancing `next_color`. Answer `None` if every
+/// color has already been allocated.
+fn allocate_color (next_color: &mut u64) -> Option<u64>
+{
+	let color = *next_color;
+	if color == 0
+	{
+		return None
+	}
+	// Shifting out the last bit leaves zero, which marks exhaustion.
+	*next_color = color << 1;
+	Some(color)
+}
+
+/// Answer the specified color mask renumbered according to the specified
+/// allocation, such that the color at index `i` of the allocation becomes the
+/// color `1 << i`. Colors absent from the allocation are dropped.
+fn renumber_colors (allocation: &[u64], mask: u64) -> u64
+{
+	allocation.iter()
+		.zip((0..).map(|bit| 1u64 << bit))
+		.filter(|(color, _)| mask & **color != 0)
+		.fold(0, |renumbered, (_, color)| renumbered | color)
+}
+
+/// Answer the specified [stone] with its colors renumbered by the specified
+/// function. Stones without colors are answered unchanged.
+///
+/// [stone]: AnyStone
+fn recolor (stone: AnyStone, renumber: &dyn Fn(u64) -> u64) -> AnyStone
+{
+	let recolor = |o: OrdinaryStone|
+		OrdinaryStone::new(o.rep, renumber(o.color));
+	match stone
+	{
+		AnyStone::Ordinary(o) => AnyStone::Ordinary(recolor(o)),
+		AnyStone::Armored(a) => AnyStone::Armored(
+			ArmoredStone::new(recolor(a.stone), a.hits_remaining)),
+		AnyStone::Wild(WildStone { restriction: Some(mask) }) =>
+			AnyStone::Wild(WildStone { restriction: Some(renumber(mask)) }),
+		stone => stone
+	}
+}
+
+/// Answer the 1-based line and column numbers of the specified byte offset
+/// into the specified text. Columns are measured in characters.
+fn location (text: &str, offset: usize) -> (usize, usize)
+{
+	let before = &text[..offset];
+	let line = before.matches('\n').count() + 1;
+	let column = before.rfind('\n')
+		.map_or(before, |i| &before[i + 1..])
+		.chars()
+		.count() + 1;
+	(line, column)
+}
+
+/// The JSON representation of a board, as accepted by [`from_json`].
+///
+/// [`from_json`]: Board::from_json
+#[cfg(feature = "json")]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonBoard
+{
+	/// The `width` property.
+	width: Option<u32>,
+
+	/// The `height` property.
+	height: Option<u32>,
+
+	/// The `wild` property.
+	wild: Option<String>,
+
+	/// The `colorlock` property, either a boolean or a string of colors.
+	colorlock: Option<serde_json::Value>,
+
+	/// The `survivorrule` property.
+	survivorrule: Option<String>,
+
+	/// The `gravity` property.
+	gravity: Option<String>,
+
+	/// The `access` property.
+	access: Option<String>,
+
+	/// The display properties, keyed by stone character.
+	#[serde(default)]
+	legend: BTreeMap<String, serde_json::Value>,
+
+	/// The rows of the grid, as stone codes.
+	grid: Vec<Vec<String>>
+}
+
+/// The token filter for the board legend.
+struct LegendFilter;
+
+impl filters::Filter for LegendFilter
+{
+	fn on_char (&self, c: &char) -> (bool, bool)
+	{
+		match *c
+		{
+			' ' | '\t' => (true, false),
+			'=' | '\n' => (true, true),
+			_ => (false, false)
+		}
+	}
+}
+
+/// A parse expectation for a board legend parser.
+#[derive(PartialEq, Eq)]
+enum LegendParseState
+{
+	/// Expect either a new property key, a linefeed, or end-of-string.
+	ExpectKeyOrLinefeedOrEnd,
+
+	/// Expect an equals sign (`=`).
+	ExpectEquals,
+
+	/// Expect a property value.
+	ExpectValue,
+
+	/// Expect a linefeed.
+	ExpectLinefeed,
+
+	/// Expect the rest of a comment, i.e., anything up to the next linefeed.
+	ExpectCommentOrLinefeed
+}
+
+/******************************************************************************
+ *                                   Tests.                                   *
+ ******************************************************************************/
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::solve::WILD_COLOR;
+	use proptest::prelude::*;
+
+	/// The sample boards that are well-formed and solvable.
+	const SOLVABLE: [&str; 8] = [
+		include_str!("../examples/board001.tsb"),
+		include_str!("../examples/board002.tsb"),
+		include_str!("../examples/board004.tsb"),
+		include_str!("../examples/board005.tsb"),
+		include_str!("../examples/board006.tsb"),
+		include_str!("../examples/board007.tsb"),
+		include_str!("../examples/board329.tsb"),
+		include_str!("../examples/board415.tsb")
+	];
+
+	/// Play the solution of the specified board with [`play_undoable`],
+	/// answering the board and the number of moves played.
+	///
+	/// [`play_undoable`]: Board::play_undoable
+	fn play_solution (tsb: &str) -> (Board, usize)
+	{
+		let mut board = Board::parse(tsb).unwrap();
+		let solution = board.solve().unwrap();
+		let colors = board.solution_colors(&solution).unwrap();
+		for (index, p) in solution.iter().enumerate()
+		{
+			board.play_undoable(*p, colors[index / 3]);
+		}
+		assert!(board.is_solved());
+		(board, solution.len())
+	}
+
+	#[test]
+	fn undo_restores_parsed_board ()
+	{
+		for tsb in SOLVABLE
+		{
+			let (mut board, moves) = play_solution(tsb);
+			for _ in 0..moves
+			{
+				assert!(board.undo());
+			}
+			assert!(!board.undo());
+			assert_eq!(board, Board::parse(tsb).unwrap());
+			for _ in 0..moves
+			{
+				assert!(board.redo());
+			}
+			assert!(!board.redo());
+			assert!(board.is_solved());
+		}
+	}
+
+	#[test]
+	fn incomplete_undo_is_detected ()
+	{
+		for tsb in SOLVABLE
+		{
+			// Simulate an undo that forgets the first move.
+			let (mut board, moves) = play_solution(tsb);
+			for _ in 1..moves
+			{
+				assert!(board.undo());
+			}
+			assert_ne!(board, Board::parse(tsb).unwrap());
+		}
+	}
+
+	/// Answer the 64-bit FNV-1a hash of the specified text.
+	fn fnv1a (text: &str) -> u64
+	{
+		text.bytes().fold(
+			0xcbf2_9ce4_8422_2325,
+			|hash, byte| (hash ^ byte as u64).wrapping_mul(0x100_0000_01b3))
+	}
+
+	#[test]
+	fn checksum_covers_grid_as_written ()
+	{
+		let rows = "r r r\n# _ _\n";
+		let tsb = format!("checksum = {:x}\n---\n{}", fnv1a(rows), rows);
+		let board = Board::parse(&tsb).unwrap();
+		// The idle survivor is released, so the grid no longer hashes to the
+		// declared checksum.
+		assert_ne!(board.content_hash(), fnv1a(rows));
+		assert_eq!(Board::parse(&board.to_tsb()).unwrap(), board);
+	}
+
+	#[test]
+	fn corrupted_grid_fails_checksum ()
+	{
+		let rows = "r r r\ng g g\n";
+		let corrupted = "r r r\ng r g\n";
+		let tsb = format!("checksum = {:x}\n---\n{}", fnv1a(rows), corrupted);
+		match Board::parse(&tsb)
+		{
+			Err(ParseError::ChecksumMismatch { expected, actual }) =>
+			{
+				assert_eq!(expected, fnv1a(rows));
+				assert_eq!(actual, fnv1a(corrupted));
+			},
+			result => panic!("expected a checksum mismatch, not {:?}", result)
+		}
+	}
+
+	#[test]
+	fn tsb_round_trips ()
+	{
+		for tsb in SOLVABLE
+		{
+			let board = Board::parse(tsb).unwrap();
+			assert_eq!(Board::parse(&board.to_tsb()).unwrap(), board);
+		}
+	}
+
+	/// Answer the stone that the specified code denotes in
+	/// [`builder_boards_round_trip`].
+	fn arbitrary_stone (code: u8) -> AnyStone
+	{
+		let reps = ['r', 'g', 'b', 'y'];
+		match code
+		{
+			0 => AnyStone::None(NoStone),
+			1 => AnyStone::Survivor(SurvivorStone),
+			2 => AnyStone::Toggle(ToggleStone::open()),
+			3 => AnyStone::Toggle(ToggleStone::closed().with_period(2)),
+			4 => AnyStone::Wild(WildStone::new()),
+			5 => AnyStone::Armored(
+				ArmoredStone::new(OrdinaryStone::new('r', 1), 2)),
+			_ =>
+			{
+				let i = (code - 6) as usize % reps.len();
+				AnyStone::Ordinary(OrdinaryStone::new(reps[i], 1 << i))
+			}
+		}
+	}
+
+	proptest!
+	{
+		#[test]
+		fn builder_boards_round_trip (
+			width in 1u32..6,
+			height in 1u32..6,
+			codes in prop::collection::vec(0u8..12, 25..26),
+			locked in 0u8..2
+		)
+		{
+			let mut builder = BoardBuilder::new(width, height);
+			for (i, code) in codes.iter()
+				.take((width * height) as usize)
+				.enumerate()
+			{
+				let p = (i as u32 % width, i as u32 / width);
+				builder.set(p, arbitrary_stone(*code));
+			}
+			// Name every color, so that every wild color can be written.
+			for (i, rep) in ['r', 'g', 'b', 'y'].into_iter().enumerate()
+			{
+				builder.name_color(rep, 1 << i);
+			}
+			let wilds = codes.iter()
+				.take((width * height) as usize)
+				.filter(|code| **code == 4)
+				.count();
+			builder.wild_colors((1u64 << wilds.min(4)) - 1);
+			builder.color_lock(locked == 1);
+			// Most random boards are malformed, e.g., because they have too
+			// many wild stones; those are of no interest here.
+			if let Ok(board) = builder.build()
+			{
+				let tsb = board.to_tsb();
+				
+				prop_assert_eq!(Board::parse(&tsb).unwrap(), board);
+			}
+		}
+	}
+
+	#[test]
+	fn tsb_round_trips_after_play ()
+	{
+		for tsb in SOLVABLE
+		{
+			let mut board = Board::parse(tsb).unwrap();
+			let solution = board.solve().unwrap();
+			// The last triplet empties the board, which cannot be parsed.
+			for triplet in solution.chunks(3).rev().skip(1).rev()
+			{
+				board.play([triplet[0], triplet[1], triplet[2]]).unwrap();
+				// The text format describes only the current state of the
+				// board, i.e., neither the turn, nor the lock, nor the colors
+				// of the stones that are gone, nor the progress of toggle
+				// stones through their periods.
+				let tsb = board.to_tsb();
+				
+				let parsed = Board::parse(&tsb).unwrap();
+				assert_eq!(parsed.to_tsb(), tsb);
+				let toggles = board.iter_stones()
+					.any(|(_, s)| matches!(s, AnyStone::Toggle(_)));
+				if !toggles
+				{
+					let mut expected = board.canonicalize();
+					expected.turn = 0;
+					expected.locked_color = 0;
+					assert_eq!(parsed.canonicalize(), expected);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn tsb_omits_consumed_wild ()
+	{
+		let mut board = Board::parse("wild = r\n---\ng g g\nr * r\n").unwrap();
+		board.play([(0, 1), (1, 1), (2, 1)]).unwrap();
+		assert_eq!(board.wild_colors(), 0);
+		let tsb = board.to_tsb();
+		assert!(!tsb.contains("wild"));
+		let mut expected = board.canonicalize();
+		expected.turn = 0;
+		assert_eq!(Board::parse(&tsb).unwrap().canonicalize(), expected);
+	}
+
+	#[test]
+	fn tsb_restricts_wild_to_remaining_colors ()
+	{
+		let mut board =
+			Board::parse("wild = rb\n---\nb b *rb\nr r *rb\n").unwrap();
+		board.play([(0, 1), (1, 1), (2, 1)]).unwrap();
+		let tsb = board.to_tsb();
+		assert!(tsb.contains("wild = b\n"));
+		assert!(tsb.contains("*b\n"));
+		let mut reparsed = Board::parse(&tsb).unwrap();
+		assert_eq!(reparsed.to_tsb(), tsb);
+		assert!(reparsed.solve().is_some());
+		// A restricted wild stone whose colors are all consumed is dead.
+		let mut board =
+			Board::parse("wild = rb\n---\nb b *r\nr r *rb\n").unwrap();
+		board.play([(0, 1), (1, 1), (2, 1)]).unwrap();
+		let tsb = board.to_tsb();
+		assert!(tsb.contains("*-\n"));
+		let mut reparsed = Board::parse(&tsb).unwrap();
+		assert_eq!(reparsed.to_tsb(), tsb);
+		assert_eq!(reparsed.wild_colors().count_ones(), 1);
+		assert!(reparsed.solve().is_none());
+	}
+
+	#[test]
+	fn grid_2d_matches_get ()
+	{
+		let board = Board::parse(include_str!("../examples/board005.tsb"))
+			.unwrap();
+		let grid = board.grid_2d();
+		assert_eq!(grid.len(), board.height() as usize);
+		assert!(grid.iter().all(|row| row.len() == board.width() as usize));
+		assert_eq!(grid[3][4], board.get((4, 3)).unwrap());
+		assert!(matches!(grid[3][4], AnyStone::Wild(_)));
+		for (y, row) in grid.iter().enumerate()
+		{
+			for (x, stone) in row.iter().enumerate()
+			{
+				assert_eq!(*stone, board.get((x as u32, y as u32)).unwrap());
+			}
+		}
+	}
+
+	#[test]
+	fn index_and_point_round_trip ()
+	{
+		let board = Board::parse(include_str!("../examples/board005.tsb"))
+			.unwrap();
+		assert_ne!(board.width(), board.height());
+		for index in 0..board.grid.len()
+		{
+			let (x, y) = board.point(index);
+			assert!(x < board.width() && y < board.height());
+			assert_eq!(board.index((x, y)), index);
+		}
+		for y in 0..board.height()
+		{
+			for x in 0..board.width()
+			{
+				assert_eq!(board.point(board.index((x, y))), (x, y));
+			}
+		}
+	}
+
+	#[test]
+	fn strict_width_rejects_short_row ()
+	{
+		let tsb = "strictwidth = true\n---\nr r r\ng g\ng _ _\n";
+		match Board::parse(tsb)
+		{
+			Err(ParseError::RowWidthMismatch { line, row, expected, actual }) =>
+				assert_eq!((line, row, expected, actual), (4, 2, 3, 2)),
+			result => panic!("expected a row width mismatch, not {:?}", result)
+		}
+	}
+
+	#[test]
+	fn color_lock_rejects_unknown_color ()
+	{
+		let tsb = "colorlock = rx\n---\nr r r\ng g g\n";
+		match Board::parse(tsb)
+		{
+			Err(ParseError::UnknownLockColor { rep }) => assert_eq!(rep, 'x'),
+			result => panic!("expected an unknown lock color, not {:?}", result)
+		}
+		let board = Board::parse("colorlock = rg\n---\nr r r\ng g g\n");
+		assert_eq!(board.unwrap().lock_colors().count_ones(), 2);
+	}
+
+	#[test]
+	fn armor_requires_legend_marker ()
+	{
+		let board = Board::parse("g2 g2 g2\n").unwrap();
+		assert_eq!(board.width(), 6);
+		assert!(matches!(board.get((1, 0)), Some(AnyStone::Ordinary(_))));
+		let board = Board::parse("armor = true\n---\ng2 g\n").unwrap();
+		assert_eq!(board.width(), 2);
+		assert!(matches!(
+			board.get((0, 0)),
+			Some(AnyStone::Armored(a)) if a.hits_remaining() == 2));
+		let tsb = board.to_tsb();
+		assert!(tsb.starts_with("armor = true\n"));
+		assert_eq!(Board::parse(&tsb).unwrap().to_tsb(), tsb);
+	}
+
+	#[test]
+	fn invalid_suffix_is_rejected ()
+	{
+		for (tsb, bad) in [
+			("r r /1\n", "/1"),
+			("r r /0\n", "/0"),
+			("r r +1\n", "+1"),
+			("r r /23\n", "/23"),
+			("armor = true\n---\nr23 r\n", "r23"),
+			("armor = true\n---\nr1 r r\n", "r1")]
+		{
+			match Board::parse(tsb)
+			{
+				Err(ParseError::InvalidStoneCode { code, .. }) =>
+					assert_eq!(code, bad),
+				result => panic!("expected an invalid code, not {:?}", result)
+			}
+		}
+	}
+
+	#[test]
+	fn toggle_of_period_two_changes_state_every_two_turns ()
+	{
+		let mut board = Board::parse("r r r r r r /2\n").unwrap();
+		let mut states = vec![];
+		for column in 0..6
+		{
+			match board.get((6, 0))
+			{
+				Some(AnyStone::Toggle(t)) => states.push(t.is_open()),
+				stone => panic!("expected a toggle, not {:?}", stone)
+			}
+			board.play_undoable((column, 0), 0);
+		}
+		assert_eq!(states, [true, true, false, false, true, true]);
+	}
+
+	#[test]
+	fn gravity_settles_column_and_undo_lifts_it ()
+	{
+		let tsb = "gravity = down\n---\nr g\ng b\nb r\n";
+		let mut board = Board::parse(tsb).unwrap();
+		board.play_undoable((0, 2), 0);
+		assert!(board.to_tsb().ends_with("---\n_ g\nr b\ng r\n"));
+		assert_eq!(board.removable_stones(), 5);
+		board.play_undoable((0, 2), 0);
+		assert!(board.to_tsb().ends_with("---\n_ g\n_ b\nr r\n"));
+		assert!(board.undo());
+		assert!(board.undo());
+		assert_eq!(board, Board::parse(tsb).unwrap());
+		assert_eq!(board.removable_stones(), 6);
+	}
+
+	#[test]
+	fn advance_hint_steps_through_solution ()
+	{
+		for tsb in SOLVABLE
+		{
+			let mut board = Board::parse(tsb).unwrap();
+			let moves = board.solve().unwrap();
+			let colors = board.solution_colors(&moves).unwrap();
+			for (index, &p) in moves.iter().enumerate()
+			{
+				let style = match index % 2
+				{
+					0 => RenderStyle::Plain,
+					_ => RenderStyle::Ansi
+				};
+				let rendition = board.advance_hint(p, colors[index / 3], style);
+				assert_eq!(rendition.contains('\x1b'), index % 2 == 1);
+			}
+			assert!(board.is_solved());
+			assert_eq!(board.turn() as usize, moves.len());
+		}
+	}
+
+	#[test]
+	fn bomb_clears_cluster_and_undo_restores_it ()
+	{
+		let tsb = "g g g\ng o g\ng g r\n";
+		let mut board = Board::parse(tsb).unwrap();
+		assert_eq!(board.removable_stones(), 9);
+		board.play_undoable((1, 1), 0);
+		assert!(board.to_tsb().ends_with("---\n_ _ _\n_ _ _\n_ _ _\n"));
+		assert_eq!(board.removable_stones(), 0);
+		assert!(board.undo());
+		assert_eq!(board, Board::parse(tsb).unwrap());
+		assert_eq!(board.removable_stones(), 9);
+	}
+
+	#[test]
+	fn bombs_explode_in_chains ()
+	{
+		let tsb = "r _ _ _ b\n_ o _ _ _\n_ _ o g _\n";
+		let mut board = Board::parse(tsb).unwrap();
+		board.play_undoable((1, 1), 0);
+		assert!(board.to_tsb().ends_with(
+			"---\n_ _ _ _ b\n_ _ _ _ _\n_ _ _ _ _\n"));
+		assert_eq!(board.removable_stones(), 1);
+		assert!(board.undo());
+		assert_eq!(board, Board::parse(tsb).unwrap());
+		assert_eq!(board.removable_stones(), 5);
 	}
 
-	/// Remove all [survivors] from the row of the specified point, but only if
-	/// there are no removable stones in the row with them. Answer the removed
-	/// survivors.
-	///
-	/// [survivors]: SurvivorStone
-	#[must_use]
-	fn remove_survivors (&mut self, p: Point) -> Vec<Point>
+	#[test]
+	fn incremental_frontier_matches_measured_frontier ()
 	{
-		let removable = (0..self.width)
-			.map(|column|
-			{
-				let index = (p.1 * self.width + column) as usize;
-				self.grid[index]
-			})
-			.filter(|s| s.is_removable())
-			.count();
-		if removable == 0
+		let tsb = "g r + r\ng / r b\nr / g +\nr / b +\n/ r b +\n";
+		let mut board = Board::parse(tsb).unwrap();
+		let moves = board.solve().unwrap();
+		let colors = board.solution_colors(&moves).unwrap();
+		for (index, &p) in moves.iter().enumerate()
 		{
-			let mut survivors = Vec::<Point>::new();
-			for column in 0..self.width
+			// Without clearances, the frontier is found by scanning each
+			// column from the bottom.
+			let mut measured = board.clone();
+			measured.clearances.clear();
+			for color in [WILD_COLOR, colors[index / 3]]
 			{
-				let index = (p.1 * self.width + column) as usize;
-				match self.grid[index]
+				for allow_wild in [false, true]
 				{
-					AnyStone::Survivor(_) =>
-					{
-						survivors.push((column, p.1));
-						self.grid[index] = AnyStone::None(NoStone);
-					},
-					_ => {}
+					assert_eq!(
+						board.frontier(color, allow_wild),
+						measured.frontier(color, allow_wild),
+						"move {}",
+						index);
 				}
 			}
-			survivors
-		}
-		else
-		{
-			vec![]
+			board.play_undoable(p, colors[index / 3]);
+			let mut measured = board.clone();
+			measured.measure_clearances();
+			assert_eq!(board.clearances, measured.clearances);
 		}
+		assert!(board.is_solved());
 	}
 
-	/// Add [survivors] to the specified locations.
-	///
-	/// [survivors]: SurvivorStone
-	fn add_survivors (&mut self, survivors: &Vec<Point>)
+	#[test]
+	fn force_remove_counts_down_to_solved ()
 	{
-		for p in survivors
+		for tsb in SOLVABLE
 		{
-			let index = (p.1 * self.width + p.0) as usize;
-			self.grid[index] = AnyStone::Survivor(SurvivorStone);
+			let mut board = Board::parse(tsb).unwrap();
+			let moves = board.solve().unwrap();
+			for (index, &p) in moves.iter().enumerate()
+			{
+				assert!(!board.is_solved());
+				let before = board.removable_stones();
+				board.force_remove(p);
+				let last = index == moves.len() - 1;
+				assert_eq!(board.removable_stones() == 0, last);
+				assert!(board.removable_stones() < before
+					|| matches!(board.get(p), Some(AnyStone::Armored(_))));
+			}
+			assert!(board.is_solved());
 		}
+		let mut board = Board::parse("# r r r\n/ g g g\n").unwrap();
+		assert_eq!(board.removable_stones(), 6);
+		board.force_remove((0, 0));
+		board.force_remove((0, 1));
+		assert_eq!(board.removable_stones(), 6);
 	}
 
-	/// Forcibly remove the [stone] at the specified location without doing any
-	/// accounting other than incrementing the turn. This is a destructive
-	/// operation, and should not be used for computing a board solution.
-	///
-	/// [stone]: AnyStone
-	pub fn force_remove (&mut self, p: Point)
+	#[test]
+	fn plain_rendition_has_no_escapes ()
 	{
-		let index = (p.1 * self.width + p.0) as usize;
-		self.grid[index] = AnyStone::None(NoStone);
-		self.turn += 1;
-		let _ = self.remove_survivors(p);
+		for tsb in SOLVABLE
+		{
+			let mut board = Board::parse(tsb).unwrap();
+			assert!(!board.render(RenderStyle::Plain).contains('\x1b'));
+			assert!(board.render(RenderStyle::Ansi).contains('\x1b'));
+			let p = board.legal_moves()[0];
+			let mut rendition = String::new();
+			board.with_highlight(
+				p, &mut |board| rendition = board.render(RenderStyle::Plain));
+			assert!(!rendition.contains('\x1b'));
+		}
 	}
 
-	/// Apply the specified closure to the [stone] at `(x,y)`, where the origin
-	/// `(0,0)` is the uppermost leftmost grid cell.
-	///
-	/// [stone]: AnyStone
-	pub fn stone_do (
-		&self,
-		p: Point,
-		action: &mut dyn for<'r, 's> FnMut(&'r Board, &'s AnyStone))
+	#[test]
+	fn solve_restores_board ()
 	{
-		let index = (p.1 * self.width + p.0) as usize;
-		let stone = self.grid[index].for_board(self);
-		action(self, &stone)
+		for tsb in SOLVABLE
+		{
+			let mut board = Board::parse(tsb).unwrap();
+			assert!(board.solve().is_some());
+			assert_eq!(board, Board::parse(tsb).unwrap());
+		}
 	}
 
-	/// Apply the specified closure while the specified [stone] is highlighted.
-	///
-	/// [stone]: AnyStone
-	pub fn with_highlight (
-		&mut self,
-		p: Point,
-		action: &mut dyn for<'r> FnMut(&'r Board))
+	#[test]
+	fn solution_labels_follow_move_order ()
 	{
-		self.highlight = Some(p);
-		action(self);
-		self.highlight = None;
+		let mut board = Board::parse("r r r #\ng g g _\n").unwrap();
+		let solution = board.solve().unwrap();
+		let labels = board.solution_labels(&solution)
+			.iter()
+			.map(|label| label.map(|label| label.to_string()))
+			.collect::<Vec<_>>();
+		for (n, &p) in solution.iter().enumerate()
+		{
+			let expected = (n + 1).to_string();
+			assert_eq!(labels[board.index(p)].as_deref(), Some(&*expected));
+		}
+		// The survivor goes with the last stone of its row.
+		assert_eq!(labels[board.index((3, 0))].as_deref(), Some("#6"));
+		assert_eq!(labels[board.index((3, 1))], None);
+		assert!(board.render_solution(&solution).contains("#6"));
 	}
-}
-
-const NW_CORNER: char = '\u{250F}';
-const NE_CORNER: char = '\u{2513}';
-const SE_CORNER: char = '\u{251B}';
-const SW_CORNER: char = '\u{2517}';
-const V_LINE: char = '\u{2503}';
-const H_LINE: char = '\u{2501}';
 
-impl Display for Board
-{
-	/// We use ANSI colors and Unicode box characters to draw pretty
-	/// representations of the board, so VT 100 or similar is required for best
-	/// effect.
-	fn fmt (&self, f: &mut Formatter) -> Result
+	#[test]
+	fn four_rotations_restore_board ()
 	{
-		write!(f, "Turn #{}", self.turn + 1)?;
-		if let Some((column, row)) = self.highlight
-		{
-			write!(f, ": \u{1b}[38;5;15m({}, {})\u{1b}[0m", column, row)?;
-		}
-		// Write the top of the box.
-		write!(f, "\n{}", NW_CORNER)?;
-		for _ in 0..(self.width << 1) - 1 { write!(f, "{}", H_LINE)?; }
-		writeln!(f, "{}", NE_CORNER)?;
-		// Write the contexts of the box.
-		for row in 0..self.height
+		let board = Board::parse("r g b\ny g b\n").unwrap();
+		let cw = board.rotate_cw();
+		assert_eq!((cw.width(), cw.height()), (2, 3));
+		assert_eq!(cw.to_tsb(), "\n---\ny r\ng g\nb b\n");
+		assert_eq!(board.rotate_ccw().to_tsb(), "\n---\nb b\ng g\nr y\n");
+		for tsb in SOLVABLE
 		{
-			write!(f, "{}", V_LINE)?;
-			for column in 0..self.width
+			let mut board = Board::parse(tsb).unwrap();
+			let mut cw = board.clone();
+			let mut ccw = board.clone();
+			for _ in 0..4
 			{
-				let index = (row * self.width + column) as usize;
-				let stone = self.grid[index].for_board(self);
-				let highlight =
-					if Some((column, row))==self.highlight {"\u{1b}[48;5;231m"}
-					else { "" };
-				let space = if column == self.width - 1 { "" } else { " " };
-				match stone
-				{
-					AnyStone::Ordinary(o) =>
-					{
-						match self.properties.get(&PropertyKey::Display(o.rep))
-						{
-							Some(PropertyValue::String(display)) => write!(
-								f,
-								"{}{}\u{1b}[0m{}",
-								highlight,
-								display,
-								space)?,
-							_ => write!(
-								f, "{}{}\u{1b}[0m{}", highlight, o, space)?
-						}
-					},
-					s => write!(f, "{}{}\u{1b}[0m{}", highlight, s, space)?
-				};
+				cw = cw.rotate_cw();
+				ccw = ccw.rotate_ccw();
 			}
-			writeln!(f, "{}", V_LINE)?;
+			assert_eq!(cw, board);
+			assert_eq!(ccw, board);
+			assert_eq!(board.rotate_cw().rotate_ccw(), board);
+			assert!(cw.solve().is_some());
+			assert!(board.solve().is_some());
 		}
-		// Write the bottom of the box.
-		write!(f, "{}", SW_CORNER)?;
-		for _ in 0..(self.width << 1) - 1 { write!(f, "{}", H_LINE)?; }
-		writeln!(f, "{}", SE_CORNER)?;
-		Ok(())
 	}
-}
 
-/******************************************************************************
- *                             Property support.                              *
- ******************************************************************************/
+	#[test]
+	fn tsb_preserves_legend_order ()
+	{
+		let legend = "r = 9\nauthor = someone\nwild = g\ncolorlock = true\n";
+		let tsb = format!("{}---\nr r r\ng g *\n", legend);
+		let board = Board::parse(&tsb).unwrap();
+		assert_eq!(board.to_tsb(), tsb);
+	}
 
-pub type PropertyMap = HashMap<PropertyKey, PropertyValue>;
+	#[test]
+	fn footer_gives_expected_solution ()
+	{
+		let grid = "r g g\nr r g\n";
+		assert_eq!(Board::parse(grid).unwrap().expected_solution(), None);
+		let tsb = format!(
+			"{}\nsolution = 0,1 0,0 1,1\nsolution = 2,1 2,0 1,0\n",
+			grid);
+		let mut board = Board::parse(&tsb).unwrap();
+		let expected = board.expected_solution().unwrap();
+		assert_eq!(expected, [(0, 1), (0, 0), (1, 1), (2, 1), (2, 0), (1, 0)]);
+		assert!(board.is_valid_solution(&expected));
+		let tsb = format!(
+			"{}\nsolution = 0,1 1,1 2,1\nsolution = 0,0 1,0 2,0\n",
+			grid);
+		let mut board = Board::parse(&tsb).unwrap();
+		let expected = board.expected_solution().unwrap();
+		assert!(!board.is_valid_solution(&expected));
+		// Moves must lie on the board.
+		let tsb = format!("{}\nsolution = 3,0\n", grid);
+		assert!(matches!(
+			Board::parse(&tsb),
+			Err(ParseError::InvalidPropertyValue { line: 4 })));
+	}
 
-/// A board property key.
-#[derive(PartialEq, Eq, Hash, Debug)]
-pub enum PropertyKey
-{
-	/// The width, in stones, i.e., the row stride.
-	Width,
+	#[test]
+	fn legend_errors_report_line_and_column ()
+	{
+		assert!(matches!(
+			Board::parse("width = 3\nbogus line\n---\nr r r\n"),
+			Err(ParseError::InvalidPropertySyntax { line: 2, column: 7 })));
+		assert!(matches!(
+			Board::parse("width = 3\n  = 4\n---\nr r r\n"),
+			Err(ParseError::InvalidPropertySyntax { line: 2, column: 3 })));
+		assert!(matches!(
+			Board::parse("width = 3\n// comment\nwild = r g\n---\nr r r\n"),
+			Err(ParseError::InvalidPropertySyntax { line: 3, column: 10 })));
+		assert!(matches!(
+			Board::parse("width = q\n---\nr r r\n"),
+			Err(ParseError::InvalidPropertyValue { line: 1 })));
+		assert!(matches!(
+			Board::parse("\n\nwild = rr\n---\nr r r\n"),
+			Err(ParseError::RepeatedWildColor { line: 3 })));
+	}
 
-	/// The specification of colors for [wild stones](WildStone).
-	Wild,
+	#[test]
+	fn ragged_rows_are_padded ()
+	{
+		// The longest row establishes the width.
+		let board = Board::parse("r r r\nr r\nr\n").unwrap();
+		assert_eq!((board.width(), board.height()), (3, 3));
+		for p in [(2, 1), (1, 2), (2, 2)]
+		{
+			assert_eq!(board.get(p), Some(AnyStone::None(NoStone)));
+		}
+		assert_eq!(board.to_tsb(), "\n---\nr r r\nr r _\nr _ _\n");
+		// An explicit width may exceed the longest row, but not fall short.
+		let board = Board::parse("width = 4\n---\nr r r\nr r r\n").unwrap();
+		assert_eq!((board.width(), board.height()), (4, 2));
+		assert_eq!(board.get((3, 1)), Some(AnyStone::None(NoStone)));
+		assert!(matches!(
+			Board::parse("width = 3\n---\nr r r\nr r r r\n"),
+			Err(ParseError::RowExceedsWidth {
+				line: 4,
+				row: 2,
+				width: 3,
+				actual: 4
+			})));
+	}
 
-	/// A color is locked once completed, and cannot be played until another
-	/// color has been played.
-	ColorLock,
+	#[test]
+	fn declared_height_must_match_rows ()
+	{
+		let board = Board::parse("height = 2\n---\nr r r\nr r r\n").unwrap();
+		assert_eq!(board.height(), 2);
+		// A dropped row is caught.
+		assert!(matches!(
+			Board::parse("height = 3\n---\nr r r\nr r r\n"),
+			Err(ParseError::DimensionMismatch { declared: 3, actual: 2 })));
+		// Absent a declaration, the height is derived.
+		assert_eq!(Board::parse("r r r\nr r r\n").unwrap().height(), 2);
+	}
 
-	/// The specification of display properties for a stone.
-	Display (char),
+	#[test]
+	fn survivor_rule_governs_column_clearing ()
+	{
+		// Only the column of the survivor empties.
+		let grid = "# g g g\nr r r _\n";
+		let survivor = Some(AnyStone::Survivor(SurvivorStone));
+		let mut board = Board::parse(grid).unwrap();
+		board.play_undoable((0, 1), 0);
+		assert_eq!(board.get((0, 0)), survivor);
+		let tsb = format!("survivorrule = roworcolumn\n---\n{}", grid);
+		let mut board = Board::parse(&tsb).unwrap();
+		board.play_undoable((0, 1), 0);
+		assert_eq!(board.get((0, 0)), Some(AnyStone::None(NoStone)));
+		assert!(board.undo());
+		assert_eq!(board.get((0, 0)), survivor);
+		assert_eq!(board, Board::parse(&tsb).unwrap());
+	}
 
-	/// An unknown property.
-	Unknown (String)
-}
+	#[test]
+	fn many_colors_fit_until_mask_overflows ()
+	{
+		// One row of three stones per color.
+		let rows = |count: u32| (0..count)
+			.map(|i| char::from_u32(0x100 + i).unwrap())
+			.map(|c| format!("{} {} {}\n", c, c, c))
+			.collect::<String>();
+		let mut board = Board::parse(&rows(33)).unwrap();
+		let last = board.get((0, 32)).unwrap();
+		assert!(matches!(last, AnyStone::Ordinary(o) if o.color() == 1 << 32));
+		assert_eq!(board.solve().map(|moves| moves.len()), Some(99));
+		assert!(Board::parse(&rows(MAX_COLORS)).is_ok());
+		assert!(matches!(
+			Board::parse(&rows(MAX_COLORS + 1)),
+			Err(ParseError::TooManyColors { line: 65 })));
+	}
 
-/// A board property value.
-#[derive(PartialEq, Eq, Hash, Debug)]
-pub enum PropertyValue
-{
-	/// An arbitrary `bool`.
-	Bool (bool),
+	#[cfg(feature = "serde")]
+	#[test]
+	fn serde_round_trip_preserves_state ()
+	{
+		let tsb = "wild = g\ncolorlock = true\n---\nr g g\nr r *\n";
+		let mut board = Board::parse(tsb).unwrap();
+		board.play_undoable((0, 1), 0);
+		let json = serde_json::to_string(&board).unwrap();
+		let restored = serde_json::from_str::<Board>(&json).unwrap();
+		assert_eq!(restored, board);
+		assert_eq!(restored.turn(), board.turn());
+		assert_eq!(restored.wild_colors(), board.wild_colors());
+		assert_eq!(restored.color_locked(), board.color_locked());
+	}
 
-	/// An arbitrary `u32`.
-	U32 (u32),
+	#[cfg(feature = "json")]
+	#[test]
+	fn from_json_agrees_with_text_format ()
+	{
+		let json = r##"{
+			"width": 5,
+			"wild": "g",
+			"colorlock": true,
+			"gravity": "none",
+			"legend": {"r": 9, "g": 28},
+			"grid": [["r", "g", "_", "_", "_"], ["r", "r", "g", "*", "#"]]
+		}"##;
+		let tsb = "wild = g\ncolorlock = true\ngravity = none\nr = 9\ng = 28\n\
+			---\nr g _ _ _\nr r g * #\n";
+		assert_eq!(Board::from_json(json).unwrap(), Board::parse(tsb).unwrap());
+		let malformed = [
+			"{\"grid\": ",
+			"{\"grid\": [[\"r\", \"r\", \"rr\"]]}",
+			"{\"legend\": {\"rg\": 9}, \"grid\": [[\"r\", \"r\", \"r\"]]}",
+			"{\"depth\": 3, \"grid\": [[\"r\", \"r\", \"r\"]]}"
+		];
+		for json in malformed
+		{
+			assert!(
+				matches!(Board::from_json(json), Err(ParseError::Json(_))),
+				"{}",
+				json);
+		}
+		// The same validations apply as for the text format.
+		assert!(matches!(
+			Board::from_json(
+				"{\"wild\": \"g\", \"grid\": [[\"r\", \"r\", \"r\"]]}"),
+			Err(ParseError::WrongWildCount)));
+		assert!(matches!(
+			Board::from_json(
+				"{\"gravity\": \"up\", \"grid\": [[\"r\", \"r\", \"r\"]]}"),
+			Err(ParseError::InvalidPropertyValue { line: 0 })));
+	}
 
-	/// Arbitrary text.
-	String (String)
-}
+	/// Assert that the specified XML is well formed, at least insofar as its
+	/// tags balance.
+	fn assert_tags_balance (xml: &str)
+	{
+		let mut open = Vec::<&str>::new();
+		for tag in xml.split('<').skip(1).map(|s| &s[..s.find('>').unwrap()])
+		{
+			if tag.starts_with('?') || tag.ends_with('/')
+			{
+				continue
+			}
+			match tag.strip_prefix('/')
+			{
+				Some(name) => assert_eq!(open.pop(), Some(name)),
+				None => open.push(tag.split_whitespace().next().unwrap())
+			}
+		}
+		assert!(open.is_empty(), "unclosed: {:?}", open);
+	}
 
-/******************************************************************************
- *                              Parsing support.                              *
- ******************************************************************************/
+	#[test]
+	fn svg_draws_one_rect_per_stone ()
+	{
+		let tsb = "wild = g\nr = 9\n---\nr # g\n+ * g\nr r /\n_ _ _\n";
+		let board = Board::parse(tsb).unwrap();
+		let svg = board.to_svg();
+		assert!(svg.starts_with("<?xml"));
+		assert_tags_balance(&svg);
+		assert_eq!(svg.matches("<rect ").count(), 9);
+		assert!(svg.contains("fill=\"url(#wild)\""));
+		let mut board = Board::parse("r r r #\ng g g _\n").unwrap();
+		let solution = board.solve().unwrap();
+		let svg = board.solution_to_svg(&solution);
+		assert_tags_balance(&svg);
+		assert_eq!(svg.matches("<rect ").count(), 7);
+		for label in ["1", "2", "3", "4", "5", "6", "#6"]
+		{
+			assert!(svg.contains(&format!(">{}</text>", label)), "{}", label);
+		}
+	}
 
-type BoardResult = result::Result<Board, ParseError>;
-type ColorMap = HashMap<char, u32>;
-type LegendResult = result::Result<(), ParseError>;
-type GridResult = result::Result<Vec<AnyStone>, ParseError>;
+	#[test]
+	fn compact_encoding_round_trips ()
+	{
+		for tsb in SOLVABLE
+		{
+			let board = Board::parse(tsb).unwrap();
+			let compact = board.to_compact();
+			assert!(!compact.contains('\n'));
+			assert_eq!(Board::from_compact(&compact).unwrap(), board);
+		}
+		let tsb = "wild = g\narmor = true\n---\nr g r2\n* +2 g\n";
+		let board = Board::parse(tsb).unwrap();
+		let compact = "wild=g;armor=true;w3:rgr2*+2g";
+		assert_eq!(board.to_compact(), compact);
+		assert_eq!(Board::from_compact(compact).unwrap(), board);
+	}
 
-/// The enumeration of errors that can result from [parsing] a [board].
-///
-/// [parsing]: Board::parse
-/// [board]: Board
-#[derive(Debug)]
-pub enum ParseError
-{
-	/// Invalid property syntax in board legend.
-	InvalidPropertySyntax,
+	#[test]
+	fn border_styles_draw_their_own_characters ()
+	{
+		let board = Board::parse("r r r\n").unwrap();
+		let heavy = board.render_with_border(BorderStyle::Heavy);
+		assert_eq!(board.to_string(), heavy);
+		let styles = [
+			(BorderStyle::Heavy, "┏━┓┃┛┗"),
+			(BorderStyle::Light, "┌─┐│┘└"),
+			(BorderStyle::Ascii, "+-|")
+		];
+		for (style, chars) in styles
+		{
+			let rendition = board.render_with_border(style);
+			for c in chars.chars()
+			{
+				assert!(rendition.contains(c), "{:?} lacks {:?}", style, c);
+			}
+			for (other, others) in styles
+			{
+				if other != style
+				{
+					let corner = others.chars().next().unwrap();
+					assert!(!rendition.contains(corner));
+				}
+			}
+		}
+		let ascii = board.render_with_border(BorderStyle::Ascii);
+		assert_eq!(ascii.lines().nth(1), Some("+-----+"));
+		let none = board.render_with_border(BorderStyle::None);
+		assert!(!none.chars().any(|c| "┏┓┃┌┐│+-|".contains(c)));
+	}
 
-	/// Invalid property value for well-known property key.
-	InvalidPropertyValue,
+	#[test]
+	fn clone_is_independent_of_original ()
+	{
+		for tsb in SOLVABLE
+		{
+			let original = Board::parse(tsb).unwrap();
+			let mut clone = original.clone();
+			assert_eq!(clone, original);
+			let moves = clone.solve().unwrap();
+			for &p in &moves
+			{
+				clone.force_remove(p);
+			}
+			assert!(clone.is_solved());
+			assert_eq!(original, Board::parse(tsb).unwrap());
+			assert!(!original.is_solved());
+			// The original still solves just as it did.
+			let mut original = original;
+			assert_eq!(original.solve(), Some(moves));
+		}
+	}
 
-	/// Repeated color in [wild stone](WildStone) specification.
-	RepeatedWildColor,
+	#[test]
+	fn equality_ignores_presentation ()
+	{
+		let tsb = "r = 9\n---\nr g g\nr r g\n";
+		let mut board = Board::parse(tsb).unwrap();
+		let expected = Board::parse(tsb).unwrap();
+		let mut equal = false;
+		board.with_highlight((0, 1), &mut |b| equal = *b == expected);
+		assert!(equal);
+		board.set_coordinate_origin(CoordinateOrigin::BottomLeft0);
+		assert_eq!(board, expected);
+		// Display colors matter, however, as do the stones and the turn.
+		assert_ne!(Board::parse("r g g\nr r g\n").unwrap(), expected);
+		let other = Board::parse("r = 9\n---\nr g g\ng r r\n").unwrap();
+		assert_ne!(other, expected);
+		board.force_remove((0, 1));
+		assert_ne!(board, expected);
+	}
 
-	/// Incomplete board, i.e., the last row is not fully populated.
-	IncompleteBoard,
+	#[test]
+	fn builder_agrees_with_parser ()
+	{
+		let red = AnyStone::Ordinary(OrdinaryStone::new('r', 1));
+		let green = AnyStone::Ordinary(OrdinaryStone::new('g', 2));
+		let mut builder = BoardBuilder::new(3, 2);
+		builder.set((0, 0), red)
+			.set((1, 0), green)
+			.set((2, 0), AnyStone::Wild(WildStone::new()))
+			.set((0, 1), red)
+			.set((1, 1), red)
+			.set((2, 1), green)
+			.wild_colors(2)
+			.color_lock(true)
+			.display('r', 9);
+		let tsb = "wild = g\ncolorlock = true\nr = 9\n---\nr g *\nr r g\n";
+		assert_eq!(builder.build().unwrap(), Board::parse(tsb).unwrap());
+		// The same validations apply.
+		assert!(matches!(
+			builder.wild_colors(3).build(),
+			Err(ParseError::WrongWildCount)));
+		assert!(matches!(
+			builder.wild_colors(2).set((2, 1), AnyStone::None(NoStone)).build(),
+			Err(ParseError::NotDivisibleByThree { moves: 5 })));
+	}
 
-	/// Wrong count of [wild stones](WildStone).
-	WrongWildCount
-}
+	#[test]
+	fn fresh_move_clears_redo ()
+	{
+		let tsb = "r g g\nr r g\n";
+		let mut board = Board::parse(tsb).unwrap();
+		board.play_undoable((0, 1), 0);
+		let after_one = board.to_tsb();
+		board.play_undoable((1, 1), 0);
+		assert_eq!(board.turn(), 2);
+		assert!(board.undo());
+		assert_eq!((board.turn(), board.to_tsb()), (1, after_one.clone()));
+		assert!(board.redo());
+		assert_eq!(board.turn(), 2);
+		assert!(board.undo());
+		// Another move forgets the move that was undone.
+		board.play_undoable((0, 0), 0);
+		assert_eq!(board.turn(), 2);
+		assert!(!board.redo());
+		assert!(board.undo());
+		assert_eq!(board.to_tsb(), after_one);
+		assert!(board.undo());
+		assert_eq!(board.turn(), 0);
+		assert_eq!(board, Board::parse(tsb).unwrap());
+	}
 
-impl From<ParseIntError> for ParseError
-{
-	fn from (_error: ParseIntError) -> Self
+	#[test]
+	fn legend_comments_are_ignored ()
 	{
-		ParseError::InvalidPropertyValue
+		let plain = "wild = g\nr = 9\n---\nr r r #\ng g * _\n";
+		let commented = "# A level with a survivor.\nwild = g\n\
+			// The red stones are bright.\nr = 9\n---\nr r r #\ng g * _\n";
+		let board = Board::parse(commented).unwrap();
+		assert_eq!(board, Board::parse(plain).unwrap());
+		// Within the grid, `#` is a survivor.
+		assert_eq!(board.get((3, 0)), Some(AnyStone::Survivor(SurvivorStone)));
+		assert_eq!(board.to_tsb(), plain);
 	}
-}
 
-impl From<ParseBoolError> for ParseError
-{
-	fn from (_error: ParseBoolError) -> Self
+	#[test]
+	fn strict_legend_rejects_unknown_key ()
 	{
-		ParseError::InvalidPropertyValue
+		let board = Board::parse("widht = 3\n---\nr r r\n").unwrap();
+		let key = PropertyKey::Unknown("widht".to_string());
+		let stored = board.properties()
+			.find(|(k, _)| **k == key)
+			.map(|(_, value)| value.clone());
+		assert_eq!(stored, Some(PropertyValue::String("3".to_string())));
+		let error = Board::parse("strict = true\nwidht = 3\n---\nr r r\n")
+			.unwrap_err();
+		assert!(matches!(
+			error,
+			ParseError::UnknownProperty { line: 2, ref key }
+				if key == "widht"));
+		let lenient = "strict = false\nwidht = 3\n---\nr r r\n";
+		assert!(Board::parse(lenient).is_ok());
 	}
-}
 
-/// The token filter for the board legend.
-struct LegendFilter;
+	#[test]
+	fn display_accepts_names_and_hex ()
+	{
+		let tsb = "r = red\ng = #5f87af\nb = 33\n---\nr g b\nr g b\nr g b\n";
+		let board = Board::parse(tsb).unwrap();
+		let rendition = board.render(RenderStyle::Ansi);
+		assert!(rendition.contains("\u{1b}[38;5;9mr"));
+		assert!(rendition.contains("\u{1b}[38;5;67mg"));
+		assert!(rendition.contains("\u{1b}[38;5;33mb"));
+		// The nearest color in the palette stands in for any other.
+		assert_eq!(palette_index("#5f87b0"), Some(67));
+		assert!(board.to_tsb().starts_with("r = 9\ng = 67\nb = 33\n"));
+		assert!(matches!(
+			Board::parse("r = mauve\n---\nr r r\n"),
+			Err(ParseError::InvalidPropertyValue { line: 1 })));
+	}
 
-impl filters::Filter for LegendFilter
-{
-	fn on_char (&self, c: &char) -> (bool, bool)
+	#[test]
+	fn crlf_and_trailing_spaces_are_tolerated ()
 	{
-		match *c
+		let unix = Board::parse("width = 3\n---\nr g g\nr r g\n").unwrap();
+		let windows = Board::parse("width = 3\r\n---\r\nr g g\r\nr r g\r\n")
+			.unwrap();
+		assert_eq!(windows, unix);
+		let padded = Board::parse("width = 3\n---\nr g g   \nr r g \n")
+			.unwrap();
+		assert_eq!(padded, unix);
+		assert_eq!(padded.width(), 3);
+	}
+
+	#[test]
+	fn empty_input_is_rejected ()
+	{
+		for tsb in ["", "  \n\t\n", "width = 3\n---\n", "width = 3\n---\n \n"]
 		{
-			' ' | '\t' => (true, false),
-			'=' | '\n' => (true, true),
-			_ => (false, false)
+			assert!(
+				matches!(Board::parse(tsb), Err(ParseError::EmptyBoard)),
+				"{:?}", tsb);
 		}
 	}
-}
 
-/// A parse expectation for a board legend parser.
-#[derive(PartialEq, Eq)]
-enum LegendParseState
-{
-	/// Expect either a new property key, a linefeed, or end-of-string.
-	ExpectKeyOrLinefeedOrEnd,
+	#[test]
+	fn width_must_fit_the_grid ()
+	{
+		for width in ["0", "7", "4294967295"]
+		{
+			let tsb = format!("width = {}\n---\nr g g\nr r g\n", width);
+			assert!(
+				matches!(
+					Board::parse(&tsb),
+					Err(ParseError::InvalidPropertyValue { line: 1 })),
+				"width = {}", width);
+		}
+		assert!(Board::parse("width = 6\n---\nr g g\nr r g\n").is_ok());
+	}
 
-	/// Expect an equals sign (`=`).
-	ExpectEquals,
+	#[test]
+	fn iterators_visit_every_stone ()
+	{
+		let board = Board::parse("r g g\nr b g\nb r b\n").unwrap();
+		let reds = board.iter_stones()
+			.filter(|(_, s)| matches!(s, AnyStone::Ordinary(o) if o.rep == 'r'))
+			.count();
+		assert_eq!(reds, 3);
+		let points = board.iter_stones()
+			.map(|(p, _)| p)
+			.collect::<Vec<_>>();
+		assert_eq!(points.len(), 9);
+		assert_eq!(points[0], (0, 0));
+		assert_eq!(points[5], (2, 1));
+		assert!(matches!(
+			board.iter_stones().nth(8),
+			Some(((2, 2), AnyStone::Ordinary(o))) if o.rep == 'b'));
+		let rows = board.iter_rows()
+			.map(|row| row.count())
+			.collect::<Vec<_>>();
+		assert_eq!(rows, vec![3, 3, 3]);
+	}
 
-	/// Expect a property value.
-	ExpectValue,
+	#[test]
+	fn get_answers_none_out_of_bounds ()
+	{
+		let board = Board::parse("r g g\nr r g\n").unwrap();
+		assert!(matches!(
+			board.get((1, 0)), Some(AnyStone::Ordinary(o)) if o.rep == 'g'));
+		assert!(matches!(
+			board.get((0, 1)), Some(AnyStone::Ordinary(o)) if o.rep == 'r'));
+		assert!(board.get((3, 0)).is_none());
+		assert!(board.get((0, 2)).is_none());
+		assert!(board.get((u32::MAX, u32::MAX)).is_none());
+	}
 
-	/// Expect a linefeed.
-	ExpectLinefeed
-}
+	#[test]
+	fn removable_stones_must_divide_by_three ()
+	{
+		assert!(matches!(
+			Board::parse("r r r g\n"),
+			Err(ParseError::NotDivisibleByThree { moves: 4 })));
+		assert!(matches!(
+			Board::parse("r r r\ng g _\n"),
+			Err(ParseError::NotDivisibleByThree { moves: 5 })));
+		assert!(matches!(
+			Board::parse("wild = r\n---\nr r * g\n"),
+			Err(ParseError::NotDivisibleByThree { moves: 4 })));
+		// Survivors and toggles are never played, so they do not count.
+		assert!(Board::parse("r r r # /\n").is_ok());
+		assert!(Board::parse("wild = r\n---\nr r *\n").is_ok());
+	}
 
-/// The token filter for the board grid.
-struct StoneFilter;
+	#[test]
+	fn reset_restores_freshly_parsed_board ()
+	{
+		for tsb in SOLVABLE
+		{
+			let fresh = Board::parse(tsb).unwrap();
+			let mut board = fresh.clone();
+			let moves = board.solve().unwrap();
+			for &p in &moves
+			{
+				board.force_remove(p);
+			}
+			assert!(board.is_solved());
+			board.reset();
+			assert_eq!(board, fresh);
+			assert_eq!(board.turn(), 0);
+			assert_eq!(board.wild_colors(), fresh.wild_colors());
+			assert_eq!(board.removable_stones(), fresh.removable_stones());
+			assert_eq!(board.to_tsb(), fresh.to_tsb());
+			assert!(!board.undo());
+		}
+	}
 
-impl filters::Filter for StoneFilter
-{
-	fn on_char (&self, c: &char) -> (bool, bool)
+	#[test]
+	fn toggles_survive_turns_near_overflow ()
 	{
-		match *c
+		let mut board = Board::parse("r r r r r r /2 /3\n").unwrap();
+		let open = |board: &Board, column| match board.get((column, 0))
+		{
+			Some(AnyStone::Toggle(t)) => t.is_open(),
+			stone => panic!("expected a toggle, not {:?}", stone)
+		};
+		// u32::MAX is 3 modulo both 4 and 6.
+		let cases = [
+			(u32::MAX - 3, [true, true]),
+			(u32::MAX - 2, [true, true]),
+			(u32::MAX - 1, [false, true]),
+			(u32::MAX, [false, false])
+		];
+		for (turn, expected) in cases
 		{
-			' ' | '\t' | '\n' => (true, false),
-			_ => (true, true)
+			board.turn = turn;
+			assert_eq!([open(&board, 6), open(&board, 7)], expected);
 		}
+		// The turn saturates rather than wrapping around.
+		board.play_undoable((0, 0), 0);
+		assert_eq!(board.turn(), u32::MAX);
+		assert!(board.undo());
+		assert_eq!(board.turn(), u32::MAX);
+	}
+
+	#[test]
+	fn canonicalize_ignores_color_assignment ()
+	{
+		// Turning the board over keeps the colors of its stones, but the
+		// parser numbers the colors in their new order of appearance.
+		let board = Board::parse("r g g\nr r g\n").unwrap();
+		let turned = board.rotate_cw().rotate_cw();
+		let parsed = Board::parse(&turned.to_tsb()).unwrap();
+		assert_eq!(parsed.to_tsb(), "\n---\ng r r\ng g r\n");
+		assert_ne!(turned, parsed);
+		assert_eq!(turned.canonicalize(), parsed.canonicalize());
+		assert_eq!(parsed.canonicalize(), parsed);
+		// Different structures remain different.
+		let other = Board::parse("r g g\ng r r\n").unwrap();
+		assert_ne!(turned.canonicalize(), other.canonicalize());
+	}
+
+	#[test]
+	fn whole_triplet_is_highlighted_at_once ()
+	{
+		const HIGHLIGHT: &str = "\u{1b}[48;5;231m";
+		let mut board = Board::parse("r g g\nr r g\n").unwrap();
+		let triplet = [(0, 1), (1, 1), (0, 0)];
+		let mut rendition = String::new();
+		board.with_highlight_many(
+			&triplet,
+			&mut |board| rendition = board.render(RenderStyle::Ansi));
+		assert_eq!(rendition.matches(HIGHLIGHT).count(), 3);
+		let highlighted = rendition.split(HIGHLIGHT)
+			.skip(1)
+			.map(|cell| cell.trim_start_matches(|c| c != 'r' && c != 'g'))
+			.map(|cell| cell.chars().next().unwrap())
+			.collect::<String>();
+		assert_eq!(highlighted, "rrr");
+		// The highlight ends with the closure.
+		let rendition = board.render(RenderStyle::Ansi);
+		assert!(!rendition.contains(HIGHLIGHT));
+	}
+
+	#[test]
+	fn grid_chars_agree_with_parser ()
+	{
+		let board = Board::from_grid_chars(&["r g *", "r r g"], "g", true)
+			.unwrap();
+		let tsb = "wild = g\ncolorlock = true\n---\nr g *\nr r g\n";
+		assert_eq!(board, Board::parse(tsb).unwrap());
+		let rows = ["r r r", "", "g g g b b b"];
+		let board = Board::from_grid_chars(&rows, "", false).unwrap();
+		let tsb = "r r r _ _ _\ng g g b b b\n";
+		assert_eq!(board, Board::parse(tsb).unwrap());
+		// Errors report the offending row.
+		assert!(matches!(
+			Board::from_grid_chars(&["r r r", "g g /23"], "", false),
+			Err(ParseError::InvalidStoneCode { line: 2, .. })));
+		assert!(matches!(
+			Board::from_grid_chars(&["r r r"], "r", false),
+			Err(ParseError::WrongWildCount)));
 	}
 }
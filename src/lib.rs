@@ -0,0 +1,135 @@
+//
+// lib.rs
+// Copyright © 2019-2023, Todd L Smith.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+//    this list of conditions and the following disclaimer.
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+//    this list of conditions and the following disclaimer in the documentation
+//    and/or other materials provided with the distribution.
+// 3. Neither the name of the copyright holder nor the names of its contributors
+//    may be used to endorse or promote products derived from this software
+//    without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+//
+
+//!
+//! ## Tumblesolve
+//!
+//! Herein is a solver for [Tumblestone](http://www.tumblestonegame.com/)
+//! puzzles, suitable for embedding within other programs. [Parse](Board::parse)
+//! a [`Board`] from its textual description, then [solve](Board::solve) or
+//! [diagnose](Board::diagnose) it.
+//!
+//! ```
+//! use tumblesolve::{Board, Unsolvable};
+//!
+//! let mut board = Board::parse("r g g\nr r g\n").unwrap();
+//! let solution = board.solve().unwrap();
+//! assert!(board.is_valid_solution(&solution));
+//!
+//! let mut board = Board::parse("g r r\nr g g\n").unwrap();
+//! assert!(matches!(board.diagnose(), Err(Unsolvable::Exhausted)));
+//! ```
+//!
+//! ### Stability
+//!
+//! The following are stable, and will only change incompatibly with a major
+//! version bump:
+//!
+//! * [`Board`] parsing, accessors, and removal of stones;
+//! * the stones: [`AnyStone`], [`Stone`], and the concrete stone types;
+//! * construction by [`BoardBuilder`], and the round trip through
+//!   [`to_tsb`](Board::to_tsb) and [`parse`](Board::parse), or through
+//!   [`to_compact`](Board::to_compact) and
+//!   [`from_compact`](Board::from_compact);
+//! * [`ParseError`], [`Unsolvable`], and [`TimedOut`];
+//! * [`solve`](Board::solve), [`diagnose`](Board::diagnose),
+//!   [`solve_until`](Board::solve_until), and
+//!   [`is_valid_solution`](Board::is_valid_solution).
+//!
+//! Everything else is experimental, and may change as the solver evolves. In
+//! particular, [`SolveStats`] may gain new fields, [`Heuristic`] may gain
+//! new variants, and the ratings of [`Difficulty`] may shift.
+//!
+
+// These macros forward to their namesakes in the `log` crate when the `logging`
+// feature is enabled, and otherwise expand to nothing at all, so that the hot
+// paths of the solver pay nothing for instrumentation. They must precede the
+// module declarations in order to be visible within the modules.
+
+/// Emit a trace record, if logging is enabled.
+macro_rules! trace
+{
+	($($arg:tt)+) => {{ #[cfg(feature = "logging")] log::trace!($($arg)+); }}
+}
+
+/// Emit a debug record, if logging is enabled.
+macro_rules! debug
+{
+	($($arg:tt)+) => {{ #[cfg(feature = "logging")] log::debug!($($arg)+); }}
+}
+
+/// Emit a warning record, if logging is enabled.
+macro_rules! warn
+{
+	($($arg:tt)+) => {{ #[cfg(feature = "logging")] log::warn!($($arg)+); }}
+}
+
+mod board;
+mod solve;
+
+pub use board::{
+	AccessDirection,
+	AnyStone,
+	ArmoredStone,
+	BombStone,
+	Board,
+	BoardBuilder,
+	BorderStyle,
+	CoordinateOrigin,
+	FORMAT_VERSION,
+	GravityRule,
+	MAX_COLORS,
+	NoStone,
+	OrdinaryStone,
+	ParseError,
+	Point,
+	PropertyKey,
+	PropertyMap,
+	PropertyValue,
+	RainbowStone,
+	RenderStyle,
+	Stone,
+	SurvivorRule,
+	SurvivorStone,
+	ToggleStone,
+	WildStone
+};
+pub use solve::{
+	Assist,
+	DEADLINE_CHECK_INTERVAL,
+	Difficulty,
+	ESSENTIAL_COLOR_NODE_LIMIT,
+	Heuristic,
+	IllegalMove,
+	ReplayError,
+	SolveStats,
+	TimedOut,
+	Unsolvable,
+	WILD_COLOR
+};
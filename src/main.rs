@@ -27,60 +27,508 @@
 // POSSIBILITY OF SUCH DAMAGE.
 //
 
-mod board;
-mod solve;
+use clap::{CommandFactory, Parser};
+use clap::error::ErrorKind;
+use flate2::read::GzDecoder;
+use std::fmt::{self, Display, Formatter};
+use std::fs::{read, read_dir, read_to_string, write};
+use std::io::{Error, IsTerminal, Read, stdin, stdout};
+use std::path::Path;
+use std::process::exit;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use tumblesolve::{
+	Board,
+	CoordinateOrigin,
+	DEADLINE_CHECK_INTERVAL,
+	ParseError,
+	Point,
+	RenderStyle,
+	TimedOut
+};
 
-use std::env::args;
-use std::fs::read_to_string;
-use std::io::{Error, stdin};
-use board::{Board, ParseError};
+/// Execute the solver against the board extracted from the command line. The
+/// sole positional argument is the board file (`.tsb`); there are several
+/// example boards in the project `examples` directory. A board file whose name
+/// ends with `.gz` is decompressed with gzip before it is parsed. The board is
+/// read from standard input instead if the argument is `-`, or if it is absent
+/// and standard input is not a terminal. See [`Options`] for the available
+/// flags; [Clap](clap) reports any misuse, along with the usage, and exits.
+///
+/// The exit code is `0` if the board was solved, `1` if it has no solution, or
+/// `2` if some other [error](AppError) occurred, so that the program is usable
+/// in shell conditionals.
+fn main ()
+{
+	let options = Options::from_command_line();
+	let code = match run(&options)
+	{
+		Ok(true) => 0,
+		Ok(false) => 1,
+		Err(error) =>
+		{
+			eprintln!("Error: {}.", error);
+			i32::from(&error)
+		}
+	};
+	exit(code)
+}
 
-/// Execute the solver against the board extracted from the command line. Our
-/// command-line parsing is trivial, so we don't need to bother with, e.g.,
-/// Clap. The sole argument is the board file (`.tsb`); there are several
-/// example boards in the project `examples` directory.
-fn main () -> Result<(), AppError>
+/// Execute the solver as directed by the specified options. Answer `true` if
+/// the board was solved, or `false` if it has no solution.
+fn run (options: &Options) -> Result<bool, AppError>
 {
-	let args: Vec<String> = args().collect();
-	let file = match args.get(1)
+	if let Some(dir) = &options.dir
+	{
+		solve_directory(dir, options.timeout)?;
+		return Ok(true)
+	}
+	let contents = match options.file()
 	{
-		Some(file) => file,
-		None => return Err(AppError::UsageError)
+		"-" =>
+		{
+			let mut contents = String::new();
+			stdin().read_to_string(&mut contents).map_err(|error|
+				AppError::ReadError("standard input".to_string(), error))?;
+			contents
+		},
+		path => read_board(Path::new(path))?
 	};
-    let contents = read_to_string(file)?;
-    let mut board = Board::parse(&contents)?;
-	match board.solve()
+	let mut board = Board::parse(&contents)?;
+	board.set_coordinate_origin(options.origin);
+	if options.verify
+	{
+		return Ok(verify(&mut board, options.style()))
+	}
+	let (result, stats) = match options.timeout
+	{
+		Some(timeout) => board.diagnose_until(
+			Instant::now() + timeout, DEADLINE_CHECK_INTERVAL)?,
+		None => board.diagnose_with_stats()
+	};
+	// The statistics go to standard error, so that they never corrupt the
+	// machine-readable output of `--json`.
+	if options.stats
+	{
+		eprintln!(
+			"{}",
+			paint(
+				options.style(),
+				8,
+				&format!("Search statistics: {}.", stats)));
+	}
+	if let Some(path) = &options.svg
+	{
+		let svg = match &result
+		{
+			Ok(moves) => board.solution_to_svg(moves),
+			Err(_) => board.to_svg()
+		};
+		write(path, svg)
+			.map_err(|error| AppError::WriteError(path.clone(), error))?;
+		if result.is_ok() && !options.json { return Ok(true) }
+	}
+	if options.json
+	{
+		let moves = result.as_ref().ok().map(|moves| moves.iter()
+			.map(|&p| board.display_point(p))
+			.collect::<Vec<_>>());
+		println!("{}", solution_json(moves.as_deref()));
+		return Ok(result.is_ok())
+	}
+	match result
+	{
+		Ok(moves) =>
+		{
+			let colors = board.solution_colors(&moves)
+				.expect("the solver only answers valid solutions");
+			for (index, m) in moves.into_iter().enumerate()
+			{
+				if options.auto.is_some()
+					&& options.style() == RenderStyle::Ansi
+				{
+					print!("{}", CLEAR_SCREEN);
+				}
+				// Between triplets, warn the player if the next triplet cannot
+				// be played without a wild stone.
+				let color = colors[index / 3];
+				if index % 3 == 0 && board.needs_wild()
+				{
+					let name = board.color_rep(color)
+						.map_or("any color".to_string(), |rep|
+							format!("'{}'", rep));
+					println!(
+						"{}",
+						paint(
+							options.style(),
+							8,
+							&format!(
+								"You'll need to use a wild for {}.",
+								name)));
+				}
+				println!("{}", board.advance_hint(m, color, options.style()));
+				match options.auto
+				{
+					Some(millis) => sleep(Duration::from_millis(millis)),
+					None if !options.batch() =>
+					{
+						println!(
+							"Press {} for next hint.",
+							paint(options.style(), 15, "[Enter]"));
+						stdin().read_line(&mut String::new())?;
+					},
+					None => {}
+				}
+			}
+			Ok(true)
+		}
+		Err(reason) =>
+		{
+			println!(
+				"{}",
+				paint(
+					options.style(),
+					11,
+					&format!("No solution exists: {}.", reason)));
+			Ok(false)
+		}
+	}
+}
+
+/// Answer the contents of the specified board file. If its name ends with
+/// `.gz`, then it is decompressed with gzip; otherwise, it is read as plain
+/// text.
+fn read_board (path: &Path) -> Result<String, AppError>
+{
+	let name = || path.display().to_string();
+	if path.extension().is_some_and(|ext| ext == "gz")
+	{
+		let compressed = read(path)
+			.map_err(|error| AppError::ReadError(name(), error))?;
+		let mut contents = String::new();
+		GzDecoder::new(&compressed[..])
+			.read_to_string(&mut contents)
+			.map_err(|error| AppError::DecompressError(name(), error))?;
+		Ok(contents)
+	}
+	else
 	{
-		Some(moves) =>
+		read_to_string(path).map_err(|error| AppError::ReadError(name(), error))
+	}
+}
+
+/// Solve every board file (`.tsb`, or `.tsb.gz` if compressed) in the
+/// specified directory, in order by name, abandoning each search after the
+/// specified timeout, if any. Print a one-line summary for each file,
+/// continuing past files that cannot be read, decompressed, parsed, or solved.
+/// Answer an error if any file was not solved.
+fn solve_directory (
+	dir: &str,
+	timeout: Option<Duration>) -> Result<(), AppError>
+{
+	let mut paths = read_dir(dir)
+		.and_then(|entries| entries
+			.map(|entry| entry.map(|entry| entry.path()))
+			.collect::<Result<Vec<_>, _>>())
+		.map_err(|error| AppError::ReadError(dir.to_string(), error))?;
+	paths.retain(|path| path.file_name()
+		.and_then(|name| name.to_str())
+		.is_some_and(|name|
+			name.ends_with(".tsb") || name.ends_with(".tsb.gz")));
+	paths.sort();
+	let mut failures = 0;
+	for path in &paths
+	{
+		let summary = match read_board(path)
 		{
-			for m in moves
+			Err(AppError::DecompressError(_, error)) =>
+				Err(format!("decompression error: {}", error)),
+			Err(AppError::ReadError(_, error)) =>
+				Err(format!("read error: {}", error)),
+			Err(error) => Err(error.to_string()),
+			Ok(contents) => match Board::parse(&contents)
 			{
-				board.with_highlight(
-					m,
-					&mut |board| println!("{}", board));
-				board.force_remove(m);
-				println!(
-					"Press \u{1b}[38;5;15m[Enter]\u{1b}[0m for next hint.");
-				stdin().read_line(&mut String::new())?;
+				Err(error) => Err(format!("parse error: {}", error)),
+				Ok(mut board) =>
+				{
+					let result = match timeout
+					{
+						Some(timeout) => board
+							.diagnose_until(
+								Instant::now() + timeout,
+								DEADLINE_CHECK_INTERVAL)
+							.map(|(result, _)| result),
+						None => Ok(board.diagnose())
+					};
+					match result
+					{
+						Ok(Ok(moves)) =>
+							Ok(format!("solved in {} turns", moves.len())),
+						Ok(Err(reason)) =>
+							Err(format!("no solution: {}", reason)),
+						Err(_) => Err("timed out".to_string())
+					}
+				}
 			}
+		};
+		let summary = summary.unwrap_or_else(|summary|
+		{
+			failures += 1;
+			summary
+		});
+		println!("{}: {}", path.display(), summary);
+	}
+	match failures
+	{
+		0 => Ok(()),
+		failures => Err(AppError::LevelsFailed(failures))
+	}
+}
+
+/// Verify the specified board, reporting in the specified style. If the
+/// board's footer gives an expected solution, then check that it legally
+/// solves the board. Otherwise, check that the board has some solution. Answer
+/// `true` if the check succeeded.
+fn verify (board: &mut Board, style: RenderStyle) -> bool
+{
+	let (valid, message) = match board.expected_solution()
+	{
+		Some(moves) if board.is_valid_solution(&moves) =>
+			(true, "Expected solution is valid."),
+		Some(_) => (false, "Expected solution is invalid."),
+		None if board.solve().is_some() => (true, "Solution exists."),
+		None => (false, "No solution exists.")
+	};
+	println!("{}", paint(style, if valid { 10 } else { 11 }, message));
+	valid
+}
+
+/// Answer a JSON object that describes the specified solution, if any, for
+/// consumption by other programs. The object has the form
+/// `{"solvable": true, "moves": [[x,y], …], "triplets": [[[x,y], …], …],
+/// "turns": N}`, where the moves are also grouped into triplets, or
+/// `{"solvable": false}` if there is no solution.
+fn solution_json (moves: Option<&[Point]>) -> String
+{
+	let point = |&(x, y): &Point| format!("[{},{}]", x, y);
+	let list = |points: &[Point]|
+		points.iter().map(point).collect::<Vec<_>>().join(",");
+	match moves
+	{
+		Some(moves) => format!(
+			"{{\"solvable\": true, \"moves\": [{}], \"triplets\": [{}], \
+				\"turns\": {}}}",
+			list(moves),
+			moves.chunks(3)
+				.map(|triplet| format!("[{}]", list(triplet)))
+				.collect::<Vec<_>>()
+				.join(","),
+			moves.len()),
+		None => "{\"solvable\": false}".to_string()
+	}
+}
+
+/// The ANSI escape sequence that clears the screen and homes the cursor.
+const CLEAR_SCREEN: &str = "\u{1b}[2J\u{1b}[H";
+
+/// Answer the specified text painted in the specified color of the ANSI
+/// 256-color palette, or just the text if the style is
+/// [plain](RenderStyle::Plain).
+fn paint (style: RenderStyle, color: u8, text: &str) -> String
+{
+	match style
+	{
+		RenderStyle::Ansi => format!("\u{1b}[38;5;{}m{}\u{1b}[0m", color, text),
+		RenderStyle::Plain => text.to_string()
+	}
+}
+
+/// The options specified on the command line.
+#[derive(Parser)]
+#[command(version, about = "Solve a Tumblestone board, one hint at a time.")]
+struct Options
+{
+	/// The path to the board file (`.tsb`, or `.tsb.gz` if compressed), or
+	/// `-` to read the board from standard input. If absent, then the board
+	/// is read from standard input, unless it is a terminal.
+	#[arg(conflicts_with = "dir")]
+	file: Option<String>,
+
+	/// Solve every board file in the specified directory, printing a summary
+	/// of each, instead of solving a single board.
+	#[arg(long, value_name = "PATH")]
+	dir: Option<String>,
+
+	/// Verify the board instead of solving it interactively.
+	#[arg(long)]
+	verify: bool,
+
+	/// Print the search statistics before the first hint.
+	#[arg(long)]
+	stats: bool,
+
+	/// Abandon the search after the specified number of seconds.
+	#[arg(long, value_name = "SECS", value_parser = parse_timeout)]
+	timeout: Option<Duration>,
+
+	/// Render without ANSI escape sequences. This is implied when standard
+	/// output is not a terminal.
+	#[arg(long)]
+	no_color: bool,
+
+	/// Write an SVG image of the solution to the specified path, instead of
+	/// stepping through the hints. If there is no solution, then write an
+	/// image of the board.
+	#[arg(long, value_name = "PATH")]
+	svg: Option<String>,
+
+	/// Print the solution as a JSON object, instead of stepping through the
+	/// hints.
+	#[arg(long)]
+	json: bool,
+
+	/// Print every hint at once, instead of waiting for the user to press
+	/// Enter before each one. This is implied when standard input is not a
+	/// terminal.
+	#[arg(long, visible_alias = "no-interactive")]
+	batch: bool,
+
+	/// Replay the solution as an animation, clearing the screen and then
+	/// drawing each hint in turn, pausing for the specified number of
+	/// milliseconds after each one, instead of waiting for the user to press
+	/// Enter.
+	#[arg(long, value_name = "MILLIS")]
+	auto: Option<u64>,
+
+	/// The origin of the displayed coordinates: `topleft0` (the default),
+	/// `bottomleft0`, or `topleft1`.
+	#[arg(
+		long,
+		value_name = "ORIGIN",
+		default_value = "topleft0",
+		value_parser = parse_origin)]
+	origin: CoordinateOrigin
+}
+
+impl Options
+{
+	/// Parse the options from the command line. If they are malformed, or if
+	/// there is no board to read, then print the usage and exit.
+	fn from_command_line () -> Options
+	{
+		let options = Options::parse();
+		if options.file.is_none() && options.dir.is_none()
+			&& stdin().is_terminal()
+		{
+			Options::command()
+				.error(
+					ErrorKind::MissingRequiredArgument,
+					"a board file is required when standard input is a \
+						terminal")
+				.exit();
 		}
-		None => println!("\u{1b}[38;5;11mNo solution exists.\u{1b}[0m")
+		options
+	}
+
+	/// Answer the path to the board file, or `-` for standard input.
+	fn file (&self) -> &str
+	{
+		self.file.as_deref().unwrap_or("-")
+	}
+
+	/// Answer the style in which to render the board.
+	fn style (&self) -> RenderStyle
+	{
+		if self.no_color || !stdout().is_terminal() { RenderStyle::Plain }
+		else { RenderStyle::Ansi }
 	}
-    Ok(())
+
+	/// Answer whether to print every hint at once.
+	fn batch (&self) -> bool
+	{
+		self.batch || !stdin().is_terminal()
+	}
+}
+
+/// Parse a coordinate origin.
+fn parse_origin (origin: &str) -> Result<CoordinateOrigin, String>
+{
+	origin.parse::<CoordinateOrigin>()
+		.map_err(|_| format!("expected a coordinate origin, not `{}`", origin))
+}
+
+/// Parse a timeout, given as a nonnegative number of seconds.
+fn parse_timeout (secs: &str) -> Result<Duration, String>
+{
+	secs.parse::<f64>()
+		.ok()
+		.filter(|secs| secs.is_finite() && *secs >= 0.0)
+		.map(Duration::from_secs_f64)
+		.ok_or_else(|| format!("expected a number of seconds, not `{}`", secs))
 }
 
 /// An application error.
 #[derive(Debug)]
 enum AppError
 {
-	/// The user has invoked the application incorrectly.
-	UsageError,
-
 	/// An I/O error has occurred.
     IOError (Error),
 
+	/// The specified path could not be read.
+	ReadError (String, Error),
+
+	/// The specified path could not be written.
+	WriteError (String, Error),
+
+	/// The specified path could not be decompressed, e.g., because it is not
+	/// really compressed with gzip.
+	DecompressError (String, Error),
+
 	/// A parse error has occurred.
-    ParseError (ParseError)
+    ParseError (ParseError),
+
+	/// The solver ran past its deadline.
+	TimedOut (TimedOut),
+
+	/// The specified number of board files in a directory could not be
+	/// solved.
+	LevelsFailed (usize)
+}
+
+impl Display for AppError
+{
+	fn fmt (&self, f: &mut Formatter) -> fmt::Result
+	{
+		match self
+		{
+			AppError::IOError(error) => write!(f, "{}", error),
+			AppError::ReadError(path, error) =>
+				write!(f, "could not read {}: {}", path, error),
+			AppError::WriteError(path, error) =>
+				write!(f, "could not write {}: {}", path, error),
+			AppError::DecompressError(path, error) =>
+				write!(f, "could not decompress {}: {}", path, error),
+			AppError::ParseError(error) =>
+				write!(f, "could not parse the board: {}", error),
+			AppError::TimedOut(error) => write!(f, "{}", error),
+			AppError::LevelsFailed(count) =>
+				write!(f, "could not solve {} of the boards", count)
+		}
+	}
+}
+
+impl From<&AppError> for i32
+{
+	/// Answer the process exit code for the specified error: `1` if some
+	/// boards could not be solved, or `2` for any other error.
+	fn from (error: &AppError) -> Self
+	{
+		match error
+		{
+			AppError::LevelsFailed(_) => 1,
+			_ => 2
+		}
+	}
 }
 
 impl From<ParseError> for AppError
@@ -91,6 +539,14 @@ impl From<ParseError> for AppError
    	}
 }
 
+impl From<TimedOut> for AppError
+{
+	fn from (error: TimedOut) -> Self
+	{
+		AppError::TimedOut(error)
+	}
+}
+
 impl From<Error> for AppError
 {
     fn from (error: Error) -> Self
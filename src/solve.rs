@@ -33,6 +33,15 @@
 //! Herein is functionality specific to solving Tumblestone puzzles.
 //!
 
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Write};
+#[cfg(feature = "parallel")]
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use crate::board::*;
 
 /******************************************************************************
@@ -40,156 +49,3656 @@ use crate::board::*;
  ******************************************************************************/
 
 /// The sentinel color of [wild stones](WildStone).
-pub const WILD_COLOR: u32 = 0;
+pub const WILD_COLOR: u64 = 0;
 
 impl Board
 {
-	/// Answer `true` if the receiver is solved, `false` otherwise.
-	fn is_solved (&self) -> bool
+	/// Answer `true` if the receiver is solved, i.e., no [removable] stones
+	/// remain, `false` otherwise. Survivors, toggles, and empty cells may
+	/// remain on a solved board.
+	///
+	/// [removable]: Stone::is_removable
+	pub fn is_solved (&self) -> bool
 	{
 		self.removable_stones() == 0
 	}
 
 	/// Solve the board. Answer the sequences of moves required to solve the
-	/// board, or `None` if the board has no solution.
+	/// board, or `None` if the board has no solution. Use [`diagnose`] to learn
+	/// why a board has no solution.
+	///
+	/// [`diagnose`]: Board::diagnose
 	pub fn solve (&mut self) -> Option<Vec<Point>>
 	{
-		let mut moves = Vec::<Point>::new();
-		match self.solve_recursively(&mut moves, WILD_COLOR, 0, true)
+		self.diagnose().ok()
+	}
+
+	/// Answer the first move of a solution to the board, without revealing
+	/// the rest of the solution, or `None` if the board is already solved or
+	/// has no solution. The board is left unchanged.
+	pub fn hint (&mut self) -> Option<Point>
+	{
+		self.solve()?.first().copied()
+	}
+
+	/// Answer `true` if the receiver is deadlocked, i.e., it is not yet solved,
+	/// but no sequence of moves from its current state can solve it, e.g.,
+	/// because the player made a wrong move. Like the solver, this assumes
+	/// that no triplet is in progress, so ask between triplets, e.g., after
+	/// each [`play`]. The board is left unchanged.
+	///
+	/// [`play`]: Board::play
+	pub fn is_deadlocked (&mut self) -> bool
+	{
+		!self.is_solved() && self.solve().is_none()
+	}
+
+	/// Answer guidance for a player between triplets, e.g., after each
+	/// [`play`]: the [hint], whether the board is [deadlocked], and whether
+	/// the next triplet must include a [wild stone]. A wild stone is needed
+	/// if the board is solvable, but no solution plays the next triplet
+	/// without one; then the guidance also gives the color of the next
+	/// triplet of the hinted solution, e.g., to tell the player "you'll need
+	/// to use a wild for blue." Like the solver, this assumes that no triplet
+	/// is in progress. The board is left unchanged.
+	///
+	/// This comprises one search, plus another that forbids wild stones in
+	/// the next triplet if any wild colors remain; see [`needs_wild`].
+	///
+	/// [`play`]: Board::play
+	/// [hint]: Board::hint
+	/// [deadlocked]: Board::is_deadlocked
+	/// [wild stone]: WildStone
+	/// [`needs_wild`]: Board::needs_wild
+	pub fn solve_interactive_assist (&mut self) -> Assist
+	{
+		let solution = match self.solve()
 		{
-			true if moves.len() % 3 == 0 => Some(moves),
-			_ => None
+			Some(solution) => solution,
+			None => return Assist {
+				hint: None,
+				deadlocked: !self.is_solved(),
+				wild: None
+			}
+		};
+		let hint = solution.first().copied();
+		if hint.is_none() || !self.needs_wild()
+		{
+			return Assist { hint, deadlocked: false, wild: None }
 		}
+		let wild = self.solution_colors(&solution[..3])
+			.ok()
+			.map(|colors| colors[0]);
+		Assist { hint, deadlocked: false, wild }
 	}
 
-	/// Solve the receiver recursively. `moves` is the sequence of moves played
-	/// thus far, `color` is the active color filter, and `allow_wild` is `true`
-	/// iff a [wild stone] may be chosen.
+	/// Answer `true` if any wild colors remain, but no solution plays the next
+	/// triplet without a [wild stone]. The answer is only meaningful for a
+	/// solvable board. This comprises at most one search, so a caller that
+	/// already knows a solution, e.g., an interactive hint loop, can ask
+	/// whether to warn the player without solving the board again. Like the
+	/// solver, this assumes that no triplet is in progress.
 	///
 	/// [wild stone]: WildStone
-	fn solve_recursively (
+	pub fn needs_wild (&mut self) -> bool
+	{
+		if self.wild_colors() == 0 || self.is_solved()
+		{
+			return false
+		}
+		let mut solvable = false;
+		let mut on_solution = |_: &[Point], _|
+		{
+			solvable = true;
+			true
+		};
+		let mut search = Search::new(&mut on_solution);
+		search.wild_free_moves = 3;
+		self.explore(&mut search);
+		!solvable
+	}
+
+	/// Solve the board. Answer the sequence of moves required to solve the
+	/// board, or the reason that the board has no solution. Cheap feasibility
+	/// checks run before the search, so that obviously unsolvable boards are
+	/// rejected immediately.
+	pub fn diagnose (&mut self) -> Result<Vec<Point>, Unsolvable>
+	{
+		self.diagnose_with_stats().0
+	}
+
+	/// Solve the board, as by [`solve`]. Answer both the solution, if any, and
+	/// the [statistics] gathered by the search.
+	///
+	/// [`solve`]: Board::solve
+	/// [statistics]: SolveStats
+	pub fn solve_with_stats (&mut self) -> (Option<Vec<Point>>, SolveStats)
+	{
+		let (result, stats) = self.diagnose_with_stats();
+		(result.ok(), stats)
+	}
+
+	/// Solve the board, as by [`diagnose`]. Answer both the solution or the
+	/// reason that the board has no solution, and the [statistics] gathered by
+	/// the search. If a cheap feasibility check rejects the board, then no
+	/// search is performed and the statistics are all zero.
+	///
+	/// [`diagnose`]: Board::diagnose
+	/// [statistics]: SolveStats
+	pub fn diagnose_with_stats (
+		&mut self
+	) -> (Result<Vec<Point>, Unsolvable>, SolveStats)
+	{
+		self.search_first(None, Heuristic::default())
+			.expect("search without a deadline cannot time out")
+	}
+
+	/// Rate the difficulty of the board, from `0` (trivial) to `100`
+	/// (fiendish), by [solving](Board::diagnose) it once and measuring the
+	/// [effort](SolveStats) that the search required. See [`Difficulty`] for
+	/// the formula. An unsolvable board is rated by the effort required to
+	/// prove that it has no solution, so a board rejected by a cheap
+	/// feasibility check rates `0`. The board is left unchanged.
+	pub fn difficulty (&mut self) -> Difficulty
+	{
+		Difficulty::from(self.diagnose_with_stats().1)
+	}
+
+	/// Solve the board, as by [`solve`], but abandon the search if it runs
+	/// past the specified deadline. The clock is consulted every
+	/// [`DEADLINE_CHECK_INTERVAL`] nodes. The board is fully restored before
+	/// this method returns, even if the search times out.
+	///
+	/// [`solve`]: Board::solve
+	pub fn solve_until (
 		&mut self,
-		moves: &mut Vec<Point>,
-		color: u32,
-		forbidden_color: u32,
-		allow_wild: bool) -> bool
+		deadline: Instant
+	) -> Result<Option<Vec<Point>>, TimedOut>
 	{
-		// If the board has been solved, then return; let the callers deal with
-		// restoring the board to its original state.
-		if self.is_solved()
+		self.diagnose_until(deadline, DEADLINE_CHECK_INTERVAL)
+			.map(|(result, _)| result.ok())
+	}
+
+	/// Solve the board, as by [`diagnose_with_stats`], but abandon the search
+	/// if it runs past the specified deadline. The clock is consulted every
+	/// `check_interval` nodes, so a larger interval reduces the overhead of
+	/// reading the clock at the expense of overshooting the deadline by more.
+	/// The board is fully restored before this method returns, even if the
+	/// search times out.
+	///
+	/// [`diagnose_with_stats`]: Board::diagnose_with_stats
+	pub fn diagnose_until (
+		&mut self,
+		deadline: Instant,
+		check_interval: u64
+	) -> Result<(Result<Vec<Point>, Unsolvable>, SolveStats), TimedOut>
+	{
+		self.search_first(
+			Some((deadline, check_interval.max(1))), Heuristic::default())
+	}
+
+	/// Solve the board, as by [`solve_with_stats`], but try the available
+	/// moves in the order preferred by the specified [heuristic]. A good
+	/// heuristic reaches a solution after visiting fewer nodes, but every
+	/// heuristic finds a solution iff one exists.
+	///
+	/// [`solve_with_stats`]: Board::solve_with_stats
+	/// [heuristic]: Heuristic
+	pub fn solve_with_heuristic (
+		&mut self,
+		heuristic: Heuristic
+	) -> (Option<Vec<Point>>, SolveStats)
+	{
+		let (result, stats) = self.search_first(None, heuristic)
+			.expect("search without a deadline cannot time out");
+		(result.ok(), stats)
+	}
+
+	/// Solve the board, as by [`solve_with_heuristic`], but if the board is
+	/// [mirror symmetric], then skip each first move whose mirror image has
+	/// already been tried, because its outcome is a mirror image of that
+	/// move's outcome. Symmetries that arise later in the search are not
+	/// detected.
+	///
+	/// In [column order], the second half of the frontier mirrors the first,
+	/// so a skipped move would only have been tried after every other move had
+	/// failed; the pruning then roughly halves the search of an unsolvable
+	/// board, but cannot shorten the search for a solution. Other heuristics
+	/// interleave the mirror images, so the pruning can shorten both.
+	///
+	/// [`solve_with_heuristic`]: Board::solve_with_heuristic
+	/// [mirror symmetric]: Board::is_mirror_symmetric
+	/// [column order]: Heuristic::ColumnOrder
+	pub fn solve_with_symmetry (
+		&mut self,
+		heuristic: Heuristic
+	) -> (Option<Vec<Point>>, SolveStats)
+	{
+		if self.check_feasibility().is_err()
 		{
-			return true
+			return (None, SolveStats::default())
 		}
-		// Iterate through all available moves, using the current color and wild
-		// stone permissiveness.
-		let available = self.frontier(color, forbidden_color, allow_wild);
-		for p in available
+		let mut solution = None;
+		let mut on_solution = |moves: &[Point], _|
 		{
-			moves.push(p);
-			let mut stone = AnyStone::None(NoStone);
-			let mut undo = self.remove(p, &mut stone, color);
-			// Update the allowed next color and wild permissiveness based on 1)
-			// whether a triplet is already in progress and 2) the nature of the
-			// stone just removed.
-			let (new_color, new_forbidden_color, new_allow_wild) =
-				if self.turn() % 3 == 0
+			solution = Some(moves.to_vec());
+			true
+		};
+		let mut search = Search::new(&mut on_solution);
+		search.heuristic = heuristic;
+		search.symmetric = self.is_mirror_symmetric();
+		self.explore(&mut search);
+		let stats = search.stats;
+		(solution, stats)
+	}
+
+	/// Solve the board, as by [`solve`], but consider only those solutions
+	/// that begin with the specified move, e.g., to answer a player who asks
+	/// "if I play here, can I still win?" Answer the whole solution,
+	/// including the specified move, or `None` if no solution begins with it,
+	/// e.g., because the move is not [legal] at all. Like the solver, this
+	/// assumes that no triplet is in progress. The board is left unchanged.
+	///
+	/// [`solve`]: Board::solve
+	/// [legal]: Board::legal_moves
+	pub fn solve_from_move (&mut self, first: Point) -> Option<Vec<Point>>
+	{
+		if !self.legal_moves().contains(&first)
+			|| self.check_feasibility().is_err()
+		{
+			return None
+		}
+		let mut solution = None;
+		let mut on_solution = |moves: &[Point], _|
+		{
+			solution = Some(moves.to_vec());
+			true
+		};
+		let mut search = Search::new(&mut on_solution);
+		search.first_move = Some(first);
+		self.explore(&mut search);
+		solution
+	}
+
+	/// Search for the first solution, trying moves in the order preferred by
+	/// the specified heuristic. Abandon the search if it runs past the
+	/// deadline, if any, which is paired with the number of nodes to visit
+	/// between consultations of the clock.
+	fn search_first (
+		&mut self,
+		deadline: Option<(Instant, u64)>,
+		heuristic: Heuristic
+	) -> Result<(Result<Vec<Point>, Unsolvable>, SolveStats), TimedOut>
+	{
+		if let Err(reason) = self.check_feasibility()
+		{
+			return Ok((Err(reason), SolveStats::default()))
+		}
+		let mut solution = None;
+		let mut on_solution = |moves: &[Point], _|
+		{
+			solution = Some(moves.to_vec());
+			true
+		};
+		let mut search = Search::new(&mut on_solution);
+		search.deadline = deadline;
+		search.heuristic = heuristic;
+		self.explore(&mut search);
+		if search.truncated
+		{
+			return Err(TimedOut)
+		}
+		let stats = search.stats;
+		Ok((solution.ok_or(Unsolvable::Exhausted), stats))
+	}
+
+	/// Solve the board, as by [`solve`], but explore the moves of the first
+	/// ply concurrently, each on its own copy of the board, using the global
+	/// rayon thread pool. Answer the first solution found by any thread; the
+	/// other threads are cancelled promptly thereafter. When the board has
+	/// several solutions, the answer may differ from that of [`solve`] and
+	/// may vary from run to run.
+	///
+	/// [`solve`]: Board::solve
+	#[cfg(feature = "parallel")]
+	pub fn solve_parallel (&self) -> Option<Vec<Point>>
+	{
+		self.check_feasibility().ok()?;
+		if self.is_solved()
+		{
+			return Some(vec![])
+		}
+		let cancelled = AtomicBool::new(false);
+		self.commit_colors(self.frontier(WILD_COLOR, true), WILD_COLOR)
+			.into_par_iter()
+			.find_map_any(|(p, color)|
+			{
+				// The undo closures never leave this thread, so each thread is
+				// free to mutate its own copy of the board.
+				let mut board = self.clone();
+				let mut stone = AnyStone::None(NoStone);
+				let _undo = board.remove(p, &mut stone, color);
+				let (color, allow_wild) =
+					board.next_filters(stone, color, true);
+				let mut solution = None;
+				let mut on_solution = |moves: &[Point], _|
+				{
+					solution = Some(moves.to_vec());
+					true
+				};
+				let mut search = Search::new(&mut on_solution);
+				search.moves.push(p);
+				search.bombs = self.has_bombs();
+				search.cancelled = Some(&cancelled);
+				board.solve_recursively(&mut search, color, allow_wild);
+				if solution.is_some()
 				{
-					(
-						WILD_COLOR,
-						if self.color_locked() { color } else { 0 },
-						true
-					)
+					cancelled.store(true, Ordering::Relaxed);
 				}
-				else
+				solution
+			})
+	}
+
+	/// Solve the board, as by [`solve`], but maintain the search on an
+	/// explicit stack rather than the native call stack, so that very large
+	/// boards cannot overflow the latter. Answer the same solution as
+	/// [`solve`].
+	///
+	/// [`solve`]: Board::solve
+	pub fn solve_iterative (&mut self) -> Option<Vec<Point>>
+	{
+		self.check_feasibility().ok()?;
+		#[cfg(debug_assertions)]
+		let snapshot = self.clone();
+		let bombs = self.has_bombs();
+		let mut moves = Vec::<Point>::new();
+		let mut solution = None;
+		let mut frames = vec![Frame {
+			undo: None,
+			available: self.commit_colors(
+				self.frontier(WILD_COLOR, true), WILD_COLOR),
+			next: 0,
+			allow_wild: true
+		}];
+		if self.is_solved()
+		{
+			solution = Some(vec![]);
+		}
+		while solution.is_none()
+		{
+			let frame = match frames.last_mut()
+			{
+				Some(frame) => frame,
+				None => break
+			};
+			match frame.available.get(frame.next)
+			{
+				Some(&(p, color)) =>
 				{
-					match stone
+					// Play the next available move, and push a frame to explore
+					// its consequences.
+					frame.next += 1;
+					let allow_wild = frame.allow_wild;
+					let mut stone = AnyStone::None(NoStone);
+					let undo = self.remove(p, &mut stone, color);
+					moves.push(p);
+					let (color, allow_wild) =
+						self.next_filters(stone, color, allow_wild);
+					frames.push(Frame {
+						undo: Some(undo),
+						available: self.commit_colors(
+							self.frontier(color, allow_wild), color),
+						next: 0,
+						allow_wild
+					});
+					// Absent bombs, every solution has the same length, so if
+					// this one doesn't comprise complete triplets, then no
+					// solution does. Otherwise, keep looking.
+					if self.is_solved()
+					{
+						if moves.len() % 3 == 0
+						{
+							solution = Some(moves.clone());
+						}
+						else if !bombs
+						{
+							break
+						}
+					}
+				},
+				None =>
+				{
+					// Every move available here has been explored, so undo the
+					// move that led here.
+					if let Some(mut undo) = frames.pop().and_then(|f| f.undo)
 					{
-						AnyStone::Ordinary(o) =>
-							(o.color(), forbidden_color, allow_wild),
-						AnyStone::Wild(_) => (color, forbidden_color, false),
-						_ => unreachable!()
+						undo(self);
+						moves.pop();
 					}
-				};
-			// Recurse using the new move sequence, color filter, and wild
-			// permissiveness.
-			if self.solve_recursively(
-				moves,
-				new_color,
-				new_forbidden_color,
-				new_allow_wild)
+				}
+			}
+		}
+		// Undo any moves still in effect, most recent first.
+		while let Some(frame) = frames.pop()
+		{
+			if let Some(mut undo) = frame.undo
 			{
 				undo(self);
-				return true;
 			}
-			// Undo the effects of the latest move prior to playing the next
-			// one.
-			undo(self);
-			moves.truncate(moves.len() - 1);
 		}
-		return false
+		#[cfg(debug_assertions)]
+		assert!(*self == snapshot, "board not restored after solving");
+		solution
 	}
 
-	/// Compute the frontier of the board, i.e., those [stones] which may be
-	/// physically manipulated. Answer the coordinates of all stones that pass
-	/// the specified color and wild filters.
+	/// Apply the cheap feasibility checks that precede a search. Answer the
+	/// reason that the board has no solution, if any of the checks fail.
+	fn check_feasibility (&self) -> Result<(), Unsolvable>
+	{
+		if self.has_stranded_survivor()
+		{
+			return Err(Unsolvable::StrandedSurvivor)
+		}
+		// Bombs remove arbitrarily many stones at once, so none of the counting
+		// arguments below apply to a board with bombs.
+		if self.has_bombs()
+		{
+			return Ok(())
+		}
+		// Armored stones count once per hit.
+		if (self.removable_stones() + self.armor_hits()) % 3 != 0
+		{
+			return Err(Unsolvable::TotalStonesNotDivisibleByThree)
+		}
+		// Rainbow stones join triplets of any color, so the per-color counting
+		// arguments below do not apply to a board with rainbow stones.
+		if self.has_rainbows()
+		{
+			return Ok(())
+		}
+		// Every wild color is consumed exactly once, so every color must have a
+		// multiple of three stones when its wild stone, if any, is included.
+		let census = self.color_census();
+		for (stone, count) in &census
+		{
+			let wild = (self.wild_colors() & stone.color() != 0) as u32;
+			if (count + wild) % 3 != 0
+			{
+				return Err(Unsolvable::ColorCountNotDivisibleByThree(
+					stone.rep()))
+			}
+		}
+		// A wild stone committed to a color absent from the board could never
+		// complete its triplet.
+		let present = census.iter()
+			.fold(0, |present, (stone, _)| present | stone.color());
+		if self.wild_colors() & !present != 0
+		{
+			return Err(Unsolvable::WildParityMismatch)
+		}
+		Ok(())
+	}
+
+	/// Answer `true` if any [bomb](BombStone) remains on the board, `false`
+	/// otherwise.
+	fn has_bombs (&self) -> bool
+	{
+		match self.bitboards()
+		{
+			Some(bitboards) => bitboards.bombs != 0,
+			None => self.has_stone(|stone| matches!(stone, AnyStone::Bomb(_)))
+		}
+	}
+
+	/// Answer `true` if any [rainbow stone](RainbowStone) remains on the
+	/// board, `false` otherwise.
+	fn has_rainbows (&self) -> bool
+	{
+		match self.bitboards()
+		{
+			Some(bitboards) => bitboards.rainbows != 0,
+			None =>
+				self.has_stone(|stone| matches!(stone, AnyStone::Rainbow(_)))
+		}
+	}
+
+	/// Answer `true` if any [stone] on the board satisfies the specified
+	/// predicate, `false` otherwise.
 	///
-	/// [stones]: AnyStone
-	fn frontier (
-		&self,
-		color: u32,
-		forbidden_color: u32,
-		allow_wild: bool) -> Vec<Point>
+	/// [stone]: AnyStone
+	fn has_stone (&self, predicate: fn(&AnyStone) -> bool) -> bool
 	{
-		let mut vec = Vec::<(u32, u32)>::new();
-		let mut next_column ;
-		for column in 0..self.width()
+		let mut found = false;
+		for row in 0..self.height()
+		{
+			for column in 0..self.width()
+			{
+				self.stone_do((column, row), &mut |_, stone|
+					found |= predicate(stone));
+			}
+		}
+		found
+	}
+
+	/// Solve the board exhaustively. Answer every winning sequence of moves,
+	/// in the order discovered, but no more than `limit` of them if a limit is
+	/// given. Unlike [`solve_k`], solutions that differ only by the order of
+	/// moves within some triplet are reported separately. The number of
+	/// solutions can be vast, even for modest boards, so a limit is advisable.
+	///
+	/// [`solve_k`]: Board::solve_k
+	pub fn solve_all (&mut self, limit: Option<usize>) -> Vec<Vec<Point>>
+	{
+		let mut solutions = Vec::<Vec<Point>>::new();
+		let limit = limit.unwrap_or(usize::MAX);
+		if limit == 0
+		{
+			return solutions
+		}
+		self.explore(&mut Search::new(&mut |moves, _|
+		{
+			solutions.push(moves.to_vec());
+			solutions.len() >= limit
+		}));
+		solutions
+	}
+
+	/// Solve the board. Answer up to `k` distinct solutions, stopping the
+	/// search as soon as `k` have been found. Two solutions are distinct iff
+	/// they differ by more than the order of moves within some triplet, i.e.,
+	/// iff they differ once the moves of each triplet are sorted. Under
+	/// [gravity], however, stones fall between the moves of a triplet, so the
+	/// same points in another order can remove other stones; every sequence of
+	/// moves is then distinct.
+	///
+	/// [gravity]: GravityRule::Down
+	pub fn solve_k (&mut self, k: usize) -> Vec<Vec<Point>>
+	{
+		let mut solutions = Vec::<Vec<Point>>::new();
+		if k == 0
+		{
+			return solutions
+		}
+		let falls = self.gravity() == GravityRule::Down;
+		let mut seen = HashSet::<Vec<Point>>::new();
+		self.explore(&mut Search::new(&mut |moves, _|
+		{
+			let key = if falls { moves.to_vec() } else { canonical_key(moves) };
+			if seen.insert(key)
+			{
+				solutions.push(moves.to_vec());
+			}
+			solutions.len() >= k
+		}));
+		solutions
+	}
+
+	/// Solve the board exhaustively, maximizing the score. Answer the solution
+	/// whose moves remove stones worth the most [points] in total, paired with
+	/// its score, or `None` if the board has no solution. Among solutions of
+	/// equal score, answer the first discovered. Stones cleared by [bombs]
+	/// are not played, so they score nothing; absent bombs, every solution
+	/// plays every stone, so every solution scores the same. Like
+	/// [`solve_all`], the search visits every solution, so it can take a very
+	/// long time.
+	///
+	/// [points]: Stone::points
+	/// [bombs]: BombStone
+	/// [`solve_all`]: Board::solve_all
+	pub fn solve_max_score (&mut self) -> Option<(Vec<Point>, u32)>
+	{
+		let mut best = None::<(Vec<Point>, u32)>;
+		self.explore(&mut Search::new(&mut |moves, score|
+		{
+			let better = match &best
+			{
+				Some((_, best)) => score > *best,
+				None => true
+			};
+			if better
+			{
+				best = Some((moves.to_vec(), score));
+			}
+			false
+		}));
+		best
+	}
+
+	/// Solve toward a partial goal, e.g., for a puzzle that asks only that
+	/// certain stones be cleared. Answer a sequence of complete triplets after
+	/// which every cell of the goal is empty, or `None` if there is no such
+	/// sequence. Other stones may remain, and the sequence is not necessarily
+	/// the shortest. A goal that includes a point outside the board or a
+	/// [toggle stone], which is never removed, is unreachable. Under
+	/// [gravity], stones fall into emptied cells, so a cell of the goal must
+	/// remain empty after the last move.
+	///
+	/// [toggle stone]: ToggleStone
+	/// [gravity]: GravityRule
+	pub fn solve_goal (&mut self, goal: &[Point]) -> Option<Vec<Point>>
+	{
+		let reachable = goal.iter()
+			.all(|&p| !matches!(self.get(p), None | Some(AnyStone::Toggle(_))));
+		if !reachable
+		{
+			return None
+		}
+		let is_goal = |board: &Board| goal.iter()
+			.all(|&p| matches!(board.get(p), Some(AnyStone::None(_))));
+		let mut solution = None;
+		let mut on_solution = |moves: &[Point], _|
+		{
+			solution = Some(moves.to_vec());
+			true
+		};
+		let mut search = Search::new(&mut on_solution);
+		search.goal = Some(&is_goal);
+		self.explore(&mut search);
+		solution
+	}
+
+	/// Solve the board, as by [`solve`], but write a trace of the search to
+	/// the specified writer, e.g., to learn why the search fails or takes so
+	/// long. Each move tried writes a line like `try (0, 6) color=0x8`, and
+	/// each move undone writes a line like `undo (0, 6)`, both indented by the
+	/// number of moves that precede them. The cheap feasibility checks are
+	/// skipped, so that even an obviously unsolvable board is traced. The
+	/// search is abandoned if the trace cannot be written. The board is fully
+	/// restored before this method returns.
+	///
+	/// [`solve`]: Board::solve
+	pub fn solve_traced (
+		&mut self,
+		out: &mut dyn Write
+	) -> io::Result<Option<Vec<Point>>>
+	{
+		let mut solution = None;
+		let mut on_solution = |moves: &[Point], _|
+		{
+			solution = Some(moves.to_vec());
+			true
+		};
+		let mut search = Search::new(&mut on_solution);
+		search.trace = Some(out);
+		self.explore(&mut search);
+		match search.trace_error
+		{
+			Some(e) => Err(e),
+			None => Ok(solution)
+		}
+	}
+
+	/// Solve the board, as by [`solve`], but in at most `max_moves` moves.
+	/// Answer `None` if the board has no such solution. Absent [bombs], every
+	/// solution comprises the same number of moves, so a lower limit is
+	/// rejected without a search; otherwise, the search abandons any sequence
+	/// of moves that grows too long.
+	///
+	/// [`solve`]: Board::solve
+	/// [bombs]: BombStone
+	pub fn solve_within (&mut self, max_moves: usize) -> Option<Vec<Point>>
+	{
+		let required = (self.removable_stones() + self.armor_hits()) as usize;
+		if !self.has_bombs() && required > max_moves
+		{
+			return None
+		}
+		if self.check_feasibility().is_err()
+		{
+			return None
+		}
+		let mut solution = None;
+		let mut on_solution = |moves: &[Point], _|
+		{
+			solution = Some(moves.to_vec());
+			true
+		};
+		let mut search = Search::new(&mut on_solution);
+		search.move_limit = max_moves;
+		self.explore(&mut search);
+		solution
+	}
+
+	/// Solve the board, as by [`solve`], but without ever playing a [wild
+	/// stone], e.g., to prove that a board is solvable even ignoring its wild
+	/// stones, or for a challenge that forbids them. Wild stones are treated
+	/// as immovable obstacles, so the board counts as solved once every other
+	/// removable stone is gone. Answer `None` if the board has no such
+	/// solution. The cheap feasibility checks assume that wild stones are
+	/// played, so they are skipped.
+	///
+	/// [`solve`]: Board::solve
+	/// [wild stone]: WildStone
+	pub fn solve_without_wilds (&mut self) -> Option<Vec<Point>>
+	{
+		let wilds = self.iter_stones()
+			.filter(|(_, stone)| matches!(stone, AnyStone::Wild(_)))
+			.count() as u32;
+		let mut solution = None;
+		let mut on_solution = |moves: &[Point], _|
+		{
+			solution = Some(moves.to_vec());
+			true
+		};
+		let mut search = Search::new(&mut on_solution);
+		search.forbid_wild = true;
+		search.frozen_stones = wilds;
+		self.explore(&mut search);
+		solution
+	}
+
+	/// Answer `true` if the board has exactly one distinct solution, `false`
+	/// otherwise. Solutions that differ only by the order of moves within some
+	/// triplet clear the same stones in the same triplets, so they are deemed
+	/// the same solution; see [`solve_k`]. The search stops as soon as a second
+	/// distinct solution is found.
+	///
+	/// [`solve_k`]: Board::solve_k
+	pub fn is_uniquely_solvable (&mut self) -> bool
+	{
+		self.solve_k(2).len() == 1
+	}
+
+	/// Answer the colors that are essential to solving the board, i.e., those
+	/// colors without which the board cannot be solved. A color is essential
+	/// if treating all of its stones as immovable obstacles renders the rest
+	/// of the board unsolvable. Answer an empty vector if the board has no
+	/// solution at all.
+	///
+	/// This is a diagnostic for puzzle designers, and it is expensive: it
+	/// comprises one unconstrained search plus one constrained search per
+	/// color present. To bound the cost, each constrained search may visit at
+	/// most [`ESSENTIAL_COLOR_NODE_LIMIT`] nodes; a color whose search
+	/// exceeds this budget is conservatively deemed inessential.
+	pub fn colors_blocking_solution (&mut self) -> Vec<u64>
+	{
+		if self.solve().is_none()
+		{
+			return vec![]
+		}
+		// Frozen stones are never struck, so count stones rather than matches.
+		self.color_census()
+			.into_iter()
+			.map(|(stone, _)| stone.color())
+			.map(|color| (color, self.stones_of_color(color)))
+			.collect::<Vec<_>>()
+			.into_iter()
+			.filter(|&(color, count)|
+			{
+				let mut solved = false;
+				let mut on_solution = |_: &[Point], _|
+				{
+					solved = true;
+					true
+				};
+				let mut search = Search::new(&mut on_solution);
+				search.frozen_colors = color;
+				search.frozen_stones = count;
+				search.node_limit = ESSENTIAL_COLOR_NODE_LIMIT;
+				self.explore(&mut search);
+				let truncated = search.truncated;
+				!solved && !truncated
+			})
+			.map(|(color, _)| color)
+			.collect()
+	}
+
+	/// Answer a histogram of the colors of the [ordinary stones] on the board,
+	/// mapping each [color] present to the number of matches required to clear
+	/// its stones. An [armored stone] requires one match per hit. Absent
+	/// [bombs] and [rainbow stones], the board is solvable only if every count,
+	/// plus one if the color is also a [wild color], is a multiple of three;
+	/// the solver checks this before it begins its search.
+	///
+	/// [ordinary stones]: OrdinaryStone
+	/// [color]: OrdinaryStone::color
+	/// [armored stone]: ArmoredStone
+	/// [bombs]: BombStone
+	/// [rainbow stones]: RainbowStone
+	/// [wild color]: Board::wild_colors
+	pub fn color_histogram (&self) -> HashMap<u64, u32>
+	{
+		self.color_census()
+			.into_iter()
+			.map(|(stone, count)| (stone.color(), count))
+			.collect()
+	}
+
+	/// Answer the number of [ordinary stones] that remain on the board, by
+	/// [color]. Unlike a [histogram], each [armored stone] counts once, no
+	/// matter how many hits remain. Wild stones are not counted; their colors
+	/// are given by [`wild_colors`].
+	///
+	/// [ordinary stones]: OrdinaryStone
+	/// [color]: OrdinaryStone::color
+	/// [histogram]: Board::color_histogram
+	/// [armored stone]: ArmoredStone
+	/// [`wild_colors`]: Board::wild_colors
+	pub fn remaining_by_color (&self) -> HashMap<u64, u32>
+	{
+		if let Some(bitboards) = self.bitboards()
+		{
+			return bitboards.colors.iter()
+				.filter(|&&(_, cells)| cells != 0)
+				.map(|&(color, cells)| (color, cells.count_ones()))
+				.collect()
+		}
+		let mut remaining = HashMap::new();
+		for (_, stone) in self.iter_stones()
+		{
+			let color = match stone
+			{
+				AnyStone::Ordinary(o) => o.color(),
+				AnyStone::Armored(a) => a.color(),
+				_ => continue
+			};
+			*remaining.entry(color).or_insert(0) += 1;
+		}
+		remaining
+	}
+
+	/// Answer a census of the [ordinary stones] on the board, as pairs of the
+	/// first stone of each color, in row-major order, and the number of
+	/// matches required to clear the stones of that color. The matches exceed
+	/// the stones when some of the stones are [armored].
+	///
+	/// [ordinary stones]: OrdinaryStone
+	/// [armored]: ArmoredStone
+	fn color_census (&self) -> Vec<(OrdinaryStone, u32)>
+	{
+		if let Some(bitboards) = self.bitboards()
+		{
+			// The lowest bit of each color's cells is its first stone.
+			let mut colors = bitboards.colors.iter()
+				.map(|&(_, cells)| cells)
+				.filter(|&cells| cells != 0)
+				.collect::<Vec<_>>();
+			colors.sort_by_key(|cells| cells.trailing_zeros());
+			return colors.into_iter()
+				.map(|cells|
+				{
+					let first = bitboards.points(cells).next().unwrap();
+					let mut stone = None;
+					self.stone_do(first, &mut |_, s| stone = match s
+					{
+						AnyStone::Ordinary(o) => Some(*o),
+						AnyStone::Armored(a) => Some(a.stone()),
+						_ => None
+					});
+					let hits = self.armor_hits_among(cells & bitboards.armored);
+					(stone.unwrap(), cells.count_ones() + hits)
+				})
+				.collect()
+		}
+		let mut census = Vec::<(OrdinaryStone, u32)>::new();
+		for row in 0..self.height()
 		{
-			for row in (0..self.height()).rev()
+			for column in 0..self.width()
 			{
-				next_column = false;
 				self.stone_do((column, row), &mut |_, stone|
 				{
-					match stone
+					let (o, hits) = match stone
 					{
-						AnyStone::None(_) => {},
-						AnyStone::Ordinary(_)
-							if forbidden_color != 0
-								&& color == forbidden_color =>
-							next_column = true,
-						AnyStone::Ordinary(_) if color == 0 =>
-						{
-							vec.push((column, row));
-							next_column = true;
-						}
-						AnyStone::Ordinary(o @ OrdinaryStone {..})
-							if o.color() == color =>
-						{
-							vec.push((column, row));
-							next_column = true;
-						},
-						AnyStone::Ordinary(_) => next_column = true,
-						AnyStone::Survivor(_) => next_column = true,
-						AnyStone::Wild(_) if !allow_wild => next_column = true,
-						AnyStone::Wild(_) if color == 0 =>
-						{
-							vec.push((column, row));
-							next_column = true;
-						},
-						AnyStone::Wild(_) if color & self.wild_colors() != 0 =>
-						{
-							vec.push((column, row));
-							next_column = true;
-						},
-						AnyStone::Wild(_) => next_column = true,
-						AnyStone::Toggle(toggle) =>
-						{
-							next_column = !toggle.is_open();
-						},
+						AnyStone::Ordinary(o) => (*o, 1),
+						AnyStone::Armored(a) =>
+							(a.stone(), a.hits_remaining() as u32),
+						_ => return
+					};
+					match census.iter_mut()
+						.find(|(s, _)| s.color() == o.color())
+					{
+						Some((_, count)) => *count += hits,
+						None => census.push((o, hits))
 					}
 				});
-				if next_column { break }
 			}
 		}
-		vec
+		census
+	}
+
+	/// Answer the number of hits required by [armored stones] beyond the one
+	/// hit that every stone requires.
+	///
+	/// [armored stones]: ArmoredStone
+	fn armor_hits (&self) -> u32
+	{
+		if let Some(bitboards) = self.bitboards()
+		{
+			return self.armor_hits_among(bitboards.armored)
+		}
+		let mut hits = 0;
+		for row in 0..self.height()
+		{
+			for column in 0..self.width()
+			{
+				self.stone_do((column, row), &mut |_, stone|
+				{
+					if let AnyStone::Armored(a) = stone
+					{
+						hits += a.hits_remaining() as u32 - 1;
+					}
+				});
+			}
+		}
+		hits
+	}
+
+	/// Answer the number of hits required by the [armored stones] among the
+	/// cells of the specified mask of the [bitboards], beyond the one hit that
+	/// every stone requires.
+	///
+	/// [armored stones]: ArmoredStone
+	/// [bitboards]: Bitboards
+	fn armor_hits_among (&self, cells: u128) -> u32
+	{
+		let mut hits = 0;
+		if let Some(bitboards) = self.bitboards()
+		{
+			for p in bitboards.points(cells)
+			{
+				self.stone_do(p, &mut |_, stone|
+				{
+					if let AnyStone::Armored(a) = stone
+					{
+						hits += a.hits_remaining() as u32 - 1;
+					}
+				});
+			}
+		}
+		hits
+	}
+
+	/// Answer the number of [ordinary] and [armored] stones of the specified
+	/// color.
+	///
+	/// [ordinary]: OrdinaryStone
+	/// [armored]: ArmoredStone
+	fn stones_of_color (&self, color: u64) -> u32
+	{
+		if let Some(bitboards) = self.bitboards()
+		{
+			return bitboards.color(color).count_ones()
+		}
+		let mut stones = 0;
+		for row in 0..self.height()
+		{
+			for column in 0..self.width()
+			{
+				self.stone_do((column, row), &mut |_, stone|
+				{
+					match stone
+					{
+						AnyStone::Ordinary(o) if o.color() == color =>
+							stones += 1,
+						AnyStone::Armored(a) if a.color() == color =>
+							stones += 1,
+						_ => {}
+					}
+				});
+			}
+		}
+		stones
+	}
+
+	/// Search the board for solutions, as directed by the specified search.
+	/// The board is fully restored before this method returns.
+	fn explore (&mut self, search: &mut Search)
+	{
+		// In debug builds, verify that the search leaves no trace upon the
+		// board. A mismatch means that some removal was not faithfully undone.
+		#[cfg(debug_assertions)]
+		let snapshot = self.clone();
+		debug!(
+			"search started: {}×{} board, {} removable stones",
+			self.width(),
+			self.height(),
+			self.removable_stones());
+		let start = Instant::now();
+		search.bombs = self.has_bombs();
+		self.solve_recursively(search, WILD_COLOR, true);
+		search.stats.elapsed = start.elapsed();
+		debug!("search finished: {}", search.stats);
+		#[cfg(debug_assertions)]
+		assert!(*self == snapshot, "board not restored after solving");
+	}
+
+	/// Solve the receiver recursively. `search` tracks the sequence of moves
+	/// played thus far, `color` is the active color filter, and `allow_wild`
+	/// is `true` iff a [wild stone] may be chosen. Answer `true` if the search
+	/// should stop, `false` if it should continue.
+	///
+	/// [wild stone]: WildStone
+	fn solve_recursively (
+		&mut self,
+		search: &mut Search,
+		color: u64,
+		allow_wild: bool) -> bool
+	{
+		// Abandon the search if it has exhausted its budget.
+		let stats = &mut search.stats;
+		stats.nodes_visited += 1;
+		stats.max_depth = stats.max_depth.max(search.moves.len() as u32);
+		if stats.nodes_visited > search.node_limit
+		{
+			search.truncated = true;
+			return true
+		}
+		// Abandon the search if some sibling search has already succeeded.
+		#[cfg(feature = "parallel")]
+		if let Some(cancelled) = search.cancelled
+		{
+			if cancelled.load(Ordering::Relaxed)
+			{
+				search.truncated = true;
+				return true
+			}
+		}
+		// Abandon the search if it has run past its deadline. Reading the clock
+		// is comparatively expensive, so only do so periodically.
+		if let Some((deadline, check_interval)) = search.deadline
+		{
+			if stats.nodes_visited % check_interval == 0
+				&& Instant::now() >= deadline
+			{
+				search.truncated = true;
+				return true
+			}
+		}
+		// If the board has been solved, then report the solution; let the
+		// callers deal with restoring the board to its original state. Absent
+		// bombs, every solution has the same length, so if this one doesn't
+		// comprise complete triplets, then no solution does. Bombs clear
+		// stones without playing them, so then another solution might be
+		// shorter or longer. Frozen stones are never removed, so they are
+		// excluded from consideration. A partial goal may be met in the midst
+		// of a triplet, in which case the triplet must still be completed.
+		let solved = match (search.goal, search.frozen_stones)
+		{
+			(Some(goal), _) => goal(self),
+			(None, 0) => self.is_solved(),
+			(None, frozen) => self.removable_stones() == frozen
+		};
+		if solved
+		{
+			if search.moves.len() % 3 == 0
+			{
+				return (search.on_solution)(&search.moves, search.score)
+			}
+			if search.goal.is_none()
+			{
+				return !search.bombs
+			}
+		}
+		// Abandon the branch if another move would exceed the limit.
+		if search.moves.len() >= search.move_limit
+		{
+			return false
+		}
+		// Iterate through all available moves, using the current color and wild
+		// stone permissiveness, in the order preferred by the heuristic. A
+		// frozen stone still obstructs the stones above it, so simply dropping
+		// it from the frontier is sufficient. The column order is just the
+		// order of the frontier, so consume the frontier lazily, a lane at a
+		// time, exactly as frontier_iter does; every move is undone before
+		// the next lane is examined. A wild stone that begins a triplet
+		// commits the triplet to one of its colors, so it is tried once for
+		// each. On a symmetric board, a first move whose mirror image has
+		// already been tried is skipped, as is any first move other than a
+		// pinned one.
+		let wild_ok = allow_wild
+			&& !search.forbid_wild
+			&& search.moves.len() >= search.wild_free_moves;
+		let mut lanes = 0..self.lane_count();
+		let mut ordered = match search.heuristic
+		{
+			Heuristic::ColumnOrder => None,
+			heuristic => Some(
+				self.frontier_ordered(color, wild_ok, heuristic).into_iter())
+		};
+		let prune = search.symmetric && search.moves.is_empty();
+		let pinned = search.first_move.filter(|_| search.moves.is_empty());
+		let mut tried = Vec::<(Point, u64)>::new();
+		while let Some(p) = match ordered.as_mut()
+		{
+			Some(ordered) => ordered.next(),
+			None => lanes.find_map(|lane|
+				self.lane_frontier(lane, color, wild_ok))
+		}
+		{
+			for color in self.committed_colors(p, color)
+			{
+				if pinned.is_some_and(|first| first != p)
+				{
+					continue
+				}
+				if prune
+				{
+					if tried.contains(&(self.mirror(p), color))
+					{
+						continue
+					}
+					tried.push((p, color));
+				}
+				if search.frozen_colors != 0 && self.is_frozen(p, search)
+				{
+					continue
+				}
+				trace!("{:depth$}play {:?}", "", p, depth = search.moves.len());
+				if search.trace.is_some() && !search.write_trace(
+					format_args!("try {:?} color={:#x}", p, color))
+				{
+					return true
+				}
+				search.moves.push(p);
+				let mut stone = AnyStone::None(NoStone);
+				let mut undo = self.remove(p, &mut stone, color);
+				search.score += stone.points();
+				let (new_color, new_allow_wild) =
+					self.next_filters(stone, color, allow_wild);
+				// Recurse using the new move sequence, color filter, and wild
+				// permissiveness.
+				let stop = self.solve_recursively(
+					search,
+					new_color,
+					new_allow_wild);
+				// Undo the effects of the latest move prior to playing the next
+				// one.
+				#[cfg(test)]
+				if search.skip_undo
+				{
+					return true
+				}
+				undo(self);
+				search.score -= stone.points();
+				search.moves.pop();
+				trace!("{:depth$}undo {:?}", "", p, depth = search.moves.len());
+				if search.trace.is_some()
+					&& !search.write_trace(format_args!("undo {:?}", p))
+				{
+					return true
+				}
+				if stop
+				{
+					return true
+				}
+				search.stats.backtracks += 1;
+			}
+		}
+		false
+	}
+
+	/// Answer the stones that could join the [ordinary stone] at the specified
+	/// point in a triplet, including that stone itself, i.e., every stone of
+	/// the same color that is either on the frontier now or would be exposed by
+	/// the other moves of some triplet that includes the specified stone. Wild
+	/// stones are not considered. If the answer contains fewer than three
+	/// stones, then the specified stone cannot presently be matched. Answer an
+	/// empty vector if the specified point does not hold an ordinary stone.
+	///
+	/// [ordinary stone]: OrdinaryStone
+	pub fn same_color_cluster (&self, p: Point) -> Vec<Point>
+	{
+		let mut color = 0;
+		self.stone_do(p, &mut |_, stone|
+		{
+			match stone
+			{
+				AnyStone::Ordinary(o) => color = o.color(),
+				AnyStone::Armored(a) => color = a.color(),
+				_ => {}
+			}
+		});
+		let mut cluster = Vec::<Point>::new();
+		if color != 0
+		{
+			self.clone().cluster_recursively(
+				p, color, &mut Vec::new(), &mut cluster);
+		}
+		cluster
+	}
+
+	/// Explore every sequence of up to three moves of the specified color that
+	/// extends `sequence`, adding to `cluster` the moves of each sequence that
+	/// includes `p`.
+	fn cluster_recursively (
+		&mut self,
+		p: Point,
+		color: u64,
+		sequence: &mut Vec<Point>,
+		cluster: &mut Vec<Point>)
+	{
+		if sequence.contains(&p)
+		{
+			for q in sequence.iter()
+			{
+				if !cluster.contains(q)
+				{
+					cluster.push(*q);
+				}
+			}
+		}
+		if sequence.len() == 3
+		{
+			return
+		}
+		for q in self.frontier(color, false)
+		{
+			sequence.push(q);
+			let mut stone = AnyStone::None(NoStone);
+			let mut undo = self.remove(q, &mut stone, color);
+			self.cluster_recursively(p, color, sequence, cluster);
+			undo(self);
+			sequence.pop();
+		}
+	}
+
+	/// Pair each of the specified moves with the color to which it commits the
+	/// triplet in progress, given the color filter that applies to the moves.
+	/// Only a [wild stone] that begins a triplet has a choice, so it is paired
+	/// with each of its colors that the [color lock] permits, in ascending
+	/// order; every other move is paired with the color filter itself.
+	///
+	/// [wild stone]: WildStone
+	/// [color lock]: Board::locked_color
+	fn commit_colors (
+		&self,
+		moves: Vec<Point>,
+		color: u64) -> Vec<(Point, u64)>
+	{
+		moves.into_iter()
+			.flat_map(|p| self.committed_colors(p, color)
+				.map(move |color| (p, color)))
+			.collect()
+	}
+
+	/// Answer the colors to which the move at the specified point can commit
+	/// the triplet in progress, as described by [`commit_colors`]. The answer
+	/// does not borrow the receiver, so the move may be played while the
+	/// colors are consumed.
+	///
+	/// [`commit_colors`]: Board::commit_colors
+	fn committed_colors (
+		&self,
+		p: Point,
+		color: u64) -> impl Iterator<Item=u64>
+	{
+		let wild = match self.get(p)
+		{
+			Some(AnyStone::Wild(w)) if color == WILD_COLOR =>
+				Some(w.colors(self) & !self.locked_color()),
+			_ => None
+		};
+		(0..u64::BITS)
+			.map(|bit| 1 << bit)
+			.filter(move |&committed|
+				wild.is_some_and(|colors| colors & committed != 0))
+			.chain(wild.is_none().then_some(color))
+	}
+
+	/// Answer the color filter and wild permissiveness that
+	/// apply to the next move, given those that applied to the move that just
+	/// removed the specified [stone].
+	///
+	/// [stone]: AnyStone
+	fn next_filters (
+		&self,
+		stone: AnyStone,
+		color: u64,
+		allow_wild: bool) -> (u64, bool)
+	{
+		// Update the allowed next color and wild permissiveness based on 1)
+		// whether a triplet is already in progress and 2) the nature of the
+		// stone just removed.
+		if self.turn() % 3 == 0
+		{
+			(WILD_COLOR, true)
+		}
+		else
+		{
+			match stone
+			{
+				AnyStone::Ordinary(o) => (o.color(), allow_wild),
+				AnyStone::Armored(a) => (a.color(), allow_wild),
+				AnyStone::Wild(_) => (color, false),
+				AnyStone::Bomb(_) => (color, allow_wild),
+				AnyStone::Rainbow(_) => (color, allow_wild),
+				_ => unreachable!()
+			}
+		}
+	}
+
+	/// Answer `true` if the specified moves legally solve the board, `false`
+	/// otherwise. Every move must be available when it is played, and the
+	/// moves must clear the board in complete triplets. The board is fully
+	/// restored before this method returns. Use [`verify_solution`] to learn
+	/// why the moves do not solve the board.
+	///
+	/// [`verify_solution`]: Board::verify_solution
+	pub fn is_valid_solution (&mut self, moves: &[Point]) -> bool
+	{
+		self.verify_solution(moves).is_ok()
+	}
+
+	/// Replay the specified moves, e.g., a solution produced by another
+	/// program, and check that they legally solve the board. Every move must
+	/// be available when it is played, i.e., on the frontier, matching the
+	/// color of its triplet, and obeying the color lock. The moves must clear
+	/// the board in complete triplets. If they do not, then answer the reason.
+	/// The board is fully restored before this method returns.
+	pub fn verify_solution (
+		&mut self,
+		moves: &[Point]) -> Result<(), ReplayError>
+	{
+		let mut undos = Vec::with_capacity(moves.len());
+		let (mut color, mut allow_wild) = (WILD_COLOR, true);
+		let mut result = Ok(());
+		for index in 0..moves.len()
+		{
+			let end = moves.len().min((index / 3 + 1) * 3);
+			match self.replay_move(&moves[index..end], color, allow_wild)
+			{
+				Ok((undo, next_color, next_allow_wild)) =>
+				{
+					undos.push(undo);
+					(color, allow_wild) = (next_color, next_allow_wild);
+				},
+				Err(reason) =>
+				{
+					result = Err(ReplayError::IllegalMove { index, reason });
+					break
+				}
+			}
+		}
+		if result.is_ok()
+		{
+			if moves.len() % 3 != 0
+			{
+				result = Err(ReplayError::IncompleteTriplet);
+			}
+			else if !self.is_solved()
+			{
+				result = Err(ReplayError::Unsolved {
+					remaining: self.removable_stones()
+				});
+			}
+		}
+		for mut undo in undos.into_iter().rev()
+		{
+			undo(self);
+		}
+		result
+	}
+
+	/// Replay the specified moves, e.g., a [solution], and answer the color of
+	/// each of their triplets, in order, e.g., as a roadmap of the colors
+	/// that a player will need. The color of a triplet that begins with a
+	/// [wild stone] is the color to which the wild stone commits it. A
+	/// triplet of [bombs] and wild stones alone may have no color of its own,
+	/// in which case its color is [`WILD_COLOR`]. A trailing incomplete
+	/// triplet is included. If some move is illegal, then answer the reason,
+	/// just as for [`verify_solution`], but the moves need not solve the
+	/// board. The board is fully restored before this method returns.
+	///
+	/// [solution]: Board::solve
+	/// [wild stone]: WildStone
+	/// [bombs]: BombStone
+	/// [`verify_solution`]: Board::verify_solution
+	pub fn solution_colors (
+		&mut self,
+		moves: &[Point]) -> Result<Vec<u64>, ReplayError>
+	{
+		let mut undos = Vec::with_capacity(moves.len());
+		let (mut color, mut allow_wild) = (WILD_COLOR, true);
+		let mut colors = Vec::with_capacity(moves.len().div_ceil(3));
+		let mut result = Ok(());
+		for index in 0..moves.len()
+		{
+			if index % 3 == 0
+			{
+				colors.push(WILD_COLOR);
+			}
+			let triplet = colors.last_mut().unwrap();
+			if *triplet == WILD_COLOR
+			{
+				*triplet = match self.get(moves[index])
+				{
+					Some(AnyStone::Ordinary(o)) => o.color(),
+					Some(AnyStone::Armored(a)) => a.color(),
+					_ => WILD_COLOR
+				};
+			}
+			let end = moves.len().min((index / 3 + 1) * 3);
+			match self.replay_move(&moves[index..end], color, allow_wild)
+			{
+				Ok((undo, next_color, next_allow_wild)) =>
+				{
+					undos.push(undo);
+					(color, allow_wild) = (next_color, next_allow_wild);
+					// Within a triplet, the color filter is the color that
+					// the triplet has established, e.g., by a wild stone.
+					if *triplet == WILD_COLOR && index % 3 != 2
+					{
+						*triplet = color;
+					}
+				},
+				Err(reason) =>
+				{
+					result = Err(ReplayError::IllegalMove { index, reason });
+					break
+				}
+			}
+		}
+		for mut undo in undos.into_iter().rev()
+		{
+			undo(self);
+		}
+		result.map(|_| colors)
+	}
+
+	/// Answer the stones that a player may remove to begin the next triplet,
+	/// i.e., the [frontier] without any color filter.
+	///
+	/// [frontier]: Board::frontier
+	pub fn legal_moves (&self) -> Vec<Point>
+	{
+		self.frontier(WILD_COLOR, true)
+	}
+
+	/// Answer the [colors] in which the next triplet may begin, in ascending
+	/// order, i.e., the colors of the [ordinary] and [wild] stones on the
+	/// [frontier], less any color forbidden by the [color lock].
+	///
+	/// [colors]: OrdinaryStone::color
+	/// [ordinary]: OrdinaryStone
+	/// [wild]: WildStone
+	/// [frontier]: Board::legal_moves
+	/// [color lock]: Board::locked_color
+	pub fn playable_colors (&self) -> Vec<u64>
+	{
+		let colors = self.legal_moves()
+			.into_iter()
+			.filter_map(|p| self.get(p))
+			.fold(0, |colors, stone| colors | match stone
+			{
+				AnyStone::Ordinary(o) => o.color(),
+				AnyStone::Armored(a) => a.color(),
+				AnyStone::Wild(w) => w.colors(self),
+				_ => 0
+			}) & !self.locked_color();
+		(0..u64::BITS)
+			.map(|bit| 1 << bit)
+			.filter(|color| colors & color != 0)
+			.collect()
+	}
+
+	/// Play the specified triplet, just as a player would, removing its
+	/// stones in order and thereby advancing the turn by three. Every stone
+	/// must be on the frontier when it is played, and the stones must share a
+	/// color, as permitted by the wild stones and the color lock. If the
+	/// triplet is illegal, then answer the reason, leaving the board
+	/// unchanged.
+	pub fn play (&mut self, triplet: [Point; 3]) -> Result<(), IllegalMove>
+	{
+		if self.turn() % 3 != 0
+		{
+			return Err(IllegalMove::TripletInProgress)
+		}
+		let mut undos = Vec::<Undo>::with_capacity(triplet.len());
+		let (mut color, mut allow_wild) = (WILD_COLOR, true);
+		for index in 0..triplet.len()
+		{
+			match self.replay_move(&triplet[index..], color, allow_wild)
+			{
+				Ok((undo, next_color, next_allow_wild)) =>
+				{
+					undos.push(undo);
+					(color, allow_wild) = (next_color, next_allow_wild);
+				},
+				Err(reason) =>
+				{
+					for mut undo in undos.into_iter().rev()
+					{
+						undo(self);
+					}
+					return Err(reason)
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Play the first of the specified moves, given the color filter and wild
+	/// permissiveness that apply to it. The rest of the moves complete the
+	/// triplet in progress, and decide the color to which a [wild stone] that
+	/// begins the triplet commits it: the first of its colors with which all
+	/// of the moves are legal, if any. Answer the action that undoes the move,
+	/// together with the color filter and wild permissiveness that apply to
+	/// the next move, or else the reason that the move is illegal.
+	///
+	/// [wild stone]: WildStone
+	fn replay_move (
+		&mut self,
+		moves: &[Point],
+		color: u64,
+		allow_wild: bool) -> Result<(Undo, u64, bool), IllegalMove>
+	{
+		let p = moves[0];
+		let mut colors = self.commit_colors(vec![p], color)
+			.into_iter()
+			.map(|(_, color)| color)
+			.collect::<Vec<_>>();
+		if colors.len() > 1
+		{
+			if let Some(index) = colors.iter()
+				.position(|&color| self.is_playable(moves, color, allow_wild))
+			{
+				colors.swap(0, index);
+			}
+		}
+		let color = match colors.first()
+		{
+			Some(&committed) => committed,
+			// A wild stone whose every color is locked cannot begin a triplet.
+			None if self.frontier(color, allow_wild).contains(&p) =>
+				return Err(IllegalMove::ColorLockViolation(p)),
+			None => return Err(self.why_illegal(p, color))
+		};
+		if !self.frontier(color, allow_wild).contains(&p)
+		{
+			return Err(self.why_illegal(p, color))
+		}
+		let mut stone = AnyStone::None(NoStone);
+		let undo = self.remove(p, &mut stone, color);
+		let (color, allow_wild) = self.next_filters(stone, color, allow_wild);
+		Ok((undo, color, allow_wild))
+	}
+
+	/// Answer `true` if the specified moves can be played in order, given the
+	/// color filter and wild permissiveness that apply to the first of them,
+	/// `false` otherwise. The board is left unchanged.
+	fn is_playable (
+		&mut self,
+		moves: &[Point],
+		mut color: u64,
+		mut allow_wild: bool) -> bool
+	{
+		let mut undos = Vec::<Undo>::with_capacity(moves.len());
+		let mut playable = true;
+		for &p in moves
+		{
+			if !self.frontier(color, allow_wild).contains(&p)
+			{
+				playable = false;
+				break
+			}
+			let mut stone = AnyStone::None(NoStone);
+			undos.push(self.remove(p, &mut stone, color));
+			(color, allow_wild) = self.next_filters(stone, color, allow_wild);
+		}
+		for mut undo in undos.into_iter().rev()
+		{
+			undo(self);
+		}
+		playable
+	}
+
+	/// Answer the reason that the [stone] at the specified point cannot be
+	/// played now, given the color established for the triplet in progress.
+	/// The stone is known to be excluded from the frontier.
+	///
+	/// [stone]: AnyStone
+	fn why_illegal (&self, p: Point, color: u64) -> IllegalMove
+	{
+		if p.0 >= self.width() || p.1 >= self.height()
+		{
+			return IllegalMove::NotOnFrontier(p)
+		}
+		let mut stone = AnyStone::None(NoStone);
+		self.stone_do(p, &mut |_, s| stone = *s);
+		// An armored stone is played just like the ordinary stone beneath its
+		// armor.
+		if let AnyStone::Armored(a) = stone
+		{
+			stone = AnyStone::Ordinary(a.stone());
+		}
+		// Consult the frontier for the stone's own color, which includes the
+		// stone iff it is physically accessible.
+		let accessible = match stone
+		{
+			AnyStone::Ordinary(o) =>
+				self.frontier(o.color(), true).contains(&p),
+			AnyStone::Wild(_) =>
+				self.frontier(WILD_COLOR, true).contains(&p),
+			// A rainbow stone passes every established color filter, so any
+			// color will do.
+			AnyStone::Rainbow(_) =>
+				self.frontier(!WILD_COLOR, true).contains(&p),
+			_ => false
+		};
+		match stone
+		{
+			_ if !accessible => IllegalMove::NotOnFrontier(p),
+			AnyStone::Ordinary(o)
+				if color == WILD_COLOR && o.color() == self.locked_color() =>
+				IllegalMove::ColorLockViolation(p),
+			_ => IllegalMove::ColorMismatch(p)
+		}
+	}
+
+	/// Answer `true` if the [stone] at the specified point is frozen by the
+	/// specified search, i.e., it must not be removed.
+	///
+	/// [stone]: AnyStone
+	fn is_frozen (&self, p: Point, search: &Search) -> bool
+	{
+		let mut frozen = false;
+		self.stone_do(p, &mut |_, stone|
+		{
+			match stone
+			{
+				AnyStone::Ordinary(o) =>
+					frozen = o.color() & search.frozen_colors != 0,
+				AnyStone::Armored(a) =>
+					frozen = a.color() & search.frozen_colors != 0,
+				_ => {}
+			}
+		});
+		frozen
+	}
+
+	/// Compute the [frontier] of the board, as ordered by the specified
+	/// [heuristic]. Moves that the heuristic deems equally promising retain
+	/// their relative order.
+	///
+	/// [frontier]: Board::frontier
+	/// [heuristic]: Heuristic
+	fn frontier_ordered (
+		&self,
+		color: u64,
+		allow_wild: bool,
+		heuristic: Heuristic) -> Vec<Point>
+	{
+		let mut frontier = self.frontier(color, allow_wild);
+		match heuristic
+		{
+			Heuristic::ColumnOrder => {},
+			Heuristic::AbundantColorsFirst =>
+			{
+				// Wild stones are the most flexible stones of all, so save them
+				// for last.
+				let census = self.color_census();
+				frontier.sort_by_key(|&p|
+				{
+					let mut abundance = 0;
+					self.stone_do(p, &mut |_, stone|
+					{
+						let color = match stone
+						{
+							AnyStone::Ordinary(o) => o.color(),
+							AnyStone::Armored(a) => a.color(),
+							_ => return
+						};
+						abundance = census.iter()
+							.find(|(s, _)| s.color() == color)
+							.map_or(0, |&(_, count)| count);
+					});
+					Reverse(abundance)
+				});
+			},
+			Heuristic::SurvivorRowsFirst =>
+			{
+				// Prefer the rows whose survivors are nearest to disappearing,
+				// i.e., those with the fewest other stones remaining.
+				let rows = (0..self.height())
+					.map(|row| self.survivor_pressure(row))
+					.collect::<Vec<_>>();
+				frontier.sort_by_key(|&(_, row)| rows[row as usize]);
+			}
+		}
+		frontier
+	}
+
+	/// Answer the number of removable stones remaining in the specified row,
+	/// or `u32::MAX` if the row has no [survivors].
+	///
+	/// [survivors]: SurvivorStone
+	fn survivor_pressure (&self, row: u32) -> u32
+	{
+		if let Some(bitboards) = self.bitboards()
+		{
+			let cells = bitboards.row(row);
+			let others = bitboards.colors.iter()
+				.fold(bitboards.wilds, |others, &(_, mask)| others | mask);
+			return match bitboards.survivors & cells
+			{
+				0 => u32::MAX,
+				_ => (others & cells).count_ones()
+			}
+		}
+		let mut survivors = false;
+		let mut others = 0;
+		for column in 0..self.width()
+		{
+			self.stone_do((column, row), &mut |_, stone|
+			{
+				match stone
+				{
+					AnyStone::Ordinary(_)
+						| AnyStone::Armored(_)
+						| AnyStone::Wild(_) => others += 1,
+					AnyStone::Survivor(_) => survivors = true,
+					_ => {}
+				}
+			});
+		}
+		if survivors { others } else { u32::MAX }
+	}
+
+	/// Answer `true` if some [survivor] is provably stranded, i.e., if it can
+	/// never disappear, no matter how the remaining stones are played, or
+	/// `false` otherwise. A survivor obstructs the stones beyond it in its
+	/// lane, and it only disappears once the stones of its row (or column,
+	/// if the [survivor rule] permits) are gone, so some stone that it
+	/// obstructs may be the very stone that it awaits. This is only possible
+	/// if the lanes are rows, i.e., if stones are [accessed] from the left or
+	/// the right, because otherwise a survivor only awaits the stones nearer
+	/// than itself to the side of access.
+	///
+	/// The answer is conservative. Every accessible stone is played
+	/// optimistically, as though colors, toggles, and armor were no object,
+	/// until no more stones can be played; a survivor that still remains is
+	/// stranded. Playing stones only ever helps this optimistic play, so no
+	/// sequence of moves can strand a survivor that is not stranded already,
+	/// and the check need only precede the search. [Gravity] could bring an
+	/// obstructed stone within reach, so a board with gravity is never said
+	/// to have a stranded survivor.
+	///
+	/// [survivor]: SurvivorStone
+	/// [survivor rule]: SurvivorRule
+	/// [accessed]: AccessDirection
+	/// [Gravity]: GravityRule::Down
+	pub fn has_stranded_survivor (&self) -> bool
+	{
+		if self.gravity() == GravityRule::Down
+			|| self.bitboards().is_some_and(|b| b.survivors == 0)
+		{
+			return false
+		}
+		let (width, height) = (self.width(), self.height());
+		let lanes = match self.access()
+		{
+			AccessDirection::FromBottom => (0..width)
+				.map(|column| (0..height).rev().map(|row| (column, row))
+					.collect::<Vec<_>>())
+				.collect::<Vec<_>>(),
+			AccessDirection::FromTop => (0..width)
+				.map(|column| (0..height).map(|row| (column, row)).collect())
+				.collect(),
+			AccessDirection::FromLeft => (0..height)
+				.map(|row| (0..width).map(|column| (column, row)).collect())
+				.collect(),
+			AccessDirection::FromRight => (0..height)
+				.map(|row| (0..width).rev().map(|column| (column, row))
+					.collect())
+				.collect()
+		};
+		let index = |(column, row): Point| (row * width + column) as usize;
+		let mut cells = (0..height)
+			.flat_map(|row| (0..width).map(move |column| (column, row)))
+			.map(|p| self.get(p).unwrap_or(AnyStone::None(NoStone)))
+			.collect::<Vec<_>>();
+		let is_removable = |cells: &[AnyStone], p: Point|
+			cells[index(p)].is_removable();
+		loop
+		{
+			// Play the nearest stone of every lane. A bomb also clears the
+			// ordinary stones and bombs around it, just as it would in play.
+			let mut played = false;
+			for lane in &lanes
+			{
+				let p = match lane.iter().copied().find(|&p|
+					!matches!(cells[index(p)],
+						AnyStone::None(_) | AnyStone::Toggle(_)))
+				{
+					Some(p) if is_removable(&cells, p) => p,
+					_ => continue
+				};
+				played = true;
+				let mut bombs = vec![];
+				if let AnyStone::Bomb(_) = cells[index(p)]
+				{
+					bombs.push(p);
+				}
+				cells[index(p)] = AnyStone::None(NoStone);
+				while let Some((column, row)) = bombs.pop()
+				{
+					let columns = column.saturating_sub(1)
+						..=(column + 1).min(width - 1);
+					for y in row.saturating_sub(1)..=(row + 1).min(height - 1)
+					{
+						for x in columns.clone()
+						{
+							match cells[index((x, y))]
+							{
+								AnyStone::Ordinary(_) => {},
+								AnyStone::Bomb(_) => bombs.push((x, y)),
+								_ => continue
+							}
+							cells[index((x, y))] = AnyStone::None(NoStone);
+						}
+					}
+				}
+			}
+			// Sweep away the survivors that have been released.
+			for (column, row) in
+				(0..height).flat_map(|row| (0..width).map(move |c| (c, row)))
+			{
+				if !matches!(cells[index((column, row))], AnyStone::Survivor(_))
+				{
+					continue
+				}
+				let released = (0..width)
+					.all(|x| !is_removable(&cells, (x, row)))
+					|| self.survivor_rule() == SurvivorRule::RowOrColumn
+						&& (0..height)
+							.all(|y| !is_removable(&cells, (column, y)));
+				if released
+				{
+					cells[index((column, row))] = AnyStone::None(NoStone);
+					played = true;
+				}
+			}
+			if !played
+			{
+				break
+			}
+		}
+		cells.iter().any(|stone| matches!(stone, AnyStone::Survivor(_)))
+	}
+
+	/// Compute the frontier of the board, i.e., those [stones] which may be
+	/// physically manipulated. Answer the coordinates of all stones that pass
+	/// the specified color and wild filters, in lane order, i.e., in column
+	/// order or in row order, according to the board's [access direction].
+	///
+	/// The color filter is the [color] that the triplet in progress has
+	/// established, if any. [`WILD_COLOR`] means that no color has been
+	/// established, i.e., that a triplet is about to begin or that only wild
+	/// stones have been played so far; stones of every color pass the filter,
+	/// except that stones of the board's [locked color] are excluded. Any
+	/// other color admits only stones of that color, and wild stones that can
+	/// provide it. When `allow_wild` is `false`, no wild stones pass the
+	/// filter, e.g., because the triplet in progress already includes one.
+	/// [Bombs] are colorless, so they always pass the filters. [Rainbow
+	/// stones] are colorless too, but they pass only an established color
+	/// filter, because they cannot begin a triplet.
+	///
+	/// A [restricted] wild stone provides only those of the board's wild
+	/// colors that are also its own, so it passes the color filter only if
+	/// it can provide the filter color; a restricted wild stone that can no
+	/// longer provide any color never passes. Note that a wild stone that
+	/// begins a triplet does not establish the triplet's color, so its
+	/// restriction does not constrain the stones that follow it.
+	///
+	/// Stones that are buried behind other stones, or hidden behind closed
+	/// [toggles], are never on the frontier.
+	///
+	/// [stones]: AnyStone
+	/// [access direction]: Board::access
+	/// [color]: OrdinaryStone::color
+	/// [locked color]: Board::locked_color
+	/// [toggles]: ToggleStone
+	/// [Bombs]: BombStone
+	/// [Rainbow stones]: RainbowStone
+	/// [restricted]: WildStone::restriction
+	pub fn frontier (&self, color: u64, allow_wild: bool) -> Vec<Point>
+	{
+		self.frontier_iter(color, allow_wild).collect()
+	}
+
+	/// Answer an iterator over the [frontier] of the board, which yields the
+	/// same points in the same order, but lazily, without allocating.
+	///
+	/// [frontier]: Board::frontier
+	pub fn frontier_iter (
+		&self,
+		color: u64,
+		allow_wild: bool) -> impl Iterator<Item=Point> + '_
+	{
+		(0..self.lane_count()).filter_map(move |lane|
+			self.lane_frontier(lane, color, allow_wild))
+	}
+
+	/// Answer the number of lanes of the board, i.e., of columns or rows,
+	/// according to its [access direction].
+	///
+	/// [access direction]: AccessDirection
+	fn lane_count (&self) -> u32
+	{
+		match self.access()
+		{
+			AccessDirection::FromBottom | AccessDirection::FromTop =>
+				self.width(),
+			AccessDirection::FromLeft | AccessDirection::FromRight =>
+				self.height()
+		}
+	}
+
+	/// Answer the point of the specified lane that is on the [frontier] for
+	/// the specified color and wild filters, if any. A lane is a column or a
+	/// row, according to the board's [access direction].
+	///
+	/// [frontier]: Board::frontier
+	/// [access direction]: AccessDirection
+	fn lane_frontier (
+		&self,
+		lane: u32,
+		color: u64,
+		allow_wild: bool) -> Option<Point>
+	{
+		let (width, height) = (self.width(), self.height());
+		match self.access()
+		{
+			AccessDirection::FromBottom =>
+			{
+				// Skip the empty cells at the bottom of the column.
+				let lowest = self.lowest_stone_row(lane)?;
+				self.scan_frontier(
+					(0..=lowest).rev().map(|row| (lane, row)),
+					color,
+					allow_wild)
+			},
+			AccessDirection::FromTop => self.scan_frontier(
+				(0..height).map(|row| (lane, row)), color, allow_wild),
+			AccessDirection::FromLeft => self.scan_frontier(
+				(0..width).map(|column| (column, lane)), color, allow_wild),
+			AccessDirection::FromRight => self.scan_frontier(
+				(0..width).rev().map(|column| (column, lane)),
+				color,
+				allow_wild)
+		}
+	}
+
+	/// Scan the specified points of a lane, which are ordered from the side
+	/// of the board from which stones are accessed, and answer the first
+	/// point that is on the [frontier] for the specified color and wild
+	/// filters, if any. The scan ends at the first stone that is not empty
+	/// and not an open toggle, whether or not it passes the filters.
+	///
+	/// [frontier]: Board::frontier
+	fn scan_frontier (
+		&self,
+		lane: impl Iterator<Item=Point>,
+		color: u64,
+		allow_wild: bool) -> Option<Point>
+	{
+		let mut found = None;
+		let mut next_lane;
+		for p in lane
+		{
+			next_lane = false;
+			self.stone_do(p, &mut |_, stone|
+			{
+				match stone
+				{
+					AnyStone::None(_) => {},
+					AnyStone::Ordinary(o)
+						if color == 0 && o.color() == self.locked_color() =>
+						next_lane = true,
+					AnyStone::Ordinary(_) if color == 0 =>
+					{
+						found = Some(p);
+						next_lane = true;
+					}
+					AnyStone::Ordinary(o @ OrdinaryStone {..})
+						if o.color() == color =>
+					{
+						found = Some(p);
+						next_lane = true;
+					},
+					AnyStone::Ordinary(_) => next_lane = true,
+					AnyStone::Armored(a)
+						if color == 0 && a.color() == self.locked_color() =>
+						next_lane = true,
+					AnyStone::Armored(_) if color == 0 =>
+					{
+						found = Some(p);
+						next_lane = true;
+					}
+					AnyStone::Armored(a) if a.color() == color =>
+					{
+						found = Some(p);
+						next_lane = true;
+					},
+					AnyStone::Armored(_) => next_lane = true,
+					AnyStone::Survivor(_) => next_lane = true,
+					AnyStone::Wild(_) if !allow_wild => next_lane = true,
+					AnyStone::Wild(w) if color == 0 && w.colors(self) != 0 =>
+					{
+						found = Some(p);
+						next_lane = true;
+					},
+					AnyStone::Wild(w) if color & w.colors(self) != 0 =>
+					{
+						found = Some(p);
+						next_lane = true;
+					},
+					AnyStone::Wild(_) => next_lane = true,
+					AnyStone::Bomb(_) =>
+					{
+						found = Some(p);
+						next_lane = true;
+					},
+					AnyStone::Rainbow(_) if color != 0 =>
+					{
+						found = Some(p);
+						next_lane = true;
+					},
+					AnyStone::Rainbow(_) => next_lane = true,
+					AnyStone::Toggle(toggle) =>
+					{
+						next_lane = !toggle.is_open();
+					},
+				}
+			});
+			if next_lane { break }
+		}
+		found
+	}
+}
+
+/// The reason that a triplet cannot be [played](Board::play).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum IllegalMove
+{
+	/// The stone at the specified point is not on the frontier, e.g., because
+	/// it is buried beneath other stones, hidden by a closed toggle, or not a
+	/// removable stone at all.
+	NotOnFrontier (Point),
+
+	/// The stone at the specified point does not match the color of the
+	/// triplet in progress.
+	ColorMismatch (Point),
+
+	/// The stone at the specified point begins a triplet in the color that
+	/// the color lock forbids.
+	ColorLockViolation (Point),
+
+	/// Some stones have been removed individually since the last complete
+	/// triplet, so a new triplet cannot begin.
+	TripletInProgress
+}
+
+impl Display for IllegalMove
+{
+	fn fmt (&self, f: &mut Formatter) -> fmt::Result
+	{
+		match self
+		{
+			IllegalMove::NotOnFrontier((column, row)) => write!(
+				f, "the stone at ({}, {}) is not on the frontier", column, row),
+			IllegalMove::ColorMismatch((column, row)) => write!(
+				f,
+				"the stone at ({}, {}) does not match the color of the triplet",
+				column,
+				row),
+			IllegalMove::ColorLockViolation((column, row)) => write!(
+				f,
+				"the stone at ({}, {}) is of the locked color",
+				column,
+				row),
+			IllegalMove::TripletInProgress => write!(
+				f, "a triplet is already in progress")
+		}
+	}
+}
+
+/// The reason that a sequence of moves does not
+/// [solve](Board::verify_solution) a board.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ReplayError
+{
+	/// The move at the specified 0-based index cannot be played, for the
+	/// specified reason.
+	IllegalMove
+	{
+		/// The 0-based index of the offending move.
+		index: usize,
+
+		/// The reason that the move cannot be played.
+		reason: IllegalMove
+	},
+
+	/// Every move can be played, but the last triplet is incomplete.
+	IncompleteTriplet,
+
+	/// Every move can be played, but the specified number of removable
+	/// stones remain.
+	Unsolved
+	{
+		/// The number of removable stones that remain.
+		remaining: u32
+	}
+}
+
+impl Display for ReplayError
+{
+	fn fmt (&self, f: &mut Formatter) -> fmt::Result
+	{
+		match self
+		{
+			ReplayError::IllegalMove { index, reason } =>
+				write!(f, "move {} is illegal: {}", index, reason),
+			ReplayError::IncompleteTriplet =>
+				write!(f, "the last triplet is incomplete"),
+			ReplayError::Unsolved { remaining } => write!(
+				f, "{} removable stones remain after the last move", remaining)
+		}
+	}
+}
+
+/******************************************************************************
+ *                                Diagnosis.                                  *
+ ******************************************************************************/
+
+/// The reason that a board has no solution, as determined by [diagnosis].
+///
+/// [diagnosis]: Board::diagnose
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Unsolvable
+{
+	/// The number of stones of the specified color, including its wild stone,
+	/// if any, is not a multiple of three.
+	ColorCountNotDivisibleByThree (char),
+
+	/// The number of removable stones is not a multiple of three.
+	TotalStonesNotDivisibleByThree,
+
+	/// Some wild color has no ordinary stones with which to form a triplet.
+	WildParityMismatch,
+
+	/// Some [survivor](SurvivorStone) obstructs a stone that must be removed
+	/// before the survivor can disappear.
+	StrandedSurvivor,
+
+	/// The search exhausted every sequence of moves without finding a
+	/// solution.
+	Exhausted
+}
+
+impl Display for Unsolvable
+{
+	fn fmt (&self, f: &mut Formatter) -> fmt::Result
+	{
+		match self
+		{
+			Unsolvable::ColorCountNotDivisibleByThree(c) => write!(
+				f, "the number of '{}' stones is not a multiple of three", c),
+			Unsolvable::TotalStonesNotDivisibleByThree => write!(
+				f, "the number of removable stones is not a multiple of three"),
+			Unsolvable::WildParityMismatch => write!(
+				f, "some wild color has no stones to match"),
+			Unsolvable::StrandedSurvivor => write!(
+				f, "some survivor obstructs the stones that would clear it"),
+			Unsolvable::Exhausted => write!(
+				f, "every sequence of moves leads to a dead end")
+		}
+	}
+}
+
+/// The search ran past its deadline before it could decide whether the board
+/// has a solution.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct TimedOut;
+
+impl Display for TimedOut
+{
+	fn fmt (&self, f: &mut Formatter) -> fmt::Result
+	{
+		write!(f, "the search ran past its deadline")
+	}
+}
+
+/******************************************************************************
+ *                              Search support.                               *
+ ******************************************************************************/
+
+/// The maximum number of nodes that each constrained search may visit when
+/// [determining essential colors].
+///
+/// [determining essential colors]: Board::colors_blocking_solution
+pub const ESSENTIAL_COLOR_NODE_LIMIT: u64 = 1_000_000;
+
+/// The default number of nodes that a search with a deadline visits between
+/// consultations of the clock.
+pub const DEADLINE_CHECK_INTERVAL: u64 = 4096;
+
+/// The order in which a search tries the available moves.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub enum Heuristic
+{
+	/// Try the moves in column-then-row order, i.e., the order of the
+	/// frontier itself.
+	#[default]
+	ColumnOrder,
+
+	/// Try the stones of the colors with the most stones remaining first, and
+	/// wild stones last. Abundant colors tend to bury the most stones, and
+	/// also offer the most ways to form triplets.
+	AbundantColorsFirst,
+
+	/// Try the stones of the rows whose [survivors] are nearest to
+	/// disappearing first, in order to expose the stones that they obstruct.
+	///
+	/// [survivors]: SurvivorStone
+	SurvivorRowsFirst
+}
+
+/// The mutable state of a search in progress.
+struct Search<'a>
+{
+	/// The sequence of moves played thus far.
+	moves: Vec<Point>,
+
+	/// The action to perform whenever a solution is discovered, given the
+	/// solution and its score. It answers `true` to stop the search, or
+	/// `false` to continue searching.
+	on_solution: &'a mut dyn FnMut(&[Point], u32) -> bool,
+
+	/// The sum of the [points](Stone::points) of the stones removed by the
+	/// moves played thus far.
+	score: u32,
+
+	/// The order in which to try the available moves.
+	heuristic: Heuristic,
+
+	/// The predicate that recognizes a solved board, if other than
+	/// [`is_solved`](Board::is_solved).
+	goal: Option<&'a dyn Fn(&Board) -> bool>,
+
+	/// The bitwise OR of the colors whose stones must not be removed.
+	frozen_colors: u64,
+
+	/// The number of stones that must not be removed, e.g., those frozen by
+	/// [`frozen_colors`] or, if [`forbid_wild`] is set, the wild stones. The
+	/// board is solved once only these stones remain.
+	///
+	/// [`frozen_colors`]: Search::frozen_colors
+	/// [`forbid_wild`]: Search::forbid_wild
+	frozen_stones: u32,
+
+	/// `true` if [wild stones](WildStone) must never be played, regardless of
+	/// the triplet in progress, `false` otherwise.
+	forbid_wild: bool,
+
+	/// The number of moves at the start of the search that must not play
+	/// [wild stones](WildStone), regardless of the triplet in progress.
+	wild_free_moves: usize,
+
+	/// The move with which every solution must begin, if any.
+	first_move: Option<Point>,
+
+	/// `true` if the board was [mirror symmetric] when the search began, in
+	/// which case the mirror images of first moves are pruned.
+	///
+	/// [mirror symmetric]: Board::is_mirror_symmetric
+	symmetric: bool,
+
+	/// The statistics gathered thus far.
+	stats: SolveStats,
+
+	/// The maximum number of nodes to visit before abandoning the search.
+	node_limit: u64,
+
+	/// The maximum number of moves in a solution.
+	move_limit: usize,
+
+	/// The time after which to abandon the search, if any, paired with the
+	/// number of nodes to visit between consultations of the clock.
+	deadline: Option<(Instant, u64)>,
+
+	/// `true` if the board had any [bombs](BombStone) when the search began,
+	/// in which case solutions may differ in length.
+	bombs: bool,
+	/// The flag that, once set, directs the search to stop, if any. Sibling
+	/// searches running on other threads share the flag.
+	#[cfg(feature = "parallel")]
+	cancelled: Option<&'a AtomicBool>,
+
+	/// The destination of the [trace](Board::solve_traced), if any.
+	trace: Option<&'a mut dyn Write>,
+
+	/// The error that interrupted the trace, if any.
+	trace_error: Option<io::Error>,
+
+	/// `true` if the search should abandon its first move without undoing
+	/// it, so that a test can prove that the neglect is detected.
+	#[cfg(test)]
+	skip_undo: bool,
+
+	/// `true` if the search was abandoned because it exceeded its
+	/// [node limit](Search::node_limit) or [deadline](Search::deadline), or
+	/// because its trace could not be written, `false` otherwise.
+	truncated: bool
+}
+
+impl<'a> Search<'a>
+{
+	/// Construct an unconstrained search that applies `on_solution` to each
+	/// solution found, together with its score.
+	fn new (on_solution: &'a mut dyn FnMut(&[Point], u32) -> bool) -> Self
+	{
+		Search
+		{
+			moves: Vec::new(),
+			on_solution,
+			score: 0,
+			heuristic: Heuristic::default(),
+			goal: None,
+			frozen_colors: 0,
+			frozen_stones: 0,
+			forbid_wild: false,
+			wild_free_moves: 0,
+			first_move: None,
+			symmetric: false,
+			stats: SolveStats::default(),
+			node_limit: u64::MAX,
+			move_limit: usize::MAX,
+			deadline: None,
+			bombs: false,
+			#[cfg(feature = "parallel")]
+			cancelled: None,
+			trace: None,
+			trace_error: None,
+			#[cfg(test)]
+			skip_undo: false,
+			truncated: false
+		}
+	}
+
+	/// Write the specified line to the trace, indented by the number of moves
+	/// played thus far. Answer `true` if the line was written, or `false` if
+	/// the trace failed, in which case the search is abandoned.
+	fn write_trace (&mut self, line: fmt::Arguments) -> bool
+	{
+		let depth = self.moves.len();
+		let out = self.trace.as_mut().expect("trace is enabled");
+		match writeln!(out, "{:depth$}{}", "", line, depth = depth)
+		{
+			Ok(()) => true,
+			Err(e) =>
+			{
+				self.trace_error = Some(e);
+				self.truncated = true;
+				false
+			}
+		}
+	}
+}
+
+/// Statistics about a search, useful for gauging the difficulty of a board and
+/// the efficacy of the solver.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct SolveStats
+{
+	/// The number of nodes visited, i.e., the number of positions examined.
+	pub nodes_visited: u64,
+
+	/// The greatest number of moves played at once.
+	pub max_depth: u32,
+
+	/// The number of moves undone because they led only to dead ends.
+	pub backtracks: u64,
+
+	/// The time spent searching.
+	pub elapsed: Duration
+}
+
+impl Display for SolveStats
+{
+	fn fmt (&self, f: &mut Formatter) -> fmt::Result
+	{
+		write!(
+			f,
+			"{} nodes visited, maximum depth {}, {} backtracks, {:?} elapsed",
+			self.nodes_visited,
+			self.max_depth,
+			self.backtracks,
+			self.elapsed)
+	}
+}
+
+/// An objective rating of the difficulty of a board, as computed by
+/// [`Board::difficulty`] from the [statistics](SolveStats) of a search. The
+/// rating is a weighted sum of four measures, each normalized to `[0, 1]`:
+///
+/// * __effort__ (40%): `log₁₀(nodes_visited) / 6`, capped at `1`, so a search
+///   that visits a million nodes is maximally effortful;
+/// * __waste__ (30%): `backtracks / nodes_visited`, the fraction of the
+///   search spent in dead ends;
+/// * __branching__ (15%): `2 × (b - 1)`, capped at `1`, where `b` is the
+///   effective branching factor, i.e., the `max_depth`-th root of the ratio
+///   of `nodes_visited` to the `max_depth + 1` nodes that a search without
+///   dead ends would visit;
+/// * __depth__ (15%): `max_depth / 60`, capped at `1`, so longer solutions
+///   are harder to find.
+///
+/// Elapsed time is ignored, because it varies from machine to machine. The
+/// solver's own heuristics determine the statistics, so the rating measures
+/// how hard the board is for the solver, which generally, but not always,
+/// agrees with how hard it is for a player. The exact ratings may shift as
+/// the solver evolves.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Difficulty
+{
+	/// The rating, from `0` (trivial) to `100` (fiendish).
+	pub rating: u8,
+
+	/// The statistics of the search from which the rating was computed.
+	pub stats: SolveStats
+}
+
+impl From<SolveStats> for Difficulty
+{
+	fn from (stats: SolveStats) -> Self
+	{
+		if stats.nodes_visited == 0
+		{
+			return Difficulty { rating: 0, stats }
+		}
+		let nodes = stats.nodes_visited as f64;
+		let depth = stats.max_depth as f64;
+		let effort = (nodes.log10() / 6.0).min(1.0);
+		let waste = (stats.backtracks as f64 / nodes).min(1.0);
+		let branching = match stats.max_depth
+		{
+			0 => 0.0,
+			_ =>
+			{
+				let excess = (nodes / (depth + 1.0)).max(1.0);
+				(2.0 * (excess.powf(1.0 / depth) - 1.0)).min(1.0)
+			}
+		};
+		let depth = (depth / 60.0).min(1.0);
+		let rating =
+			40.0 * effort + 30.0 * waste + 15.0 * branching + 15.0 * depth;
+		Difficulty { rating: rating.round().clamp(0.0, 100.0) as u8, stats }
+	}
+}
+
+impl Display for Difficulty
+{
+	fn fmt (&self, f: &mut Formatter) -> fmt::Result
+	{
+		write!(f, "{}/100", self.rating)
+	}
+}
+
+/// The guidance for a player between triplets, as answered by
+/// [`solve_interactive_assist`].
+///
+/// [`solve_interactive_assist`]: Board::solve_interactive_assist
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Assist
+{
+	/// The first move of some solution, or `None` if the board is already
+	/// solved or has no solution.
+	pub hint: Option<Point>,
+
+	/// `true` if the board is [deadlocked](Board::is_deadlocked), `false`
+	/// otherwise.
+	pub deadlocked: bool,
+
+	/// The color for which a [wild stone](WildStone) must be played in the
+	/// next triplet, if every solution plays one there, or `None` otherwise.
+	/// A triplet of [bombs](BombStone) and wild stones alone may have no
+	/// color of its own, in which case this is [`WILD_COLOR`].
+	pub wild: Option<u64>
+}
+
+/// An action that undoes a [removal](Board::remove).
+type Undo = Box<dyn for<'r> FnMut(&'r mut Board)>;
+
+/// A frame of an [iterative search], corresponding to an activation of
+/// [`solve_recursively`].
+///
+/// [iterative search]: Board::solve_iterative
+/// [`solve_recursively`]: Board::solve_recursively
+struct Frame
+{
+	/// The action that undoes the move that led to this frame, or `None` for
+	/// the initial frame.
+	undo: Option<Undo>,
+
+	/// The moves available at this frame, each paired with the color to which
+	/// it commits the triplet in progress.
+	available: Vec<(Point, u64)>,
+
+	/// The index of the next available move to explore.
+	next: usize,
+
+	/// `true` iff a wild stone may be chosen.
+	allow_wild: bool
+}
+
+/// Answer the canonical key of the specified solution, i.e., the solution with
+/// the moves of each triplet sorted. Solutions that differ only by the order of
+/// moves within their triplets clear the same stones in the same triplets, so
+/// they share a canonical key.
+fn canonical_key (moves: &[Point]) -> Vec<Point>
+{
+	moves.chunks(3)
+		.flat_map(|triplet|
+		{
+			let mut triplet = triplet.to_vec();
+			triplet.sort();
+			triplet
+		})
+		.collect()
+}
+
+/******************************************************************************
+ *                                   Tests.                                   *
+ ******************************************************************************/
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::board::RenderStyle;
+	use std::thread;
+
+	/// The sample boards that are well-formed and solvable.
+	const SOLVABLE: [&str; 8] = [
+		include_str!("../examples/board001.tsb"),
+		include_str!("../examples/board002.tsb"),
+		include_str!("../examples/board004.tsb"),
+		include_str!("../examples/board005.tsb"),
+		include_str!("../examples/board006.tsb"),
+		include_str!("../examples/board007.tsb"),
+		include_str!("../examples/board329.tsb"),
+		include_str!("../examples/board415.tsb")
+	];
+
+	/// Answer the color of the ordinary stone at the specified point.
+	fn color_at (board: &Board, p: Point) -> u64
+	{
+		match board.get(p)
+		{
+			Some(AnyStone::Ordinary(o)) => o.color(),
+			stone => panic!("expected an ordinary stone, not {:?}", stone)
+		}
+	}
+
+	#[test]
+	fn assist_names_color_of_needed_wild ()
+	{
+		let mut board = Board::parse("wild = r\n---\nr * r\n").unwrap();
+		let assist = board.solve_interactive_assist();
+		assert!(assist.hint.is_some());
+		assert!(!assist.deadlocked);
+		assert_eq!(assist.wild, Some(color_at(&board, (0, 0))));
+	}
+
+	#[test]
+	fn assist_needs_no_wild_when_avoidable ()
+	{
+		let mut board =
+			Board::parse("wild = r\n---\nr * r\nr r r\n").unwrap();
+		let assist = board.solve_interactive_assist();
+		assert!(assist.hint.is_some());
+		assert_eq!(assist.wild, None);
+	}
+
+	#[test]
+	fn solve_from_move_honors_first_move ()
+	{
+		let tsb = "r g g\nr r g\n";
+		let mut board = Board::parse(tsb).unwrap();
+		assert!(board.legal_moves().contains(&(0, 1)));
+		assert!(board.legal_moves().contains(&(2, 1)));
+		let solution = board.solve_from_move((0, 1)).unwrap();
+		assert_eq!(solution[0], (0, 1));
+		assert!(board.is_valid_solution(&solution));
+		assert_eq!(board.solve_from_move((2, 1)), None);
+		assert_eq!(board, Board::parse(tsb).unwrap());
+	}
+
+	#[test]
+	fn solve_from_illegal_move_fails ()
+	{
+		let tsb = "r g g\nr r g\n";
+		let mut board = Board::parse(tsb).unwrap();
+		assert!(!board.legal_moves().contains(&(0, 0)));
+		assert_eq!(board.solve_from_move((0, 0)), None);
+		assert_eq!(board, Board::parse(tsb).unwrap());
+	}
+
+	#[test]
+	fn play_applies_legal_triplet ()
+	{
+		let tsb = "r g g\nr r g\n";
+		let mut board = Board::parse(tsb).unwrap();
+		assert_eq!(board.play([(0, 1), (1, 1), (0, 0)]), Ok(()));
+		assert_eq!(board.turn(), 3);
+		assert_eq!(board.play([(1, 0), (2, 1), (2, 0)]), Ok(()));
+		assert!(board.is_solved());
+	}
+
+	#[test]
+	fn play_rejects_illegal_triplets ()
+	{
+		let tsb = "r g g\nr r g\n";
+		let mut board = Board::parse(tsb).unwrap();
+		assert_eq!(
+			board.play([(0, 0), (0, 1), (1, 1)]),
+			Err(IllegalMove::NotOnFrontier((0, 0))));
+		assert_eq!(
+			board.play([(0, 1), (2, 1), (1, 1)]),
+			Err(IllegalMove::ColorMismatch((2, 1))));
+		assert_eq!(board, Board::parse(tsb).unwrap());
+		board.play_undoable((0, 1), 0);
+		assert_eq!(
+			board.play([(1, 1), (0, 0), (2, 1)]),
+			Err(IllegalMove::TripletInProgress));
+		let mut board = Board::parse(
+			"colorlock = true\n---\nr r r r r r g g g\n").unwrap();
+		board.play([(0, 0), (1, 0), (2, 0)]).unwrap();
+		assert_eq!(
+			board.play([(3, 0), (4, 0), (5, 0)]),
+			Err(IllegalMove::ColorLockViolation((3, 0))));
+		assert_eq!(board.turn(), 3);
+	}
+
+	#[test]
+	fn solver_hits_armored_stone_twice ()
+	{
+		let tsb = "armor = true\n---\ng g g\nr r2 _\n";
+		let mut board = Board::parse(tsb).unwrap();
+		let solution = board.solve().unwrap();
+		assert!(board.is_valid_solution(&solution));
+		assert_eq!(solution.iter().filter(|&&p| p == (1, 1)).count(), 2);
+		assert_eq!(solution.len(), 6);
+	}
+
+	#[test]
+	fn frontier_iter_matches_frontier ()
+	{
+		for tsb in SOLVABLE
+		{
+			let mut board = Board::parse(tsb).unwrap();
+			let (solution, _) =
+				board.solve_with_heuristic(Heuristic::ColumnOrder);
+			let solution = solution.unwrap();
+			assert!(board.is_valid_solution(&solution));
+			let colors = board.solution_colors(&solution).unwrap();
+			for (i, &p) in solution.iter().enumerate()
+			{
+				let color = colors[i / 3];
+				for filter in [WILD_COLOR, color]
+				{
+					for allow_wild in [false, true]
+					{
+						assert_eq!(
+							board.frontier_iter(filter, allow_wild)
+								.collect::<Vec<_>>(),
+							board.frontier(filter, allow_wild));
+					}
+				}
+				board.play_undoable(p, color);
+			}
+		}
+	}
+
+	#[test]
+	#[cfg(debug_assertions)]
+	#[should_panic(expected = "board not restored after solving")]
+	fn unfaithful_undo_is_detected ()
+	{
+		let mut board = Board::parse("r r r\ng g g\n").unwrap();
+		let mut on_solution = |_: &[Point], _| false;
+		let mut search = Search::new(&mut on_solution);
+		search.skip_undo = true;
+		board.explore(&mut search);
+	}
+
+	#[test]
+	fn wild_that_begins_triplet_commits_its_color ()
+	{
+		// The wild stone can only be red, so it cannot join the greens.
+		let mut board = Board::parse("wild = r\n---\n* g g\n").unwrap();
+		assert_eq!(board.solve(), None);
+		assert!(!board.is_valid_solution(&[(0, 0), (1, 0), (2, 0)]));
+		assert_eq!(
+			board.play([(0, 0), (1, 0), (2, 0)]),
+			Err(IllegalMove::ColorMismatch((1, 0))));
+		let mut board = Board::parse("wild = g\n---\n* g g\n").unwrap();
+		assert_eq!(board.solve(), Some(vec![(0, 0), (1, 0), (2, 0)]));
+	}
+
+	#[test]
+	fn wild_is_charged_in_any_position ()
+	{
+		for (rows, wild) in [("* r r\n", 0), ("r * r\n", 1), ("r r *\n", 2)]
+		{
+			let tsb = format!("wild = r\n---\n{}", rows);
+			let mut board = Board::parse(&tsb).unwrap();
+			let red = board.wild_colors();
+			for column in 0..3
+			{
+				board.play_undoable((column, 0), red);
+				let expected = if column < wild { red } else { 0 };
+				assert_eq!(board.wild_colors(), expected, "{:?}", rows);
+			}
+			for column in (0..3).rev()
+			{
+				assert!(board.undo());
+				let expected = if column <= wild { red } else { 0 };
+				assert_eq!(board.wild_colors(), expected, "{:?}", rows);
+			}
+			assert_eq!(board, Board::parse(&tsb).unwrap());
+			board.play([(0, 0), (1, 0), (2, 0)]).unwrap();
+			assert_eq!(board.wild_colors(), 0);
+		}
+	}
+
+	#[test]
+	fn bitboards_agree_with_grid ()
+	{
+		let survivors = [
+			"_ b g _\ng b g g\ng # g b\n",
+			"access = left\n---\nr # r r\ng g # g\n"];
+		for tsb in SOLVABLE.into_iter().chain(survivors)
+		{
+			let mut board = Board::parse(tsb).unwrap();
+			let mut plain = board.clone().without_bitboards();
+			assert!(board.bitboards().is_some());
+			assert!(plain.bitboards().is_none());
+			assert_eq!(
+				board.has_stranded_survivor(),
+				plain.has_stranded_survivor());
+			for row in 0..board.height()
+			{
+				assert_eq!(
+					board.survivor_pressure(row),
+					plain.survivor_pressure(row));
+			}
+			for heuristic in [
+				Heuristic::ColumnOrder,
+				Heuristic::AbundantColorsFirst,
+				Heuristic::SurvivorRowsFirst]
+			{
+				assert_eq!(
+					board.solve_with_heuristic(heuristic).0,
+					plain.solve_with_heuristic(heuristic).0);
+			}
+			assert_eq!(board.solve_k(4), plain.solve_k(4));
+		}
+	}
+
+	#[test]
+	fn iterative_search_handles_tall_board ()
+	{
+		for tsb in SOLVABLE
+		{
+			let mut board = Board::parse(tsb).unwrap();
+			assert_eq!(board.solve_iterative(), board.solve());
+		}
+		let tall = |rows: usize| "r r r\ng g g\n".repeat(rows / 2);
+		let mut board = Board::parse(&tall(200)).unwrap();
+		assert_eq!(board.solve_iterative(), board.solve());
+		// The recursive search needs a frame per move, so thousands of moves
+		// would overflow this meager stack; the iterative search needs none.
+		let mut board = Board::parse(&tall(1000)).unwrap();
+		let solution = thread::Builder::new()
+			.stack_size(64 * 1024)
+			.spawn(move || board.solve_iterative())
+			.unwrap()
+			.join()
+			.unwrap();
+		assert_eq!(solution.map(|moves| moves.len()), Some(3000));
+	}
+
+	#[test]
+	fn symmetry_prunes_solvable_search ()
+	{
+		let mut board =
+			Board::parse("r r _ r r\n_ g g g _\nb r b r b\n").unwrap();
+		assert!(board.is_mirror_symmetric());
+		let heuristic = Heuristic::AbundantColorsFirst;
+		let (_, plain) = board.solve_with_heuristic(heuristic);
+		let (solution, pruned) = board.solve_with_symmetry(heuristic);
+		assert!(pruned.nodes_visited < plain.nodes_visited);
+		assert!(board.is_valid_solution(&solution.unwrap()));
+	}
+
+	#[test]
+	fn symmetry_prunes_unsolvable_search ()
+	{
+		let mut board =
+			Board::parse("b r r b\nb r r b\nr b b r\n").unwrap();
+		assert!(board.is_mirror_symmetric());
+		let heuristic = Heuristic::ColumnOrder;
+		let (none, plain) = board.solve_with_heuristic(heuristic);
+		let (solution, pruned) = board.solve_with_symmetry(heuristic);
+		assert_eq!((none, solution), (None, None));
+		assert!(0 < pruned.nodes_visited);
+		assert!(pruned.nodes_visited < plain.nodes_visited);
+	}
+
+	#[test]
+	fn gravity_breaks_sideways_symmetry ()
+	{
+		let rows = "r g\nb b\nr g\n";
+		let board = Board::parse(&format!("access = left\n---\n{}", rows))
+			.unwrap();
+		assert!(board.is_mirror_symmetric());
+		let board = Board::parse(
+			&format!("access = left\ngravity = down\n---\n{}", rows))
+			.unwrap();
+		assert!(!board.is_mirror_symmetric());
+		let board = Board::parse(
+			&format!("access = right\ngravity = down\n---\n{}", rows))
+			.unwrap();
+		assert!(!board.is_mirror_symmetric());
+	}
+
+	#[test]
+	fn gravity_distinguishes_reordered_triplets ()
+	{
+		let rows = "r _\nr r\n";
+		let mut board = Board::parse(rows).unwrap();
+		assert_eq!(board.solve_k(10).len(), 1);
+		let mut board =
+			Board::parse(&format!("gravity = down\n---\n{}", rows)).unwrap();
+		let solutions = board.solve_k(10);
+		assert_eq!(solutions.len(), 3);
+		assert!(solutions.iter().all(|moves| board.is_valid_solution(moves)));
+	}
+
+	#[test]
+	fn solver_clears_survivor_that_naive_order_strands ()
+	{
+		let mut board =
+			Board::parse("_ b g _\ng b g g\ng # g b\n").unwrap();
+		let solution = board.solve().unwrap();
+		assert!(board.is_valid_solution(&solution));
+		// Clearing the column beside the survivor first leaves its row with a
+		// stone that can only be matched by the stones behind it.
+		board.play([(2, 2), (2, 1), (2, 0)]).unwrap();
+		assert!(matches!(board.get((1, 2)), Some(AnyStone::Survivor(_))));
+		assert_eq!(board.solve(), None);
+	}
+
+	#[test]
+	fn diagnose_reports_stranded_survivor ()
+	{
+		let mut board =
+			Board::parse("access = left\n---\nr r r _\n# g g g\n").unwrap();
+		assert!(board.has_stranded_survivor());
+		assert!(matches!(board.diagnose(), Err(Unsolvable::StrandedSurvivor)));
+	}
+
+	#[cfg(feature = "parallel")]
+	#[test]
+	fn parallel_agrees_with_serial ()
+	{
+		let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/examples");
+		let mut boards = 0;
+		for entry in std::fs::read_dir(dir).unwrap()
+		{
+			let path = entry.unwrap().path();
+			let name = path.file_name().unwrap().to_string_lossy();
+			if !name.starts_with("board") || !name.ends_with(".tsb")
+			{
+				continue
+			}
+			let tsb = std::fs::read_to_string(&path).unwrap();
+			let mut board = match Board::parse(&tsb)
+			{
+				Ok(board) => board,
+				Err(_) => continue
+			};
+			let parallel = board.solve_parallel();
+			assert_eq!(parallel.is_some(), board.solve().is_some(), "{}", name);
+			if let Some(solution) = parallel
+			{
+				assert!(board.is_valid_solution(&solution), "{}", name);
+			}
+			boards += 1;
+		}
+		assert!(boards > 0);
+	}
+
+	#[test]
+	fn diagnose_until_past_deadline_times_out ()
+	{
+		for tsb in SOLVABLE
+		{
+			let mut board = Board::parse(tsb).unwrap();
+			let deadline = Instant::now();
+			// With a check interval of 1, the clock is consulted at every
+			// node, so the search stops at its very first node.
+			let result = board.diagnose_until(deadline, 1);
+			assert_eq!(result.err(), Some(TimedOut));
+			assert!(deadline.elapsed() < Duration::from_secs(1));
+			assert_eq!(board, Board::parse(tsb).unwrap());
+		}
+	}
+
+	#[test]
+	fn color_lock_rejects_repeated_color ()
+	{
+		let rows = "r r r r r r g g g\n";
+		let repeated = [
+			(0, 0), (1, 0), (2, 0),
+			(3, 0), (4, 0), (5, 0),
+			(6, 0), (7, 0), (8, 0)
+		];
+		let mut board = Board::parse(rows).unwrap();
+		assert!(board.is_valid_solution(&repeated));
+		let tsb = format!("colorlock = true\n---\n{}", rows);
+		let mut board = Board::parse(&tsb).unwrap();
+		assert_eq!(
+			board.verify_solution(&repeated),
+			Err(ReplayError::IllegalMove {
+				index: 3,
+				reason: IllegalMove::ColorLockViolation((3, 0))
+			}));
+		board.play([(0, 0), (1, 0), (2, 0)]).unwrap();
+		assert_eq!(
+			board.play([(3, 0), (4, 0), (5, 0)]),
+			Err(IllegalMove::ColorLockViolation((3, 0))));
+	}
+
+	#[test]
+	fn restricted_wild_prunes_otherwise_valid_path ()
+	{
+		// Only the bottom row is accessible at first, and its reds need a red
+		// wild, which `*g` cannot provide.
+		let mut board =
+			Board::parse("wild = rg\n---\n*r g g\n*g r r\n").unwrap();
+		assert!(board.solve().is_none());
+		let mut board =
+			Board::parse("wild = rg\n---\n* g g\n* r r\n").unwrap();
+		assert!(board.solve().is_some());
+	}
+
+	#[test]
+	fn solver_obeys_color_lock ()
+	{
+		for tsb in SOLVABLE
+		{
+			let mut board = Board::parse(tsb).unwrap();
+			if !board.color_locked()
+			{
+				continue
+			}
+			let solution = board.solve().unwrap();
+			assert!(board.is_valid_solution(&solution));
+			let colors = board.solution_colors(&solution).unwrap();
+			for pair in colors.windows(2)
+			{
+				assert!(
+					pair[0] != pair[1] || pair[0] & board.lock_colors() == 0);
+			}
+		}
+		let mut board = Board::parse(
+			"colorlock = true\n---\nr r r r r r g g g\n").unwrap();
+		let colors = board.solve()
+			.map(|solution| board.solution_colors(&solution).unwrap())
+			.unwrap();
+		assert_eq!(colors.len(), 3);
+		assert_ne!(colors[0], colors[1]);
+		assert_ne!(colors[1], colors[2]);
+	}
+
+	#[cfg(feature = "logging")]
+	mod logging
+	{
+		use std::sync::Mutex;
+		use std::thread::{self, ThreadId};
+		use log::{Level, LevelFilter, Log, Metadata, Record};
+		use super::*;
+
+		/// A logger that captures every record, noting the thread that
+		/// emitted it, because the tests run concurrently.
+		struct CapturingLogger (Mutex<Vec<(ThreadId, Level, String)>>);
+
+		impl Log for CapturingLogger
+		{
+			fn enabled (&self, _metadata: &Metadata) -> bool
+			{
+				true
+			}
+
+			fn log (&self, record: &Record)
+			{
+				self.0.lock().unwrap().push((
+					thread::current().id(),
+					record.level(),
+					record.args().to_string()));
+			}
+
+			fn flush (&self) {}
+		}
+
+		/// The sole logger, which must outlive every test.
+		static LOGGER: CapturingLogger = CapturingLogger(Mutex::new(vec![]));
+
+		#[test]
+		fn search_emits_records ()
+		{
+			let _ = log::set_logger(&LOGGER);
+			log::set_max_level(LevelFilter::Trace);
+			let mut board = Board::parse(SOLVABLE[0]).unwrap();
+			assert!(board.solve().is_some());
+			let me = thread::current().id();
+			let records = LOGGER.0.lock().unwrap().iter()
+				.filter(|(id, _, _)| *id == me)
+				.map(|(_, level, text)| (*level, text.clone()))
+				.collect::<Vec<_>>();
+			assert!(records.iter().any(|(level, text)|
+				*level == Level::Debug && text.starts_with("search started")));
+			assert!(records.iter().any(|(level, text)|
+				*level == Level::Debug
+					&& text.starts_with("search finished")
+					&& text.contains("nodes visited")
+					&& text.contains("backtracks")));
+			assert!(records.iter().any(|(level, text)|
+				*level == Level::Trace
+					&& text.trim_start().starts_with("play")));
+		}
+	}
+
+	#[test]
+	fn hint_loop_keeps_board_solvable ()
+	{
+		for tsb in SOLVABLE
+		{
+			let mut board = Board::parse(tsb).unwrap();
+			let moves = board.solve().unwrap();
+			let colors = board.solution_colors(&moves).unwrap();
+			for (index, m) in moves.into_iter().enumerate()
+			{
+				if index % 3 == 0
+				{
+					let assist = board.solve_interactive_assist();
+					assert!(!assist.deadlocked, "deadlocked at move {}", index);
+					assert!(assist.hint.is_some());
+				}
+				let color = colors[index / 3];
+				let _ = board.advance_hint(m, color, RenderStyle::Plain);
+			}
+			assert!(board.is_solved());
+			assert_eq!(board.wild_colors(), 0);
+		}
+	}
+
+	#[test]
+	fn solve_k_answers_up_to_k_distinct_solutions ()
+	{
+		for tsb in ["r r r r r r\n", "r g g\nr r g\n", SOLVABLE[0]]
+		{
+			let mut board = Board::parse(tsb).unwrap();
+			let distinct = board.solve_all(None).iter()
+				.map(|moves| canonical_key(moves))
+				.collect::<HashSet<_>>()
+				.len();
+			for k in 0 ..= distinct + 1
+			{
+				let solutions = board.solve_k(k);
+				assert_eq!(solutions.len(), k.min(distinct));
+				let keys = solutions.iter()
+					.map(|moves| canonical_key(moves))
+					.collect::<HashSet<_>>();
+				assert_eq!(keys.len(), solutions.len());
+			}
+		}
+	}
+
+	#[test]
+	fn colors_blocking_solution_names_buried_color ()
+	{
+		// Frozen, the red stones bury the green ones; the reverse is not so.
+		let mut board = Board::parse("g g g\nr r r\n").unwrap();
+		let red = color_at(&board, (0, 1));
+		assert_eq!(board.colors_blocking_solution(), vec![red]);
+		let mut board = Board::parse("g g g\nr r r\ng b b\nb r g\n").unwrap();
+		assert!(board.solve().is_none());
+		assert!(board.colors_blocking_solution().is_empty());
+	}
+
+	#[test]
+	fn same_color_cluster_finds_reachable_partners ()
+	{
+		// The third red stone is buried beneath blue ones.
+		let board = Board::parse("g r g\nb b b\nr r g\n").unwrap();
+		let mut cluster = board.same_color_cluster((0, 2));
+		cluster.sort_unstable();
+		assert_eq!(cluster, [(0, 2), (1, 2)]);
+		// Each red stone exposes the next.
+		let board = Board::parse("r\nr\nr\n").unwrap();
+		let mut cluster = board.same_color_cluster((0, 2));
+		cluster.sort_unstable();
+		assert_eq!(cluster, [(0, 0), (0, 1), (0, 2)]);
+		let board = Board::parse("r r r\n_ _ _\n").unwrap();
+		assert!(board.same_color_cluster((0, 1)).is_empty());
+	}
+
+	#[test]
+	fn solve_all_counts_every_ordering ()
+	{
+		// Each triplet can be played in any of 3! orders.
+		let tsb = "r r r\ng g g\n";
+		let mut board = Board::parse(tsb).unwrap();
+		let solutions = board.solve_all(None);
+		assert_eq!(solutions.len(), 36);
+		assert_eq!(board, Board::parse(tsb).unwrap());
+		assert!(solutions.iter().all(|moves| board.is_valid_solution(moves)));
+		let distinct = solutions.iter().collect::<HashSet<_>>();
+		assert_eq!(distinct.len(), 36);
+		assert_eq!(board.solve_all(Some(5)).len(), 5);
+		assert!(board.solve_all(Some(0)).is_empty());
+	}
+
+	#[test]
+	fn uniqueness_ignores_order_within_triplets ()
+	{
+		let mut board = Board::parse("r r r\ng g g\n").unwrap();
+		assert!(board.is_uniquely_solvable());
+		// Either three of the six red stones can go first.
+		let mut board = Board::parse("r r r r r r\n").unwrap();
+		assert!(!board.is_uniquely_solvable());
+		let mut board = Board::parse("r r g\n").unwrap();
+		assert!(!board.is_uniquely_solvable());
+	}
+
+	#[test]
+	fn diagnose_explains_unsolvable_boards ()
+	{
+		let mut board = Board::parse("r r r r g g\n").unwrap();
+		let (result, stats) = board.diagnose_with_stats();
+		assert!(matches!(
+			result,
+			Err(Unsolvable::ColorCountNotDivisibleByThree('r' | 'g'))));
+		// The cheap checks preclude the search.
+		assert_eq!(stats.nodes_visited, 0);
+		let mut board = Board::parse("g r r\nr g g\n").unwrap();
+		let (result, stats) = board.diagnose_with_stats();
+		assert!(matches!(result, Err(Unsolvable::Exhausted)));
+		assert!(stats.nodes_visited > 0);
+		let mut board = Board::parse(SOLVABLE[0]).unwrap();
+		let solution = board.diagnose().unwrap();
+		assert!(board.is_valid_solution(&solution));
+	}
+
+	#[test]
+	fn stats_count_nodes_on_fixed_boards ()
+	{
+		// The root, plus one node per move, without any backtracking.
+		let mut board = Board::parse("r r r\ng g g\n").unwrap();
+		let (solution, stats) = board.solve_with_stats();
+		assert_eq!(solution.map(|moves| moves.len()), Some(6));
+		assert_eq!(
+			(stats.nodes_visited, stats.max_depth, stats.backtracks),
+			(7, 6, 0));
+		// Every move leads to a dead end within two moves.
+		let mut board = Board::parse("g r r\nr g g\n").unwrap();
+		let (solution, stats) = board.solve_with_stats();
+		assert_eq!(solution, None);
+		assert_eq!(
+			(stats.nodes_visited, stats.max_depth, stats.backtracks),
+			(6, 2, 5));
+	}
+
+	#[test]
+	fn heuristic_reduces_nodes_visited ()
+	{
+		let heuristics = [
+			Heuristic::ColumnOrder,
+			Heuristic::AbundantColorsFirst,
+			Heuristic::SurvivorRowsFirst];
+		for tsb in SOLVABLE
+		{
+			let mut board = Board::parse(tsb).unwrap();
+			for heuristic in heuristics
+			{
+				let mut ordered =
+					board.frontier_ordered(WILD_COLOR, true, heuristic);
+				ordered.sort_unstable();
+				let mut frontier = board.frontier(WILD_COLOR, true);
+				frontier.sort_unstable();
+				assert_eq!(ordered, frontier);
+				let solution = board.solve_with_heuristic(heuristic).0;
+				assert!(board.is_valid_solution(&solution.unwrap()));
+			}
+		}
+		let mut board =
+			Board::parse(include_str!("../examples/board415.tsb")).unwrap();
+		let (_, naive) = board.solve_with_heuristic(Heuristic::ColumnOrder);
+		let (_, abundant) =
+			board.solve_with_heuristic(Heuristic::AbundantColorsFirst);
+		assert!(abundant.nodes_visited < naive.nodes_visited);
+	}
+
+	#[test]
+	fn closed_toggles_hide_stones_behind_them ()
+	{
+		let mut board = Board::parse("r g g\n+ / r\nr g _\n").unwrap();
+		let sorted = |mut moves: Vec<Point>| { moves.sort_unstable(); moves };
+		assert_eq!(sorted(board.legal_moves()), [(0, 2), (1, 2), (2, 1)]);
+		// The toggles change state with every turn.
+		board.play_undoable((0, 2), 0);
+		assert_eq!(sorted(board.legal_moves()), [(0, 0), (1, 2), (2, 1)]);
+		board.play_undoable((1, 2), 0);
+		assert_eq!(sorted(board.legal_moves()), [(1, 0), (2, 1)]);
+		let red = color_at(&board, (2, 1));
+		assert_eq!(board.frontier(red, false), [(2, 1)]);
+		assert_eq!(board.frontier(WILD_COLOR, false), [(1, 0), (2, 1)]);
+	}
+
+	#[test]
+	fn max_score_prefers_playing_to_blasting ()
+	{
+		// Playing the bomb early blasts stones that would otherwise score.
+		let mut board = Board::parse("_ _ o r\nr r r r\n").unwrap();
+		let lengths = board.solve_all(None).iter()
+			.map(|moves| moves.len())
+			.collect::<HashSet<_>>();
+		assert_eq!(lengths, HashSet::from([3, 6]));
+		let (moves, score) = board.solve_max_score().unwrap();
+		assert_eq!((moves.len(), score), (6, 6));
+		assert!(board.is_valid_solution(&moves));
+		// A wild stone is worth three points.
+		let mut board = Board::parse("wild = r\n---\nr r *\n").unwrap();
+		assert_eq!(board.solve_max_score().map(|(_, score)| score), Some(5));
+		assert_eq!(Board::parse("r r g\n").unwrap().solve_max_score(), None);
+	}
+
+	#[test]
+	fn rainbow_completes_triplet_without_wild_color ()
+	{
+		// A rainbow stone cannot begin a triplet.
+		let mut board = Board::parse("@ r r\n").unwrap();
+		let solutions = board.solve_all(None);
+		assert!(!solutions.is_empty());
+		assert!(solutions.iter().all(|moves| moves[0] != (0, 0)));
+		let mut board = Board::parse("wild = g\n---\ng g *\nr r @\n").unwrap();
+		let wild_colors = board.wild_colors();
+		let red = color_at(&board, (0, 1));
+		for p in [(0, 1), (1, 1), (2, 1)]
+		{
+			board.play_undoable(p, red);
+		}
+		assert_eq!(board.wild_colors(), wild_colors);
+		let solution = board.solve().unwrap();
+		assert!(board.is_valid_solution(&solution));
+	}
+
+	#[test]
+	fn following_hints_solves_board ()
+	{
+		for tsb in SOLVABLE
+		{
+			let mut board = Board::parse(tsb).unwrap();
+			loop
+			{
+				let before = board.clone();
+				let p = match board.hint()
+				{
+					Some(p) => p,
+					None => break
+				};
+				assert_eq!(board, before);
+				let moves = board.solve_from_move(p).unwrap();
+				assert_eq!(moves[0], p);
+				board.play([moves[0], moves[1], moves[2]]).unwrap();
+			}
+			assert!(board.is_solved());
+		}
+		assert_eq!(Board::parse("g r r\nr g g\n").unwrap().hint(), None);
+	}
+
+	#[test]
+	fn histogram_rejects_four_red_stones ()
+	{
+		let mut board = Board::parse("r r r r g g\n").unwrap();
+		let (red, green) = (color_at(&board, (0, 0)), color_at(&board, (4, 0)));
+		let expected = HashMap::from([(red, 4), (green, 2)]);
+		assert_eq!(board.color_histogram(), expected);
+		let (solution, stats) = board.solve_with_stats();
+		assert_eq!(solution, None);
+		assert_eq!(stats.nodes_visited, 0);
+		// A wild stone makes up the shortfall of its color, and an armored
+		// stone counts once per hit.
+		let tsb = "wild = r\narmor = true\n---\nr r * g2 g\n";
+		let mut board = Board::parse(tsb).unwrap();
+		let green = color_at(&board, (4, 0));
+		assert_eq!(board.color_histogram()[&green], 3);
+		assert!(board.solve().is_some());
+	}
+
+	#[test]
+	fn board_is_solved_after_last_triplet ()
+	{
+		// The survivor is not removable, so it need not be cleared.
+		let mut board = Board::parse("_ g g g\n# r r r\n").unwrap();
+		assert!(!board.is_solved());
+		board.play([(1, 1), (2, 1), (3, 1)]).unwrap();
+		assert!(!board.is_solved());
+		board.play_undoable((1, 0), 0);
+		board.play_undoable((2, 0), 0);
+		assert!(!board.is_solved());
+		board.play_undoable((3, 0), 0);
+		assert!(board.is_solved());
+		assert!(board.undo());
+		assert!(!board.is_solved());
+	}
+
+	#[test]
+	fn wrong_move_deadlocks_board ()
+	{
+		let tsb = "colorlock = true\n---\ng g g r r r\nr r r g g g\n";
+		let mut board = Board::parse(tsb).unwrap();
+		assert!(!board.is_deadlocked());
+		board.play([(0, 1), (1, 1), (2, 1)]).unwrap();
+		assert!(!board.is_deadlocked());
+		// Clearing the upper greens leaves only reds, which the lock forbids,
+		// and the lower greens, which bury the reds.
+		board.play([(0, 0), (1, 0), (2, 0)]).unwrap();
+		let before = board.clone();
+		assert!(board.is_deadlocked());
+		assert_eq!(board, before);
+		let mut board = Board::parse(tsb).unwrap();
+		let solution = board.solve().unwrap();
+		for triplet in solution.chunks(3)
+		{
+			board.play([triplet[0], triplet[1], triplet[2]]).unwrap();
+		}
+		// A solved board is not deadlocked.
+		assert!(!board.is_deadlocked());
+	}
+
+	#[test]
+	fn verify_solution_names_failing_move ()
+	{
+		let tsb = "r g g\nr r g\n";
+		let mut board = Board::parse(tsb).unwrap();
+		let valid = [(0, 1), (1, 1), (0, 0), (1, 0), (2, 1), (2, 0)];
+		assert_eq!(board.verify_solution(&valid), Ok(()));
+		assert_eq!(board, Board::parse(tsb).unwrap());
+		let buried = [(0, 1), (1, 1), (0, 0), (2, 0), (2, 1), (1, 0)];
+		assert_eq!(
+			board.verify_solution(&buried),
+			Err(ReplayError::IllegalMove {
+				index: 3,
+				reason: IllegalMove::NotOnFrontier((2, 0))
+			}));
+		let mixed = [(0, 1), (1, 1), (2, 1)];
+		assert_eq!(
+			board.verify_solution(&mixed),
+			Err(ReplayError::IllegalMove {
+				index: 2,
+				reason: IllegalMove::ColorMismatch((2, 1))
+			}));
+		assert_eq!(
+			board.verify_solution(&valid[..4]),
+			Err(ReplayError::IncompleteTriplet));
+		assert_eq!(
+			board.verify_solution(&valid[..3]),
+			Err(ReplayError::Unsolved { remaining: 3 }));
+		assert_eq!(board, Board::parse(tsb).unwrap());
+	}
+
+	#[test]
+	fn remaining_counts_follow_removals_and_undos ()
+	{
+		let mut board = Board::parse("wild = g\n---\nr g g\nr r *\n").unwrap();
+		let red = color_at(&board, (0, 0));
+		let green = color_at(&board, (1, 0));
+		let counts = |board: &Board| {
+			let remaining = board.remaining_by_color();
+			(remaining.get(&red).copied(), remaining.get(&green).copied())
+		};
+		// The wild stone is not counted.
+		assert_eq!(counts(&board), (Some(3), Some(2)));
+		board.play_undoable((0, 1), 0);
+		board.play_undoable((1, 1), 0);
+		assert_eq!(counts(&board), (Some(1), Some(2)));
+		board.play_undoable((0, 0), 0);
+		assert_eq!(counts(&board), (None, Some(2)));
+		board.play_undoable((2, 1), 0);
+		assert_eq!(counts(&board), (None, Some(2)));
+		board.play_undoable((1, 0), 0);
+		assert_eq!(counts(&board), (None, Some(1)));
+		assert!(board.undo());
+		assert!(board.undo());
+		assert!(board.undo());
+		assert_eq!(counts(&board), (Some(1), Some(2)));
+	}
+
+	#[test]
+	fn color_lock_hides_just_played_color ()
+	{
+		let rows = "r r r r r r g g g\n";
+		let mut board = Board::parse(rows).unwrap();
+		let red = color_at(&board, (0, 0));
+		let green = color_at(&board, (6, 0));
+		assert_eq!(board.playable_colors(), vec![red, green]);
+		let tsb = format!("colorlock = true\n---\n{}", rows);
+		let mut locked = Board::parse(&tsb).unwrap();
+		assert_eq!(locked.playable_colors(), vec![red, green]);
+		locked.play([(0, 0), (1, 0), (2, 0)]).unwrap();
+		assert_eq!(locked.playable_colors(), vec![green]);
+		board.play([(0, 0), (1, 0), (2, 0)]).unwrap();
+		assert_eq!(board.playable_colors(), vec![red, green]);
+		let tsb = "colorlock = true\n---\ng g g r r r\nr r r g g g\n";
+		let mut locked = Board::parse(tsb).unwrap();
+		let red = color_at(&locked, (0, 1));
+		let green = color_at(&locked, (0, 0));
+		locked.play([(0, 1), (1, 1), (2, 1)]).unwrap();
+		assert_eq!(locked.playable_colors(), vec![green]);
+		locked.play([(3, 1), (4, 1), (5, 1)]).unwrap();
+		assert_eq!(locked.playable_colors(), vec![red]);
+	}
+
+	#[cfg(feature = "rand")]
+	#[test]
+	fn random_boards_are_solvable ()
+	{
+		for (width, height) in [(1, 3), (3, 1), (3, 3), (5, 3), (3, 5)]
+		{
+			for colors in 1..=4
+			{
+				for seed in 0..8
+				{
+					let random = || Board::random(width, height, colors, seed);
+					let mut board = random();
+					assert_eq!(board, random());
+					assert_eq!(board.width(), width);
+					assert_eq!(board.height(), height);
+					let solution = board.solve();
+					assert!(
+						solution.is_some(),
+						"unsolvable:\n{}",
+						board.to_tsb());
+					assert!(board.is_valid_solution(&solution.unwrap()));
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn goal_clears_corner_and_leaves_the_rest ()
+	{
+		let tsb = "r g b\nr g b\nr g b\n";
+		let mut board = Board::parse(tsb).unwrap();
+		let solution = board.solve_goal(&[(0, 0)]).unwrap();
+		assert_eq!(board, Board::parse(tsb).unwrap());
+		assert_eq!(solution, vec![(0, 2), (0, 1), (0, 0)]);
+		board.play([solution[0], solution[1], solution[2]]).unwrap();
+		assert!(matches!(board.get((0, 0)), Some(AnyStone::None(_))));
+		assert!(!board.is_solved());
+		assert_eq!(board.removable_stones(), 6);
+		// Neither a toggle nor a point beyond the board can be cleared.
+		let mut board = Board::parse("r r r /\n").unwrap();
+		assert_eq!(
+			board.solve_goal(&[(0, 0)]),
+			Some(vec![(0, 0), (1, 0), (2, 0)]));
+		assert_eq!(board.solve_goal(&[(3, 0)]), None);
+		assert_eq!(board.solve_goal(&[(4, 0)]), None);
+	}
+
+	#[test]
+	fn move_limit_prunes_longer_solutions ()
+	{
+		let tsb = "r g b\nr g b\nr g b\n";
+		let mut board = Board::parse(tsb).unwrap();
+		assert_eq!(board.solve_within(8), None);
+		assert_eq!(board.solve_within(9).map(|moves| moves.len()), Some(9));
+		assert_eq!(board, Board::parse(tsb).unwrap());
+		// Clearing the goal takes two triplets, so the search must give up if
+		// it may play only five moves.
+		let goal = [(0, 0), (1, 0)];
+		let is_goal = |board: &Board| goal.iter()
+			.all(|&p| matches!(board.get(p), Some(AnyStone::None(_))));
+		for (limit, expected) in [(5, None), (6, Some(6))]
+		{
+			let mut found = None;
+			let mut on_solution = |moves: &[Point], _|
+			{
+				found = Some(moves.len());
+				true
+			};
+			let mut search = Search::new(&mut on_solution);
+			search.goal = Some(&is_goal);
+			search.move_limit = limit;
+			board.explore(&mut search);
+			assert_eq!(found, expected);
+			assert_eq!(board, Board::parse(tsb).unwrap());
+		}
+	}
+
+	#[test]
+	fn trace_records_each_try_and_undo ()
+	{
+		let tsb = "g r r\nr g g\n";
+		let mut board = Board::parse(tsb).unwrap();
+		let mut out = Vec::new();
+		assert_eq!(board.solve_traced(&mut out).unwrap(), None);
+		assert_eq!(
+			String::from_utf8(out).unwrap(),
+			"try (0, 1) color=0x0\n\
+			undo (0, 1)\n\
+			try (1, 1) color=0x0\n \
+			try (2, 1) color=0x1\n \
+			undo (2, 1)\n\
+			undo (1, 1)\n\
+			try (2, 1) color=0x0\n \
+			try (1, 1) color=0x1\n \
+			undo (1, 1)\n\
+			undo (2, 1)\n");
+		assert_eq!(board, Board::parse(tsb).unwrap());
+		let mut board = Board::parse("r r r\n").unwrap();
+		let mut out = Vec::new();
+		let solution = board.solve_traced(&mut out).unwrap().unwrap();
+		assert_eq!(solution.len(), 3);
+		let trace = String::from_utf8(out).unwrap();
+		assert!(trace.starts_with("try (0, 0) color=0x0\n try "));
+		assert!(trace.contains("\n  try "));
+	}
+
+	#[test]
+	fn frontier_follows_access_direction ()
+	{
+		let rows = "r g g\nr r g\n";
+		let cases = [
+			("bottom", vec![(0, 1), (1, 1), (2, 1)]),
+			("top", vec![(0, 0), (1, 0), (2, 0)]),
+			("left", vec![(0, 0), (0, 1)]),
+			("right", vec![(2, 0), (2, 1)])
+		];
+		for (access, expected) in cases
+		{
+			let tsb = format!("access = {}\n---\n{}", access, rows);
+			let mut board = Board::parse(&tsb).unwrap();
+			let mut frontier = board.legal_moves();
+			frontier.sort();
+			assert_eq!(frontier, expected, "access = {}", access);
+			let solution = board.solve().unwrap();
+			assert!(board.is_valid_solution(&solution));
+		}
+		// From the left, the reds of the first column must be cleared before
+		// the red behind them.
+		let tsb = format!("access = left\n---\n{}", rows);
+		let mut board = Board::parse(&tsb).unwrap();
+		assert_eq!(
+			board.play([(1, 1), (0, 1), (0, 0)]),
+			Err(IllegalMove::NotOnFrontier((1, 1))));
+		assert_eq!(board.play([(0, 1), (0, 0), (1, 1)]), Ok(()));
+	}
+
+	#[test]
+	fn difficulty_ranks_trivial_below_hard ()
+	{
+		let rate = |tsb: &str| {
+			let mut board = Board::parse(tsb).unwrap();
+			let difficulty = board.difficulty();
+			assert_eq!(board, Board::parse(tsb).unwrap());
+			difficulty.rating
+		};
+		let trivial = rate("r r r\n");
+		let easy = rate(SOLVABLE[3]);
+		let hard = rate(SOLVABLE[6]);
+		let fiendish = rate(SOLVABLE[7]);
+		assert!(trivial < 10, "{}", trivial);
+		assert!(fiendish > 60, "{}", fiendish);
+		assert!(trivial < easy && easy < hard && hard < fiendish);
+		let idle = Difficulty::from(SolveStats::default());
+		assert_eq!(idle.rating, 0);
+		assert_eq!(idle.to_string(), "0/100");
+	}
+
+	#[test]
+	fn solving_without_wilds_leaves_them_in_place ()
+	{
+		let tsb = "wild = g\n---\ng g *\nr r r\n";
+		let mut board = Board::parse(tsb).unwrap();
+		assert!(board.solve().is_some());
+		assert_eq!(board.solve_without_wilds(), None);
+		assert_eq!(board, Board::parse(tsb).unwrap());
+		let tsb = "wild = rgb\n---\n* * * r r r g g g b b b\n";
+		let mut board = Board::parse(tsb).unwrap();
+		let solution = board.solve_without_wilds().unwrap();
+		assert_eq!(solution.len(), 9);
+		assert!(solution.iter().all(|&(x, _)| x >= 3));
+		assert_eq!(board, Board::parse(tsb).unwrap());
+	}
+
+	#[test]
+	fn solution_colors_give_roadmap ()
+	{
+		let tsb = "wild = g\n---\nr r *\nr r g\ng r r\n";
+		let mut board = Board::parse(tsb).unwrap();
+		let red = color_at(&board, (0, 0));
+		let green = color_at(&board, (0, 2));
+		let moves = [
+			(1, 2), (2, 2), (1, 1),
+			(0, 2), (2, 1), (2, 0),
+			(0, 1), (0, 0), (1, 0)
+		];
+		assert_eq!(board.verify_solution(&moves), Ok(()));
+		assert_eq!(board.solution_colors(&moves), Ok(vec![red, green, red]));
+		// The wild stone completes the green triplet, and a trailing
+		// incomplete triplet is included.
+		assert_eq!(board.solution_colors(&moves[..4]), Ok(vec![red, green]));
+		assert_eq!(
+			board.solution_colors(&[(0, 0)]),
+			Err(ReplayError::IllegalMove {
+				index: 0,
+				reason: IllegalMove::NotOnFrontier((0, 0))
+			}));
+		assert_eq!(board, Board::parse(tsb).unwrap());
 	}
 }
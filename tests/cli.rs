@@ -0,0 +1,322 @@
+//
+// cli.rs
+// Copyright © 2019-2023, Todd L Smith.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+//    this list of conditions and the following disclaimer.
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+//    this list of conditions and the following disclaimer in the documentation
+//    and/or other materials provided with the distribution.
+// 3. Neither the name of the copyright holder nor the names of its contributors
+//    may be used to endorse or promote products derived from this software
+//    without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+//
+
+//!
+//! ## Command-line interface
+//!
+//! Herein are tests of the `tumblesolve` executable, run as a user would run
+//! it.
+//!
+
+use std::fs::{
+	File,
+	copy,
+	create_dir_all,
+	read,
+	read_to_string,
+	remove_dir_all,
+	write
+};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+
+/// Run the executable with the specified arguments, with standard input
+/// closed. Answer its output.
+fn run (args: &[&str]) -> Output
+{
+	Command::new(env!("CARGO_BIN_EXE_tumblesolve"))
+		.current_dir(env!("CARGO_MANIFEST_DIR"))
+		.args(args)
+		.stdin(Stdio::null())
+		.output()
+		.unwrap()
+}
+
+/// Run the executable with the specified arguments, writing the specified text
+/// to its standard input. Answer its output.
+fn run_with_input (args: &[&str], input: &str) -> Output
+{
+	let mut child = Command::new(env!("CARGO_BIN_EXE_tumblesolve"))
+		.current_dir(env!("CARGO_MANIFEST_DIR"))
+		.args(args)
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.unwrap();
+	child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+	child.wait_with_output().unwrap()
+}
+
+/// Answer a fresh, empty scratch directory with the specified name.
+fn scratch (name: &str) -> PathBuf
+{
+	let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+	let _ = remove_dir_all(&dir);
+	create_dir_all(&dir).unwrap();
+	dir
+}
+
+/// Answer the standard output of the specified process.
+fn stdout (output: &Output) -> String
+{
+	String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+/// Answer the standard error of the specified process.
+fn stderr (output: &Output) -> String
+{
+	String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+#[test]
+fn stats_do_not_corrupt_json ()
+{
+	let output = run(&["--stats", "--json", "examples/board005.tsb"]);
+	assert_eq!(output.status.code(), Some(0));
+	let json =
+		serde_json::from_str::<serde_json::Value>(&stdout(&output)).unwrap();
+	assert_eq!(json["solvable"], true);
+	assert!(stderr(&output).contains("Search statistics:"));
+}
+
+#[test]
+fn unreadable_board_fails ()
+{
+	let output = run(&["examples/no-such-board.tsb"]);
+	assert_eq!(output.status.code(), Some(2));
+	assert!(stderr(&output).starts_with("Error: could not read"));
+}
+
+#[test]
+fn malformed_board_fails ()
+{
+	let output = run(&["examples/board000.tsb"]);
+	assert_eq!(output.status.code(), Some(2));
+	assert!(stderr(&output).starts_with("Error: could not parse the board"));
+}
+
+#[test]
+fn unsolvable_board_fails ()
+{
+	let output = run(&["examples/board003.tsb"]);
+	assert_eq!(output.status.code(), Some(1));
+	assert!(stdout(&output).contains("No solution exists"));
+}
+
+#[test]
+fn malformed_option_fails ()
+{
+	let output = run(&["--origin", "sideways", "examples/board001.tsb"]);
+	assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn dir_summarizes_every_board ()
+{
+	let output = run(&["--dir", "examples"]);
+	assert_eq!(output.status.code(), Some(1));
+	let stdout = stdout(&output);
+	let lines = stdout.lines().collect::<Vec<_>>();
+	assert_eq!(lines.len(), 10);
+	assert!(lines[0].ends_with("board000.tsb: parse error: the board requires \
+		1 move(s), which is not a multiple of three"));
+	assert!(lines[1].ends_with("board001.tsb: solved in 3 turns"));
+	assert!(lines[3].contains("board003.tsb: no solution: "));
+	assert!(lines[5].ends_with("board005.tsb: solved in 21 turns"));
+	assert_eq!(
+		stderr(&output).trim_end(),
+		"Error: could not solve 2 of the boards.");
+}
+
+#[test]
+fn dir_of_solvable_boards_succeeds ()
+{
+	let dir = scratch("dir_of_solvable_boards_succeeds");
+	for name in ["board001.tsb", "board005.tsb"]
+	{
+		copy(format!("examples/{}", name), dir.join(name)).unwrap();
+	}
+	write(dir.join("notes.txt"), "not a board").unwrap();
+	let output = run(&["--dir", dir.to_str().unwrap()]);
+	assert_eq!(output.status.code(), Some(0));
+	assert_eq!(stdout(&output).lines().count(), 2);
+}
+
+#[test]
+fn verify_checks_expected_solution ()
+{
+	let dir = scratch("verify_checks_expected_solution");
+	let board = read_to_string("examples/board001.tsb").unwrap();
+	let valid = dir.join("valid.tsb");
+	write(&valid, format!("{}\nsolution = 0,0 1,0 2,0\n", board)).unwrap();
+	let output = run(&["--verify", valid.to_str().unwrap()]);
+	assert_eq!(output.status.code(), Some(0));
+	assert_eq!(stdout(&output).trim_end(), "Expected solution is valid.");
+	let invalid = dir.join("invalid.tsb");
+	write(&invalid, format!("{}\nsolution = 0,0 1,0 3,0\n", board)).unwrap();
+	let output = run(&["--verify", invalid.to_str().unwrap()]);
+	assert_eq!(output.status.code(), Some(1));
+	assert_eq!(stdout(&output).trim_end(), "Expected solution is invalid.");
+}
+
+#[test]
+fn verify_without_expected_solution ()
+{
+	let output = run(&["--verify", "examples/board005.tsb"]);
+	assert_eq!(output.status.code(), Some(0));
+	assert_eq!(stdout(&output).trim_end(), "Solution exists.");
+	let output = run(&["--verify", "examples/board003.tsb"]);
+	assert_eq!(output.status.code(), Some(1));
+	assert_eq!(stdout(&output).trim_end(), "No solution exists.");
+}
+
+#[test]
+fn batch_prints_every_hint ()
+{
+	let output = run(&["--batch", "--no-color", "examples/board005.tsb"]);
+	assert_eq!(output.status.code(), Some(0));
+	let stdout = stdout(&output);
+	assert_eq!(stdout.matches("Turn #").count(), 21);
+	assert!(!stdout.contains("Press"));
+	assert!(!stdout.contains('\x1b'));
+}
+
+#[test]
+fn auto_replays_every_hint ()
+{
+	let output = run(&["--auto", "0", "examples/board005.tsb"]);
+	assert_eq!(output.status.code(), Some(0));
+	let stdout = stdout(&output);
+	assert_eq!(stdout.matches("Turn #").count(), 21);
+	assert!(!stdout.contains('\x1b'));
+}
+
+#[test]
+fn origin_moves_coordinates ()
+{
+	let first_move = |origin: &str|
+	{
+		let output =
+			run(&["--json", "--origin", origin, "examples/board005.tsb"]);
+		assert_eq!(output.status.code(), Some(0));
+		let json = serde_json::from_str::<serde_json::Value>(&stdout(&output))
+			.unwrap();
+		json["moves"].as_array().unwrap()[0].clone()
+	};
+	let top = first_move("topleft0");
+	let bottom = first_move("bottomleft0");
+	let one = first_move("topleft1");
+	let coordinates = |point: &serde_json::Value| point.as_array().unwrap()
+		.iter()
+		.map(|n| n.as_u64().unwrap())
+		.collect::<Vec<_>>();
+	assert_eq!(coordinates(&top), [0, 6]);
+	assert_eq!(coordinates(&bottom), [0, 0]);
+	assert_eq!(coordinates(&one), [1, 7]);
+}
+
+#[test]
+fn svg_writes_solution_image ()
+{
+	let dir = scratch("svg_writes_solution_image");
+	let svg = dir.join("board005.svg");
+	let output =
+		run(&["--svg", svg.to_str().unwrap(), "examples/board005.tsb"]);
+	assert_eq!(output.status.code(), Some(0));
+	assert!(stdout(&output).is_empty());
+	let image = read_to_string(&svg).unwrap();
+	assert!(image.starts_with("<?xml"));
+	assert!(image.contains("<svg"));
+}
+
+#[test]
+fn board_may_be_piped_through_stdin ()
+{
+	let board = read_to_string("examples/board001.tsb").unwrap();
+	let expected = run(&["examples/board001.tsb"]);
+	assert_eq!(expected.status.code(), Some(0));
+	for args in [&["-"][..], &[]]
+	{
+		let output = run_with_input(args, &board);
+		assert_eq!(output.status.code(), Some(0));
+		assert_eq!(stdout(&output), stdout(&expected));
+	}
+	let output = run_with_input(&["-"], "r r g\n");
+	assert_eq!(output.status.code(), Some(1));
+	assert!(stdout(&output).contains("No solution exists"));
+}
+
+#[test]
+fn dir_continues_past_failures ()
+{
+	let dir = scratch("dir_continues_past_failures");
+	write(dir.join("a.tsb"), "r r g\n").unwrap();
+	write(dir.join("b.tsb"), "r r\n").unwrap();
+	write(dir.join("c.tsb"), "r r r\n").unwrap();
+	let output = run(&["--dir", dir.to_str().unwrap()]);
+	assert_eq!(output.status.code(), Some(1));
+	let stdout = stdout(&output);
+	let lines = stdout.lines().collect::<Vec<_>>();
+	assert_eq!(lines.len(), 3);
+	assert!(lines[0].contains("a.tsb: no solution: "));
+	assert!(lines[1].contains("b.tsb: parse error: "));
+	assert!(lines[2].ends_with("c.tsb: solved in 3 turns"));
+	assert_eq!(
+		stderr(&output).trim_end(),
+		"Error: could not solve 2 of the boards.");
+}
+
+#[test]
+fn gzipped_board_solves_like_plain_board ()
+{
+	let dir = scratch("gzipped_board_solves_like_plain_board");
+	let compressed = dir.join("board005.tsb.gz");
+	let mut encoder = GzEncoder::new(
+		File::create(&compressed).unwrap(),
+		Compression::default());
+	encoder.write_all(&read("examples/board005.tsb").unwrap()).unwrap();
+	encoder.finish().unwrap();
+	let expected = run(&["examples/board005.tsb"]);
+	let output = run(&[compressed.to_str().unwrap()]);
+	assert_eq!(output.status.code(), Some(0));
+	assert_eq!(stdout(&output), stdout(&expected));
+	let output = run(&["--dir", dir.to_str().unwrap()]);
+	assert_eq!(output.status.code(), Some(0));
+	assert!(stdout(&output).ends_with("board005.tsb.gz: solved in 21 turns\n"));
+	// A plain file masquerading as a compressed one is rejected.
+	let fake = dir.join("fake.tsb.gz");
+	copy("examples/board005.tsb", &fake).unwrap();
+	let output = run(&[fake.to_str().unwrap()]);
+	assert_eq!(output.status.code(), Some(2));
+	assert!(stderr(&output).starts_with("Error: could not decompress"));
+}